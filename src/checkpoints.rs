@@ -0,0 +1,99 @@
+use std::{collections::HashMap, time::Instant};
+
+/// The screens of every process at a point in time, saved under a name so
+/// that output can be compared before and after a change.
+pub(crate) struct Checkpoint {
+    pub(crate) name: String,
+
+    pub(crate) created_at: Instant,
+
+    /// The lines of the screen of each process, by the name of the process.
+    screens: HashMap<String, Vec<String>>,
+}
+
+impl Checkpoint {
+    pub(crate) fn new(name: String, screens: HashMap<String, Vec<String>>) -> Self {
+        Self {
+            name,
+            created_at: Instant::now(),
+            screens,
+        }
+    }
+
+    /// The screen of a process when the checkpoint was made, or `None` if the
+    /// process didn't exist then.
+    pub(crate) fn screen(&self, process_name: &str) -> Option<&[String]> {
+        self.screens.get(process_name).map(Vec::as_slice)
+    }
+}
+
+/// Choosing a checkpoint, and then comparing the focused process with it.
+pub(crate) struct CheckpointBrowser {
+    pub(crate) selected_index: usize,
+
+    /// Whether the comparison with the selected checkpoint is being shown,
+    /// rather than the list of checkpoints.
+    pub(crate) showing_diff: bool,
+}
+
+impl CheckpointBrowser {
+    /// Start with the most recent checkpoint selected.
+    pub(crate) fn new(checkpoint_count: usize) -> Self {
+        Self {
+            selected_index: checkpoint_count.saturating_sub(1),
+            showing_diff: false,
+        }
+    }
+
+    pub(crate) fn move_selection_up(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+
+    pub(crate) fn move_selection_down(&mut self, checkpoint_count: usize) {
+        if self.selected_index + 1 < checkpoint_count {
+            self.selected_index += 1;
+        }
+    }
+}
+
+pub(crate) enum DiffLine<'a> {
+    Unchanged(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Compare two screens line by line, using the longest common subsequence of
+/// lines. Screens are small, so the quadratic cost doesn't matter.
+pub(crate) fn diff_lines<'a>(before: &'a [String], after: &'a [String]) -> Vec<DiffLine<'a>> {
+    // The length of the longest common subsequence of each pair of suffixes.
+    let mut lengths = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for before_index in (0..before.len()).rev() {
+        for after_index in (0..after.len()).rev() {
+            lengths[before_index][after_index] = if before[before_index] == after[after_index] {
+                lengths[before_index + 1][after_index + 1] + 1
+            } else {
+                lengths[before_index + 1][after_index].max(lengths[before_index][after_index + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut before_index, mut after_index) = (0, 0);
+    while before_index < before.len() && after_index < after.len() {
+        if before[before_index] == after[after_index] {
+            diff.push(DiffLine::Unchanged(&before[before_index]));
+            before_index += 1;
+            after_index += 1;
+        } else if lengths[before_index + 1][after_index] >= lengths[before_index][after_index + 1] {
+            diff.push(DiffLine::Removed(&before[before_index]));
+            before_index += 1;
+        } else {
+            diff.push(DiffLine::Added(&after[after_index]));
+            after_index += 1;
+        }
+    }
+    diff.extend(before[before_index..].iter().map(|line| DiffLine::Removed(line)));
+    diff.extend(after[after_index..].iter().map(|line| DiffLine::Added(line)));
+
+    diff
+}
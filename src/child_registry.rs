@@ -0,0 +1,272 @@
+use std::{fs, io, path::{Path, PathBuf}};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{processes::{ProcessStatus, Processes}, signals};
+
+/// Records the process IDs of running children in a runtime file, so that if
+/// Mintaka itself crashes, `mintaka recover` can stop the children it left
+/// behind.
+pub(crate) struct ChildRegistry {
+    /// The path of the registry, or `None` if the runtime directory can't be
+    /// used safely.
+    path: Option<PathBuf>,
+
+    /// The children left behind by a previous run that are still running.
+    /// These are kept in the registry so that they can still be recovered
+    /// once this run has exited.
+    orphans: Vec<ChildRecord>,
+
+    last_children: Option<Vec<(String, u32)>>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RegistryFile {
+    supervisor_pid: u32,
+
+    supervisor_start_time: Option<String>,
+
+    #[serde(default)]
+    children: Vec<ChildRecord>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct ChildRecord {
+    name: String,
+    pid: u32,
+
+    /// When the child started, so that an unrelated process that reuses the
+    /// process ID, such as after a reboot, isn't mistaken for the child.
+    start_time: Option<String>,
+}
+
+impl ChildRecord {
+    fn is_running(&self) -> bool {
+        is_recorded_process_running(self.pid, self.start_time.as_deref())
+    }
+
+    fn description(&self) -> String {
+        format!("{} (process ID {})", self.name, self.pid)
+    }
+}
+
+impl ChildRegistry {
+    pub(crate) fn new(config_path: &Path) -> Self {
+        let path = registry_path(config_path).ok();
+        let orphans = path.as_deref()
+            .and_then(read_registry)
+            .filter(|registry_file| !is_supervisor_running(registry_file))
+            .map(|registry_file| {
+                registry_file.children.into_iter()
+                    .filter(ChildRecord::is_running)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            orphans,
+            last_children: None,
+        }
+    }
+
+    /// Describe the children left behind by a previous run of Mintaka with
+    /// the same config that are still running.
+    pub(crate) fn orphaned_children(&self) -> Vec<String> {
+        self.orphans.iter().map(ChildRecord::description).collect()
+    }
+
+    /// Write the process IDs of the running children if they've changed since
+    /// the last update. Failing to write is ignored, since the registry is
+    /// only needed for recovering from crashes.
+    pub(crate) fn update(&mut self, processes: &Processes) {
+        let children: Vec<(String, u32)> = processes.processes()
+            .iter()
+            .filter(|process| !matches!(process.status(), ProcessStatus::Exited { .. }))
+            .filter_map(|process| Some((process.name().to_owned(), process.process_id()?)))
+            .collect();
+
+        if self.last_children.as_ref() == Some(&children) {
+            return;
+        }
+
+        let child_records = children.iter()
+            .map(|(name, pid)| ChildRecord {
+                name: name.clone(),
+                pid: *pid,
+                start_time: process_start_time(*pid),
+            });
+        self.write(self.orphans.iter().cloned().chain(child_records).collect());
+
+        self.last_children = Some(children);
+    }
+
+    /// Remove the registry when Mintaka exits normally, keeping any children
+    /// left behind by a previous run so that they can still be recovered.
+    pub(crate) fn remove(&self) {
+        if self.orphans.is_empty() {
+            if let Some(path) = &self.path {
+                let _ = fs::remove_file(path);
+            }
+        } else {
+            self.write(self.orphans.clone());
+        }
+    }
+
+    fn write(&self, children: Vec<ChildRecord>) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let supervisor_pid = std::process::id();
+        let registry_file = RegistryFile {
+            supervisor_pid,
+            supervisor_start_time: process_start_time(supervisor_pid),
+            children,
+        };
+        if let Ok(registry_str) = toml::to_string(&registry_file) {
+            let _ = fs::write(path, registry_str);
+        }
+    }
+}
+
+/// Stop the children left behind by a previous run of Mintaka with the same
+/// config, returning a description of what was done.
+pub(crate) fn recover(config_path: &Path) -> Result<Vec<String>, String> {
+    let path = registry_path(config_path).map_err(|error| error.to_string())?;
+    let Some(registry_file) = read_registry(&path) else {
+        return Ok(Vec::new());
+    };
+
+    if is_supervisor_running(&registry_file) {
+        return Err(format!(
+            "Mintaka is still running with process ID {}",
+            registry_file.supervisor_pid,
+        ));
+    }
+
+    let mut report = Vec::new();
+    for child in &registry_file.children {
+        if !child.is_running() {
+            continue;
+        }
+
+        // Children are started in their own session, so signalling the process
+        // group also stops any processes that they started.
+        if signals::send_signal(child.pid, "TERM") {
+            report.push(format!("stopped {}", child.description()));
+        } else {
+            report.push(format!("failed to stop {}", child.description()));
+        }
+    }
+
+    let _ = fs::remove_file(&path);
+
+    Ok(report)
+}
+
+fn is_supervisor_running(registry_file: &RegistryFile) -> bool {
+    is_recorded_process_running(registry_file.supervisor_pid, registry_file.supervisor_start_time.as_deref())
+}
+
+/// Whether the process recorded with the given process ID and start time is
+/// still running. When the start time wasn't recorded, the process is assumed
+/// to have exited, since the process ID alone may belong to another process.
+fn is_recorded_process_running(pid: u32, start_time: Option<&str>) -> bool {
+    start_time.is_some_and(|start_time| process_start_time(pid).as_deref() == Some(start_time))
+}
+
+/// When a process started, or `None` if it isn't running. On Linux, this is
+/// the start time from `/proc`, which counts from boot, so the boot ID is
+/// included to tell apart processes from before a reboot.
+#[cfg(target_os = "linux")]
+fn process_start_time(pid: u32) -> Option<String> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name is in parentheses and may contain spaces, so fields are
+    // counted from after it: the start time is the 22nd field of the line.
+    let (_, fields) = stat.rsplit_once(')')?;
+    let start_time = fields.split_whitespace().nth(19)?;
+    let boot_id = fs::read_to_string("/proc/sys/kernel/random/boot_id").unwrap_or_default();
+    Some(format!("{}:{start_time}", boot_id.trim()))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_start_time(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .arg("-o")
+        .arg("lstart=")
+        .arg("-p")
+        .arg(pid.to_string())
+        .output()
+        .ok()?;
+    let start_time = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    (output.status.success() && !start_time.is_empty()).then_some(start_time)
+}
+
+/// Start times can't be found on other platforms, so no process is ever
+/// treated as left behind.
+#[cfg(not(unix))]
+fn process_start_time(_pid: u32) -> Option<String> {
+    None
+}
+
+fn read_registry(path: &Path) -> Option<RegistryFile> {
+    let registry_str = fs::read_to_string(path).ok()?;
+    toml::from_str(&registry_str).ok()
+}
+
+fn registry_path(config_path: &Path) -> io::Result<PathBuf> {
+    runtime_file_path(config_path, "toml")
+}
+
+/// Files for an instance of Mintaka are kept in the runtime directory, with a
+/// name derived from the path of the config so that separate projects don't
+/// interfere. The directory is created if it doesn't exist.
+pub(crate) fn runtime_file_path(config_path: &Path, extension: &str) -> io::Result<PathBuf> {
+    let config_path = config_path.canonicalize()
+        .unwrap_or_else(|_| config_path.to_owned());
+    let file_name_stem: String = config_path.to_string_lossy()
+        .chars()
+        .map(|char| if char.is_alphanumeric() { char } else { '-' })
+        .collect();
+
+    let runtime_directory = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("mintaka");
+    create_private_directory(&runtime_directory)?;
+
+    Ok(runtime_directory.join(format!("{file_name_stem}.{extension}")))
+}
+
+/// Create a directory that only the current user can access, or check that an
+/// existing directory can only be accessed by the current user. Without
+/// `XDG_RUNTIME_DIR`, the runtime directory is in the shared temporary
+/// directory, where another user could otherwise create it first and plant a
+/// registry or socket in it.
+#[cfg(unix)]
+fn create_private_directory(directory: &Path) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(directory)?;
+
+    let metadata = fs::symlink_metadata(directory)?;
+    let is_private = metadata.is_dir()
+        && metadata.uid() == unsafe { libc::getuid() }
+        && metadata.permissions().mode() & 0o077 == 0;
+    if is_private {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} must be a directory that only the current user can access", directory.display()),
+        ))
+    }
+}
+
+#[cfg(not(unix))]
+fn create_private_directory(directory: &Path) -> io::Result<()> {
+    fs::create_dir_all(directory)
+}
@@ -1,16 +1,148 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use clap::Parser;
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
 
 use crate::config::MintakaConfig;
 
 #[derive(Parser)]
-struct CliArgs {
+pub(crate) struct CliArgs {
+    /// The path to the configuration file. Required unless running
+    /// `self-check`.
     #[arg(long, short)]
-    config: PathBuf,
+    config: Option<PathBuf>,
+
+    /// Write trace events for diagnosing performance to the given file.
+    #[arg(long)]
+    pub(crate) trace: Option<PathBuf>,
+
+    /// Serve the HTTP API on the given address, such as `127.0.0.1:7700`,
+    /// instead of the address in the config. The config must still set the
+    /// token of the HTTP API.
+    #[arg(long)]
+    pub(crate) listen: Option<String>,
+
+    #[command(subcommand)]
+    pub(crate) command: Option<CliCommand>,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum CliCommand {
+    /// Stop processes left running by a previous run of Mintaka with the same
+    /// config that didn't exit cleanly.
+    Recover,
+
+    /// Run the processes. This is the default command.
+    Run {
+        /// Run without the UI, printing the output of each process to stdout,
+        /// until every process has exited or reached a success or error state.
+        /// Exits with a non-zero code if any process failed.
+        #[arg(long)]
+        ci: bool,
+
+        /// Whether to color the prefixes and keep the colors of the output of
+        /// processes when running with `--ci`. By default, colors are used
+        /// when stdout is a terminal and `NO_COLOR` isn't set.
+        #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+        color: ColorChoice,
+
+        /// The prefix of each line of output when running with `--ci`, where
+        /// `{name}`, `{pid}` and `{time}` are replaced with the name of the
+        /// process, its process ID and the current UTC time.
+        #[arg(long, default_value = "{name} |")]
+        prefix_format: String,
+    },
+
+    /// Control the instance of Mintaka that is already running with the same
+    /// config.
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+
+    /// Check the config for problems and print the planned start phases
+    /// without starting any processes. Exits with a non-zero code if there are
+    /// any problems.
+    Check,
+
+    /// Send a test notification using each notification backend in the
+    /// config, and report whether each worked.
+    NotifyTest,
+
+    /// Report the version of Mintaka, and which features are supported by the
+    /// current terminal.
+    SelfCheck {
+        /// Check whether a newer version of Mintaka has been released.
+        #[arg(long)]
+        check_for_updates: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum ColorChoice {
+    Auto,
+    Always,
+    Never,
 }
 
-pub(crate) fn load_config() -> Result<MintakaConfig, crate::config::ConfigError> {
+impl ColorChoice {
+    pub(crate) fn use_color(self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                std::io::IsTerminal::is_terminal(&std::io::stdout()) && std::env::var_os("NO_COLOR").is_none()
+            },
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub(crate) enum CtlCommand {
+    /// Restart a process.
+    Restart {
+        name: String,
+    },
+
+    /// Stop a process.
+    Stop {
+        name: String,
+    },
+
+    /// Print the status of every process as JSON.
+    Status,
+}
+
+impl CtlCommand {
+    /// The request sent over the control socket.
+    pub(crate) fn request_line(&self) -> String {
+        match self {
+            CtlCommand::Restart { name } => format!("restart {name}"),
+            CtlCommand::Stop { name } => format!("stop {name}"),
+            CtlCommand::Status => "status".to_owned(),
+        }
+    }
+}
+
+impl CliArgs {
+    pub(crate) fn config_path(&self) -> &Path {
+        // The config is checked to be set when parsing the arguments.
+        self.config.as_deref().unwrap()
+    }
+
+    pub(crate) fn load_config(&self) -> Result<MintakaConfig, crate::config::ConfigError> {
+        super::config::load_config(self.config_path())
+    }
+}
+
+pub(crate) fn parse_args() -> CliArgs {
     let args = CliArgs::parse();
-    super::config::load_config(&args.config)
+
+    let needs_config = !matches!(args.command, Some(CliCommand::SelfCheck { .. }));
+    if needs_config && args.config.is_none() {
+        CliArgs::command()
+            .error(ErrorKind::MissingRequiredArgument, "--config is required")
+            .exit();
+    }
+
+    args
 }
@@ -0,0 +1,10 @@
+use termwiz::{escape::{osc::Selection, OperatingSystemCommand}, surface::Change, terminal::Terminal};
+
+/// Copy text to the clipboard of the host terminal using OSC 52. When running
+/// inside tmux, this requires the tmux option `set-clipboard` to be enabled.
+pub(crate) fn copy_to_clipboard(text: &str, terminal: &mut impl Terminal) -> termwiz::Result<()> {
+    let escape = OperatingSystemCommand::SetSelection(Selection::CLIPBOARD, text.to_owned());
+    terminal.render(&[Change::Text(escape.to_string())])?;
+    terminal.flush()?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::{collections::VecDeque, sync::{Arc, Mutex}, time::Instant};
+
+use crate::output_lines::OutputLine;
+
+/// The most lines kept in the combined log, so that memory use stays bounded
+/// however long processes run.
+const MAX_LINES: usize = 10000;
+
+/// The lines of output of every process, in the order that they were
+/// received, for showing the output of all processes together.
+#[derive(Clone)]
+pub(crate) struct CombinedLog {
+    lines: Arc<Mutex<VecDeque<CombinedLogLine>>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct CombinedLogLine {
+    pub(crate) received_at: Instant,
+    pub(crate) process_name: String,
+    pub(crate) text: String,
+}
+
+impl CombinedLog {
+    pub(crate) fn new() -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub(crate) fn push(&self, process_name: &str, lines: &[OutputLine]) {
+        let received_at = Instant::now();
+        let mut combined_lines = self.lines.lock().unwrap();
+        for line in lines {
+            if combined_lines.len() >= MAX_LINES {
+                combined_lines.pop_front();
+            }
+            combined_lines.push_back(CombinedLogLine {
+                received_at,
+                process_name: process_name.to_owned(),
+                text: line.text.clone(),
+            });
+        }
+    }
+
+    /// The most recent lines, oldest first.
+    pub(crate) fn last_lines(&self, count: usize) -> Vec<CombinedLogLine> {
+        let combined_lines = self.lines.lock().unwrap();
+        let skipped_line_count = combined_lines.len().saturating_sub(count);
+        combined_lines.iter()
+            .skip(skipped_line_count)
+            .cloned()
+            .collect()
+    }
+}
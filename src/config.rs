@@ -1,16 +1,145 @@
-use std::{fs::OpenOptions, io::Read, path::{Path, PathBuf}};
+use std::{collections::HashMap, fs::OpenOptions, io::Read, path::{Path, PathBuf}, process::{Command, ExitStatus, Stdio}, time::Duration};
 
 use regex::Regex;
 use serde::Deserialize;
+use termwiz::color::RgbColor;
 
-use crate::process_statuses::ProcessStatusAnalyzer;
+use crate::process_statuses::{JestAnalyzer, PatternAnalyzer, ProcessStatusAnalyzer};
 
 #[derive(Deserialize)]
 pub(crate) struct MintakaConfig {
     pub(crate) processes: Vec<ProcessConfig>,
+
+    #[serde(default)]
+    pub(crate) status_user_var: bool,
+
+    confirm_quit: Option<bool>,
+
+    mouse: Option<bool>,
+
+    process_list_max_width: Option<usize>,
+
+    #[serde(default)]
+    pub(crate) process_list_git_branches: bool,
+
+    pub(crate) http_api: Option<HttpApiConfig>,
+
+    pub(crate) livereload: Option<LivereloadConfig>,
+
+    #[serde(default)]
+    pub(crate) keybindings: HashMap<String, String>,
+
+    pub(crate) profiler: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub(crate) redactions: Vec<String>,
+
+    #[serde(default)]
+    pub(crate) theme: HashMap<String, String>,
+
+    pub(crate) locale: Option<String>,
+
+    pub(crate) status_file: Option<PathBuf>,
+
+    pub(crate) notifications: Option<NotificationsConfig>,
+
+    #[serde(default)]
+    pub(crate) secret_warnings: bool,
+
+    #[serde(default)]
+    pub(crate) startup_summary: bool,
+
+    stop_timeout: Option<f64>,
+
+    scrollback_lines: Option<usize>,
+}
+
+impl MintakaConfig {
+    pub(crate) fn confirm_quit(&self) -> bool {
+        self.confirm_quit.unwrap_or(true)
+    }
+
+    pub(crate) fn mouse(&self) -> bool {
+        self.mouse.unwrap_or(false)
+    }
+
+    pub(crate) fn process_list_max_width(&self) -> usize {
+        self.process_list_max_width.unwrap_or(40)
+    }
+
+    pub(crate) fn profiler_command(&self) -> Vec<String> {
+        self.profiler.clone().unwrap_or_else(|| {
+            ["perf", "record", "-g", "-p", "{pid}", "--", "sleep", "30"]
+                .map(str::to_owned)
+                .to_vec()
+        })
+    }
 }
 
 #[derive(Clone, Deserialize)]
+pub(crate) struct HttpApiConfig {
+    pub(crate) listen: String,
+
+    pub(crate) token: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct LivereloadConfig {
+    pub(crate) listen: String,
+}
+
+/// The notification backends to use, where each backend is enabled by
+/// including its table.
+#[derive(Deserialize)]
+pub(crate) struct NotificationsConfig {
+    pub(crate) status_bar: Option<NotificationBackendConfig>,
+
+    pub(crate) desktop: Option<NotificationBackendConfig>,
+
+    pub(crate) bell: Option<NotificationBackendConfig>,
+
+    pub(crate) webhook: Option<NotificationBackendConfig>,
+
+    pub(crate) command: Option<NotificationBackendConfig>,
+}
+
+#[derive(Clone, Deserialize)]
+pub(crate) struct NotificationBackendConfig {
+    /// The URL to POST notifications to, for the webhook backend.
+    pub(crate) url: Option<String>,
+
+    /// The command to run, for the command backend.
+    pub(crate) command: Option<Vec<String>>,
+
+    events: Option<Vec<NotificationEvent>>,
+}
+
+impl NotificationBackendConfig {
+    pub(crate) fn sends(&self, event: NotificationEvent) -> bool {
+        self.events.as_ref().map_or(true, |events| events.contains(&event))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum NotificationEvent {
+    /// A process started failing.
+    Failure,
+
+    /// A process succeeded after failing.
+    Recovery,
+}
+
+impl NotificationEvent {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            NotificationEvent::Failure => "failure",
+            NotificationEvent::Recovery => "recovery",
+        }
+    }
+}
+
+#[derive(Clone, Default, Deserialize)]
 pub(crate) struct ProcessConfig {
     pub(crate) command: Vec<String>,
 
@@ -28,22 +157,307 @@ pub(crate) struct ProcessConfig {
     success_regex: Option<String>,
 
     error_regex: Option<String>,
+
+    watcher: Option<bool>,
+
+    pub(crate) sandbox: Option<SandboxConfig>,
+
+    pub(crate) background: Option<String>,
+
+    pub(crate) color: Option<String>,
+
+    pub(crate) group: Option<String>,
+
+    #[serde(default)]
+    pub(crate) quick_keys: HashMap<String, String>,
+
+    #[serde(default)]
+    pub(crate) collapse_carriage_returns: bool,
+
+    #[serde(default)]
+    pub(crate) highlights: HashMap<String, String>,
+
+    pub(crate) environment: Option<EnvironmentConfig>,
+
+    pub(crate) url: Option<String>,
+
+    publish_regex: Option<String>,
+
+    pub(crate) on_ready: Option<Vec<String>>,
+
+    pub(crate) on_error: Option<Vec<String>>,
+
+    pub(crate) on_success: Option<Vec<String>>,
+
+    pub(crate) on_exit: Option<Vec<String>>,
+
+    pub(crate) ready_check: Option<ReadyCheckConfig>,
+
+    pub(crate) stop_signal: Option<String>,
+
+    stop_timeout: Option<f64>,
+
+    #[serde(default)]
+    pub(crate) livereload: bool,
+
+    clear_on_restart: Option<bool>,
+
+    autofocus_priority: Option<u32>,
+
+    startup_grace_ms: Option<u64>,
+
+    notify: Option<Vec<NotificationEvent>>,
+
+    scrollback_lines: Option<usize>,
+
+    #[serde(default)]
+    pub(crate) readonly: bool,
+
+    #[serde(default)]
+    pub(crate) watch: Vec<String>,
+
+    restart_policy: Option<RestartPolicy>,
+
+    max_restarts: Option<u32>,
+
+    /// Environment variables set in addition to the environment of Mintaka,
+    /// such as when the process was cloned with a different port.
+    #[serde(skip)]
+    pub(crate) extra_environment_variables: Vec<(String, String)>,
+
+    /// Where the process was defined, if it was loaded from a config file.
+    #[serde(skip)]
+    pub(crate) source: Option<ConfigSource>,
+}
+
+/// The text that defined a process in a config file.
+#[derive(Clone)]
+pub(crate) struct ConfigSource {
+    pub(crate) path: PathBuf,
+
+    /// The line number of the first line of the text, starting from 1.
+    pub(crate) first_line_number: usize,
+
+    pub(crate) text: String,
+}
+
+#[derive(Clone, Deserialize)]
+pub(crate) struct SandboxConfig {
+    network: Option<bool>,
+
+    #[serde(default)]
+    pub(crate) read_only_paths: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum EnvironmentConfig {
+    LoginShell,
+    InteractiveShell,
+    Direnv,
+}
+
+/// When a process is restarted after it exits.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl RestartPolicy {
+    pub(crate) fn restarts_after(self, is_failure: bool) -> bool {
+        match self {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => is_failure,
+            RestartPolicy::Always => true,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ReadyCheckConfig {
+    Tcp(String),
+    Http(String),
+}
+
+impl SandboxConfig {
+    pub(crate) fn network(&self) -> bool {
+        self.network.unwrap_or(true)
+    }
 }
 
 impl ProcessConfig {
+    /// A process that is started by Mintaka itself, rather than being
+    /// described in the config.
+    pub(crate) fn transient(name: String, command: Vec<String>) -> Self {
+        Self {
+            command,
+            name: Some(name),
+            ..Self::default()
+        }
+    }
+
+    /// A copy of the process that is started by Mintaka itself, with extra
+    /// arguments and environment variables.
+    pub(crate) fn cloned(
+        &self,
+        name: String,
+        extra_arguments: Vec<String>,
+        extra_environment_variables: Vec<(String, String)>,
+    ) -> Self {
+        let mut command = self.command.clone();
+        command.extend(extra_arguments);
+        let mut environment_variables = self.extra_environment_variables.clone();
+        environment_variables.extend(extra_environment_variables);
+        Self {
+            command,
+            name: Some(name),
+            // Nothing is started after a clone, and it starts straight away.
+            after: None,
+            autostart: None,
+            extra_environment_variables: environment_variables,
+            source: None,
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn name(&self) -> String {
+        self.name.clone()
+            .unwrap_or_else(|| self.command.join(" "))
+    }
+
+    /// Describe any regexes in the config that fail to compile.
+    pub(crate) fn regex_errors(&self) -> Vec<String> {
+        [
+            ("success_regex", &self.success_regex),
+            ("error_regex", &self.error_regex),
+            ("publish_regex", &self.publish_regex),
+        ]
+            .into_iter()
+            .filter_map(|(key, regex)| {
+                let error = Regex::new(regex.as_ref()?).err()?;
+                Some(format!("{key} is invalid: {error}"))
+            })
+            .collect()
+    }
+
     pub(crate) fn process_status_analyzer(&self) -> ProcessStatusAnalyzer {
         match self.process_type.as_ref() {
-            None => ProcessStatusAnalyzer {
+            // The status of a process with only a ready check isn't affected by
+            // its output.
+            None if self.ready_check.is_some() && self.success_regex.is_none() && self.error_regex.is_none() => {
+                ProcessStatusAnalyzer::Ignore
+            },
+            None => ProcessStatusAnalyzer::Regexes {
                 success_regex: self.success_regex.as_ref().map(|regex| Regex::new(regex).unwrap()),
                 error_regex: self.error_regex.as_ref().map(|regex| Regex::new(regex).unwrap()),
             },
-            Some(process_type) => ProcessStatusAnalyzer {
-                success_regex: process_type.success_regex(),
-                error_regex: process_type.error_regex(),
-            }
+            Some(ProcessTypeConfig::TscWatch) => ProcessStatusAnalyzer::Regexes {
+                success_regex: None,
+                error_regex: Some(TSC_WATCH_ERROR_REGEX.clone()),
+            },
+            Some(ProcessTypeConfig::Jest) => ProcessStatusAnalyzer::Jest(JestAnalyzer::new()),
+            Some(ProcessTypeConfig::Webpack) => ProcessStatusAnalyzer::Patterns(PatternAnalyzer::webpack()),
+            Some(ProcessTypeConfig::Vite) => ProcessStatusAnalyzer::Patterns(PatternAnalyzer::vite()),
         }
     }
 
+    pub(crate) fn publish_regex(&self) -> Option<Regex> {
+        self.publish_regex.as_ref().map(|regex| Regex::new(regex).unwrap())
+    }
+
+    /// How long to wait after asking the process to stop before forcibly
+    /// killing it, if at all.
+    pub(crate) fn stop_timeout(&self) -> Option<Duration> {
+        self.stop_timeout
+            .and_then(|stop_timeout| Duration::try_from_secs_f64(stop_timeout).ok())
+    }
+
+    /// How long after starting that errors are ignored by autofocus and the
+    /// status user var.
+    pub(crate) fn startup_grace(&self) -> Duration {
+        Duration::from_millis(self.startup_grace_ms.unwrap_or(0))
+    }
+
+    /// Whether to send notifications about the given event for the process.
+    pub(crate) fn notifies(&self, event: NotificationEvent) -> bool {
+        self.notify.as_ref().map_or(true, |events| events.contains(&event))
+    }
+
+    /// How many lines of output that have scrolled off the screen to keep.
+    pub(crate) fn scrollback_lines(&self) -> usize {
+        self.scrollback_lines.unwrap_or(3500)
+    }
+
+    /// Describe the problem with `stop_timeout`, if any.
+    pub(crate) fn stop_timeout_problem(&self) -> Option<String> {
+        let stop_timeout = self.stop_timeout?;
+        if Duration::try_from_secs_f64(stop_timeout).is_ok() {
+            None
+        } else {
+            Some(format!("stop_timeout {stop_timeout} is not a valid number of seconds"))
+        }
+    }
+
+    pub(crate) fn quick_key_input(&self, key: char) -> Option<&str> {
+        self.quick_keys.iter()
+            .find(|(quick_key, _input)| quick_key.chars().eq([key]))
+            .map(|(_quick_key, input)| input.as_str())
+    }
+
+    pub(crate) fn background_color(&self) -> Option<RgbColor> {
+        self.background.as_ref()
+            .and_then(|background| RgbColor::from_named_or_rgb_string(background))
+    }
+
+    /// The accent color used to pick out the process in the UI.
+    pub(crate) fn accent_color(&self) -> Option<RgbColor> {
+        self.color.as_ref()
+            .and_then(|color| RgbColor::from_named_or_rgb_string(color))
+    }
+
+    /// Whether the status of the process is determined from its output or a
+    /// ready check, as opposed to the process always being "Running" until it
+    /// exits.
+    pub(crate) fn has_status_analysis(&self) -> bool {
+        self.process_type.is_some() || self.success_regex.is_some() || self.error_regex.is_some() || self.ready_check.is_some()
+    }
+
+    /// Whether the process is expected to keep running, such as `tsc --watch`,
+    /// so exiting is a failure even with an exit code of zero. Unless set in
+    /// the config, processes with status analysis are watchers, except for
+    /// processes that others wait for, which are usually steps that finish,
+    /// such as a build.
+    pub(crate) fn is_watcher(&self, is_waited_for: bool) -> bool {
+        self.watcher.unwrap_or_else(|| {
+            !is_waited_for && (self.process_type.is_some() || self.success_regex.is_some() || self.error_regex.is_some())
+        })
+    }
+
+    pub(crate) fn clear_on_restart(&self) -> bool {
+        self.clear_on_restart.unwrap_or(true)
+    }
+
+    pub(crate) fn restart_policy(&self) -> RestartPolicy {
+        self.restart_policy.unwrap_or_default()
+    }
+
+    /// How many times in a row the process is restarted after exiting soon
+    /// after starting before giving up.
+    pub(crate) fn max_restarts(&self) -> u32 {
+        self.max_restarts.unwrap_or(5)
+    }
+
+    /// Lower numbers are higher priorities. Processes without a priority have
+    /// a lower priority than any process with one.
+    pub(crate) fn autofocus_priority(&self) -> u32 {
+        self.autofocus_priority.unwrap_or(u32::MAX)
+    }
+
     pub(crate) fn autostart(&self) -> bool {
         match self.autostart {
             None => self.after.is_none(),
@@ -56,16 +470,9 @@ impl ProcessConfig {
 #[serde(rename_all = "kebab-case")]
 enum ProcessTypeConfig {
     TscWatch,
-}
-
-impl ProcessTypeConfig {
-    fn success_regex(&self) -> Option<Regex> {
-        None
-    }
-
-    fn error_regex(&self) -> Option<Regex> {
-        Some(TSC_WATCH_ERROR_REGEX.clone())
-    }
+    Jest,
+    Webpack,
+    Vite,
 }
 
 lazy_static::lazy_static! {
@@ -80,6 +487,37 @@ pub(crate) enum ConfigError {
     FileReadFailed(std::io::Error),
 
     DeserializationFailed(toml::de::Error),
+
+    ConfigCommandEmpty,
+
+    ConfigCommandFailed(std::io::Error),
+
+    ConfigCommandUnsuccessful { status: ExitStatus, stderr: String },
+
+    ConfigCommandOutputInvalid(std::string::FromUtf8Error),
+
+    ConfigCommandConflict(String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::FileOpenFailed(error) => write!(f, "failed to open config file: {error}"),
+            ConfigError::FileReadFailed(error) => write!(f, "failed to read config file: {error}"),
+            ConfigError::DeserializationFailed(error) => write!(f, "failed to parse config file: {error}"),
+            ConfigError::ConfigCommandEmpty => write!(f, "config_command is empty"),
+            ConfigError::ConfigCommandFailed(error) => write!(f, "failed to run config_command: {error}"),
+            ConfigError::ConfigCommandUnsuccessful { status, stderr } => {
+                write!(f, "config_command failed with {status}")?;
+                if !stderr.trim().is_empty() {
+                    write!(f, ": {}", stderr.trim())?;
+                }
+                Ok(())
+            },
+            ConfigError::ConfigCommandOutputInvalid(error) => write!(f, "output of config_command is not valid UTF-8: {error}"),
+            ConfigError::ConfigCommandConflict(key) => write!(f, "{key} is set by both the config file and config_command"),
+        }
+    }
 }
 
 pub(crate) fn load_config(path: &Path) -> Result<MintakaConfig, ConfigError> {
@@ -90,8 +528,163 @@ pub(crate) fn load_config(path: &Path) -> Result<MintakaConfig, ConfigError> {
     let mut config_str = String::new();
     file.read_to_string(&mut config_str).map_err(ConfigError::FileReadFailed)?;
 
-    let config: MintakaConfig = toml::from_str(&config_str)
+    let config_command_config: ConfigCommandConfig = toml::from_str(&config_str)
         .map_err(ConfigError::DeserializationFailed)?;
+    // Any processes in the config file come before generated processes, so
+    // the sources of the processes in the file still line up.
+    let process_sources = process_sources(path, &config_str);
+    let mut config: MintakaConfig = match config_command_config.config_command {
+        None => toml::from_str(&config_str).map_err(ConfigError::DeserializationFailed)?,
+        Some(config_command) => {
+            let generated_config_str = run_config_command(&config_command, path)?;
+            merge_generated_config(&config_str, &generated_config_str)?
+        },
+    };
+
+    for (process_index, process_config) in config.processes.iter_mut().enumerate() {
+        process_config.stop_timeout = process_config.stop_timeout.or(config.stop_timeout);
+        process_config.scrollback_lines = process_config.scrollback_lines.or(config.scrollback_lines);
+        process_config.source = process_sources.get(process_index).cloned();
+    }
 
     Ok(config)
 }
+
+#[derive(Deserialize)]
+struct ConfigCommandConfig {
+    config_command: Option<Vec<String>>,
+}
+
+/// Run the command that generates the config, which is run in the directory
+/// of the config file.
+fn run_config_command(config_command: &[String], config_path: &Path) -> Result<String, ConfigError> {
+    let Some((executable, arguments)) = config_command.split_first() else {
+        return Err(ConfigError::ConfigCommandEmpty);
+    };
+    let working_directory = config_path.parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    let output = Command::new(executable)
+        .args(arguments)
+        .current_dir(working_directory)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(ConfigError::ConfigCommandFailed)?;
+    if !output.status.success() {
+        return Err(ConfigError::ConfigCommandUnsuccessful {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+
+    String::from_utf8(output.stdout).map_err(ConfigError::ConfigCommandOutputInvalid)
+}
+
+/// Combine the config file with the config generated by `config_command`.
+/// The processes of both are used, while any other key may only be set by one
+/// of them.
+fn merge_generated_config(config_str: &str, generated_config_str: &str) -> Result<MintakaConfig, ConfigError> {
+    let mut config_table: toml::Table = toml::from_str(config_str)
+        .map_err(ConfigError::DeserializationFailed)?;
+    config_table.remove("config_command");
+    let generated_config_table: toml::Table = toml::from_str(generated_config_str)
+        .map_err(ConfigError::DeserializationFailed)?;
+
+    for (key, generated_value) in generated_config_table {
+        let Some(value) = config_table.get_mut(&key) else {
+            config_table.insert(key, generated_value);
+            continue;
+        };
+        match (value, generated_value) {
+            (toml::Value::Array(processes), toml::Value::Array(generated_processes)) if key == "processes" => {
+                processes.extend(generated_processes);
+            },
+            _ => return Err(ConfigError::ConfigCommandConflict(key)),
+        }
+    }
+
+    toml::Value::Table(config_table).try_into()
+        .map_err(ConfigError::DeserializationFailed)
+}
+
+#[derive(Deserialize)]
+struct ProcessSpans {
+    processes: Vec<toml::Spanned<toml::Value>>,
+}
+
+/// Find the text that defined each process. The sources are only used for
+/// showing the config, so if they can't be found, there are no sources rather
+/// than an error.
+fn process_sources(path: &Path, config_str: &str) -> Vec<ConfigSource> {
+    let Ok(process_spans) = toml::from_str::<ProcessSpans>(config_str) else {
+        return Vec::new();
+    };
+
+    let lines: Vec<&str> = config_str.lines().collect();
+    let line_index_at = |offset: usize| config_str[..offset].matches('\n').count();
+
+    process_spans.processes
+        .iter()
+        .map(|process_span| {
+            let span = process_span.span();
+            let first_line_index = line_index_at(span.start);
+            let mut last_line_index = line_index_at(span.end.max(span.start + 1) - 1)
+                .min(lines.len().saturating_sub(1));
+
+            // The span of a table in an array of tables may only cover its
+            // header, so include the keys up to the next header.
+            if lines.get(first_line_index).is_some_and(|line| line.trim_start().starts_with("[[")) {
+                while lines.get(last_line_index + 1).is_some_and(|line| !line.trim_start().starts_with('[')) {
+                    last_line_index += 1;
+                }
+            }
+            while last_line_index > first_line_index && lines[last_line_index].trim().is_empty() {
+                last_line_index -= 1;
+            }
+
+            ConfigSource {
+                path: path.to_owned(),
+                first_line_number: first_line_index + 1,
+                text: lines[first_line_index..=last_line_index].join("\n"),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_generated_config_keeps_options_from_file() {
+        let config = merge_generated_config(
+            "config_command = [\"./gen.sh\"]\nconfirm_quit = false\n",
+            "[[processes]]\ncommand = [\"server\"]\n",
+        ).ok().unwrap();
+
+        assert!(!config.confirm_quit());
+        assert_eq!(config.processes.len(), 1);
+    }
+
+    #[test]
+    fn merge_generated_config_puts_processes_from_file_first() {
+        let config = merge_generated_config(
+            "config_command = [\"./gen.sh\"]\n[[processes]]\nname = \"db\"\ncommand = [\"db\"]\n",
+            "[[processes]]\nname = \"server\"\ncommand = [\"server\"]\n",
+        ).ok().unwrap();
+
+        let names: Vec<String> = config.processes.iter().map(ProcessConfig::name).collect();
+        assert_eq!(names, vec!["db".to_owned(), "server".to_owned()]);
+    }
+
+    #[test]
+    fn merge_generated_config_rejects_options_set_by_both() {
+        let error = merge_generated_config(
+            "config_command = [\"./gen.sh\"]\nconfirm_quit = false\nprocesses = []\n",
+            "confirm_quit = true\nprocesses = []\n",
+        ).err().unwrap();
+
+        assert!(matches!(error, ConfigError::ConfigCommandConflict(key) if key == "confirm_quit"));
+    }
+}
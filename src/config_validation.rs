@@ -0,0 +1,577 @@
+use std::{collections::{HashMap, HashSet}, fmt::Display, path::PathBuf};
+
+use crate::{config::{MintakaConfig, ProcessConfig}, executables::{executable_exists, find_executable}, file_watches, highlights::Highlighter, http_api, keybindings::Keymap, messages::Locale, ready_checks, redactions::Redactor, sandbox, shell_environment, signals, theme::Theme};
+
+/// The result of checking a config before any processes are started.
+pub(crate) struct ValidationReport {
+    /// The problems with each process, or with another section of the config,
+    /// grouped by the name of the process or section.
+    problems: Vec<(String, Vec<String>)>,
+
+    /// Possible mistakes that don't stop the processes from being started,
+    /// grouped in the same way as problems.
+    warnings: Vec<(String, Vec<String>)>,
+
+    start_phases: Vec<Vec<String>>,
+
+    manual_processes: Vec<String>,
+}
+
+impl ValidationReport {
+    pub(crate) fn has_problems(&self) -> bool {
+        !self.problems.is_empty()
+    }
+
+    pub(crate) fn first_problem(&self) -> Option<String> {
+        let (section_name, problems) = self.problems.first()?;
+        let problem = problems.first()?.lines().next()?;
+        Some(format!("{section_name}: {problem}"))
+    }
+
+    pub(crate) fn first_warning(&self) -> Option<String> {
+        let (section_name, warnings) = self.warnings.first()?;
+        let warning = warnings.first()?.lines().next()?;
+        Some(format!("{section_name}: {warning}"))
+    }
+}
+
+fn write_sections(f: &mut std::fmt::Formatter<'_>, heading: &str, sections: &[(String, Vec<String>)]) -> std::fmt::Result {
+    writeln!(f, "{heading}")?;
+    for (section_name, items) in sections {
+        writeln!(f)?;
+        writeln!(f, "  {section_name}")?;
+        for item in items {
+            for (line_index, line) in item.lines().enumerate() {
+                let indent = if line_index == 0 { "    - " } else { "      " };
+                writeln!(f, "{indent}{line}")?;
+            }
+        }
+    }
+    writeln!(f)
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.has_problems() {
+            write_sections(f, "Config problems:", &self.problems)?;
+        }
+        if !self.warnings.is_empty() {
+            write_sections(f, "Config warnings:", &self.warnings)?;
+        }
+
+        writeln!(f, "Planned start phases:")?;
+        for (phase_index, phase) in self.start_phases.iter().enumerate() {
+            writeln!(f, "  {}. {}", phase_index + 1, phase.join(", "))?;
+        }
+        if !self.manual_processes.is_empty() {
+            writeln!(f, "  Manual: {}", self.manual_processes.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn validate_config(config: &MintakaConfig) -> ValidationReport {
+    let process_names: HashSet<String> = config.processes.iter()
+        .map(|process_config| process_config.name())
+        .collect();
+
+    let mut problems: Vec<(String, Vec<String>)> = config.processes.iter()
+        .filter_map(|process_config| {
+            let mut problems = validate_process_config(process_config, &process_names);
+            if process_config.livereload && config.livereload.is_none() {
+                problems.push("livereload is enabled, but the top-level livereload option isn't set".to_owned());
+            }
+            if let Some(cycle) = dependency_cycle(config, process_config) {
+                problems.push(format!("after creates a cycle: {}", cycle.join(" -> ")));
+            }
+            if problems.is_empty() {
+                None
+            } else {
+                Some((process_config.name(), problems))
+            }
+        })
+        .collect();
+
+    problems.extend(duplicate_names_problems(config));
+
+    if config.profiler.as_ref().is_some_and(|profiler| profiler.is_empty()) {
+        problems.push(("profiler".to_owned(), vec!["command is empty".to_owned()]));
+    }
+
+    if let Err(keybindings_problems) = Keymap::new(&config.keybindings) {
+        problems.push(("keybindings".to_owned(), keybindings_problems));
+    }
+
+    if let Err(redactions_problems) = Redactor::new(&config.redactions) {
+        problems.push(("redactions".to_owned(), redactions_problems));
+    }
+
+    if let Err(theme_problems) = Theme::new(&config.theme) {
+        problems.push(("theme".to_owned(), theme_problems));
+    }
+
+    if let Some(authentication_problem) = config.http_api.as_ref().and_then(http_api::authentication_problem) {
+        problems.push(("http_api".to_owned(), vec![authentication_problem]));
+    }
+
+    if let Some(notifications_config) = &config.notifications {
+        let mut notifications_problems = Vec::new();
+        if let Some(webhook_config) = &notifications_config.webhook {
+            match &webhook_config.url {
+                Some(url) => notifications_problems.extend(ready_checks::parse_http_url(url).err().map(|error| format!("webhook {error}"))),
+                None => notifications_problems.push("webhook has no url".to_owned()),
+            }
+        }
+        if let Some(command_config) = &notifications_config.command {
+            if command_config.command.as_ref().map_or(true, |command| command.is_empty()) {
+                notifications_problems.push("command has no command".to_owned());
+            }
+        }
+        if !notifications_problems.is_empty() {
+            problems.push(("notifications".to_owned(), notifications_problems));
+        }
+    }
+
+    if let Some(locale) = &config.locale {
+        if Locale::from_tag(locale).is_none() {
+            problems.push(("locale".to_owned(), vec![format!("unsupported locale {locale}")]));
+        }
+    }
+
+    let (start_phases, manual_processes) = plan_start_phases(config);
+
+    let mut warnings = overlapping_watches_warnings(config);
+    for (name, quick_keys_warnings) in quick_keys_warnings(config) {
+        match warnings.iter_mut().find(|(other_name, _)| *other_name == name) {
+            Some((_, process_warnings)) => process_warnings.extend(quick_keys_warnings),
+            None => warnings.push((name, quick_keys_warnings)),
+        }
+    }
+
+    ValidationReport {
+        problems,
+        warnings,
+        start_phases,
+        manual_processes,
+    }
+}
+
+/// Warn about processes whose `watch` patterns match the same files, since
+/// saving one of the files restarts all of them, and a process that writes
+/// such a file can restart the other processes over and over.
+fn overlapping_watches_warnings(config: &MintakaConfig) -> Vec<(String, Vec<String>)> {
+    let watched_files: Vec<(String, HashSet<PathBuf>)> = config.processes.iter()
+        .filter(|process_config| !process_config.watch.is_empty())
+        .map(|process_config| {
+            let base_directory = process_config.working_directory.clone().unwrap_or_else(|| PathBuf::from("."));
+            (process_config.name(), file_watches::matching_files(&process_config.watch, &base_directory))
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    for (process_index, (name, files)) in watched_files.iter().enumerate() {
+        let process_warnings: Vec<String> = watched_files[process_index + 1..].iter()
+            .filter_map(|(other_name, other_files)| {
+                let shared_file = files.intersection(other_files).min()?;
+                Some(format!(
+                    "watch overlaps with the watch of {other_name}, such as {}",
+                    shared_file.display(),
+                ))
+            })
+            .collect();
+        if !process_warnings.is_empty() {
+            warnings.push((name.clone(), process_warnings));
+        }
+    }
+    warnings
+}
+
+/// Warn about quick keys that are also bound to an action, since the quick key
+/// takes precedence while the process is focused, hiding the action.
+fn quick_keys_warnings(config: &MintakaConfig) -> Vec<(String, Vec<String>)> {
+    let Ok(keymap) = Keymap::new(&config.keybindings) else {
+        return Vec::new();
+    };
+
+    config.processes.iter()
+        .filter_map(|process_config| {
+            let mut quick_keys: Vec<&String> = process_config.quick_keys.keys().collect();
+            quick_keys.sort();
+
+            let process_warnings: Vec<String> = quick_keys.into_iter()
+                .filter_map(|quick_key| {
+                    let mut chars = quick_key.chars();
+                    let (Some(key), None) = (chars.next(), chars.next()) else {
+                        return None;
+                    };
+                    let action_name = keymap.action_name(key)?;
+                    Some(format!("quick key {quick_key:?} hides the {action_name} key binding while the process is focused"))
+                })
+                .collect();
+
+            if process_warnings.is_empty() {
+                None
+            } else {
+                Some((process_config.name(), process_warnings))
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn validate_process_config(process_config: &ProcessConfig, process_names: &HashSet<String>) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let working_directory = match &process_config.working_directory {
+        Some(working_directory) => {
+            if !working_directory.is_dir() {
+                problems.push(format!("working directory {} does not exist", working_directory.display()));
+            }
+            working_directory.clone()
+        },
+        None => PathBuf::from("."),
+    };
+
+    match process_config.command.first() {
+        None => problems.push("command is empty".to_owned()),
+        Some(executable) => {
+            // When the environment is loaded before running the command, the
+            // executable may only be found using the loaded environment.
+            if process_config.environment.is_none() {
+                if let Err(error) = find_executable(executable, &working_directory) {
+                    problems.push(error.to_string());
+                }
+            }
+        },
+    }
+
+    if let Some(environment_config) = process_config.environment {
+        let loader_executable = shell_environment::loader_executable(environment_config);
+        if !executable_exists(&loader_executable, &working_directory) {
+            problems.push(format!("executable {loader_executable} for loading the environment could not be found"));
+        }
+    }
+
+    if let Some(sandbox_config) = &process_config.sandbox {
+        if let Err(error) = sandbox::check_sandbox(sandbox_config, &working_directory) {
+            problems.push(error.to_string());
+        }
+    }
+
+    problems.extend(process_config.regex_errors());
+
+    if let Err(highlight_problems) = Highlighter::new(&process_config.highlights) {
+        problems.extend(highlight_problems);
+    }
+
+    for quick_key in process_config.quick_keys.keys() {
+        if quick_key.chars().count() != 1 {
+            problems.push(format!("quick key {quick_key:?} is not a single character"));
+        }
+    }
+
+    if let Some(background) = &process_config.background {
+        if process_config.background_color().is_none() {
+            problems.push(format!("background {background} is not a valid color"));
+        }
+    }
+
+    if let Some(color) = &process_config.color {
+        if process_config.accent_color().is_none() {
+            problems.push(format!("color {color} is not a valid color"));
+        }
+    }
+
+    let hooks = [
+        ("on_ready", &process_config.on_ready),
+        ("on_error", &process_config.on_error),
+        ("on_success", &process_config.on_success),
+        ("on_exit", &process_config.on_exit),
+    ];
+    for (hook_name, hook) in hooks {
+        if hook.as_ref().is_some_and(|hook| hook.is_empty()) {
+            problems.push(format!("{hook_name} is empty"));
+        }
+    }
+
+    if let Some(stop_signal) = &process_config.stop_signal {
+        if signals::stop_signal_name(stop_signal).is_none() {
+            problems.push(format!("stop_signal {stop_signal} is not a supported signal"));
+        }
+    }
+
+    problems.extend(process_config.stop_timeout_problem());
+
+    if let Some(ready_check) = &process_config.ready_check {
+        problems.extend(ready_checks::ready_check_problem(ready_check));
+    }
+
+    if let Some(after) = &process_config.after {
+        if !process_names.contains(after) {
+            problems.push(format!("after refers to unknown process {after}"));
+        }
+    }
+
+    problems
+}
+
+/// Processes are referred to by name, such as by `after`, so each name must
+/// only be used once.
+fn duplicate_names_problems(config: &MintakaConfig) -> Option<(String, Vec<String>)> {
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    for process_config in &config.processes {
+        *name_counts.entry(process_config.name()).or_default() += 1;
+    }
+
+    let mut duplicate_names: Vec<(String, usize)> = name_counts.into_iter()
+        .filter(|(_, count)| *count > 1)
+        .collect();
+    if duplicate_names.is_empty() {
+        return None;
+    }
+    duplicate_names.sort();
+
+    let problems = duplicate_names.into_iter()
+        .map(|(name, count)| format!("name {name} is used by {count} processes"))
+        .collect();
+    Some(("processes".to_owned(), problems))
+}
+
+/// Find the chain of `after` settings that leads from the process back to
+/// itself, if any. Such processes would never start.
+fn dependency_cycle(config: &MintakaConfig, process_config: &ProcessConfig) -> Option<Vec<String>> {
+    let name = process_config.name();
+    let mut chain = vec![name.clone()];
+    let mut current_config = process_config;
+
+    while let Some(after) = &current_config.after {
+        if *after == name {
+            chain.push(after.clone());
+            return Some(chain);
+        }
+        if chain.contains(after) {
+            // The cycle doesn't include this process, and will be reported
+            // for the processes that it does include.
+            return None;
+        }
+        current_config = config.processes.iter()
+            .find(|other_config| other_config.name() == *after)?;
+        chain.push(after.clone());
+    }
+
+    None
+}
+
+/// Group the processes by when they will first start: the first phase is the
+/// processes that start automatically, and each later phase is the processes
+/// that wait for a process in the previous phase.
+fn plan_start_phases(config: &MintakaConfig) -> (Vec<Vec<String>>, Vec<String>) {
+    let mut start_phases: Vec<Vec<String>> = Vec::new();
+    let mut planned: HashSet<String> = HashSet::new();
+
+    let mut phase: Vec<String> = config.processes.iter()
+        .filter(|process_config| process_config.autostart())
+        .map(|process_config| process_config.name())
+        .collect();
+
+    while !phase.is_empty() {
+        planned.extend(phase.iter().cloned());
+        let next_phase = config.processes.iter()
+            .filter(|process_config| {
+                !planned.contains(&process_config.name()) &&
+                    process_config.after.as_ref().is_some_and(|after| phase.contains(after))
+            })
+            .map(|process_config| process_config.name())
+            .collect();
+        start_phases.push(phase);
+        phase = next_phase;
+    }
+
+    let manual_processes = config.processes.iter()
+        .map(|process_config| process_config.name())
+        .filter(|name| !planned.contains(name))
+        .collect();
+
+    (start_phases, manual_processes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(config_str: &str) -> MintakaConfig {
+        toml::from_str(config_str).unwrap()
+    }
+
+    fn process_config<'a>(config: &'a MintakaConfig, name: &str) -> &'a ProcessConfig {
+        config.processes.iter()
+            .find(|process_config| process_config.name() == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn dependency_cycle_is_none_for_chain_of_after() {
+        let config = config(r#"
+            [[processes]]
+            name = "a"
+            command = ["true"]
+
+            [[processes]]
+            name = "b"
+            command = ["true"]
+            after = "a"
+        "#);
+
+        assert_eq!(dependency_cycle(&config, process_config(&config, "b")), None);
+    }
+
+    #[test]
+    fn dependency_cycle_is_found_from_each_process_in_cycle() {
+        let config = config(r#"
+            [[processes]]
+            name = "a"
+            command = ["true"]
+            after = "b"
+
+            [[processes]]
+            name = "b"
+            command = ["true"]
+            after = "a"
+        "#);
+
+        assert_eq!(dependency_cycle(&config, process_config(&config, "a")), Some(vec!["a".to_owned(), "b".to_owned(), "a".to_owned()]));
+        assert_eq!(dependency_cycle(&config, process_config(&config, "b")), Some(vec!["b".to_owned(), "a".to_owned(), "b".to_owned()]));
+    }
+
+    #[test]
+    fn dependency_cycle_is_none_for_process_waiting_for_cycle() {
+        let config = config(r#"
+            [[processes]]
+            name = "a"
+            command = ["true"]
+            after = "b"
+
+            [[processes]]
+            name = "b"
+            command = ["true"]
+            after = "a"
+
+            [[processes]]
+            name = "c"
+            command = ["true"]
+            after = "a"
+        "#);
+
+        assert_eq!(dependency_cycle(&config, process_config(&config, "c")), None);
+    }
+
+    #[test]
+    fn dependency_cycle_is_none_when_after_names_unknown_process() {
+        let config = config(r#"
+            [[processes]]
+            name = "a"
+            command = ["true"]
+            after = "missing"
+        "#);
+
+        assert_eq!(dependency_cycle(&config, process_config(&config, "a")), None);
+    }
+
+    #[test]
+    fn quick_keys_warnings_lists_quick_keys_bound_to_actions() {
+        let config = config(r#"
+            keybindings = { restart = "ctrl+r" }
+
+            [[processes]]
+            name = "jest"
+            command = ["true"]
+            quick_keys = { a = "a", r = "r", t = "a" }
+        "#);
+
+        assert_eq!(quick_keys_warnings(&config), vec![(
+            "jest".to_owned(),
+            vec!["quick key \"a\" hides the toggle_autofocus key binding while the process is focused".to_owned()],
+        )]);
+    }
+
+    #[test]
+    fn plan_start_phases_groups_processes_by_after() {
+        let config = config(r#"
+            [[processes]]
+            name = "db"
+            command = ["true"]
+
+            [[processes]]
+            name = "build"
+            command = ["true"]
+
+            [[processes]]
+            name = "migrate"
+            command = ["true"]
+            after = "db"
+
+            [[processes]]
+            name = "server"
+            command = ["true"]
+            after = "migrate"
+
+            [[processes]]
+            name = "tests"
+            command = ["true"]
+            after = "build"
+        "#);
+
+        let (start_phases, manual_processes) = plan_start_phases(&config);
+
+        assert_eq!(start_phases, vec![
+            vec!["db".to_owned(), "build".to_owned()],
+            vec!["migrate".to_owned(), "tests".to_owned()],
+            vec!["server".to_owned()],
+        ]);
+        assert_eq!(manual_processes, Vec::<String>::new());
+    }
+
+    #[test]
+    fn plan_start_phases_lists_processes_that_never_start_automatically_as_manual() {
+        let config = config(r#"
+            [[processes]]
+            name = "server"
+            command = ["true"]
+
+            [[processes]]
+            name = "seed"
+            command = ["true"]
+            autostart = false
+
+            [[processes]]
+            name = "a"
+            command = ["true"]
+            after = "b"
+
+            [[processes]]
+            name = "b"
+            command = ["true"]
+            after = "a"
+        "#);
+
+        let (start_phases, manual_processes) = plan_start_phases(&config);
+
+        assert_eq!(start_phases, vec![vec!["server".to_owned()]]);
+        assert_eq!(manual_processes, vec!["seed".to_owned(), "a".to_owned(), "b".to_owned()]);
+    }
+
+    #[test]
+    fn plan_start_phases_is_empty_without_autostarted_processes() {
+        let config = config(r#"
+            [[processes]]
+            name = "seed"
+            command = ["true"]
+            autostart = false
+        "#);
+
+        let (start_phases, manual_processes) = plan_start_phases(&config);
+
+        assert_eq!(start_phases, Vec::<Vec<String>>::new());
+        assert_eq!(manual_processes, vec!["seed".to_owned()]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{io, path::{Path, PathBuf}, sync::{Arc, Mutex}};
+
+use crate::{child_registry::runtime_file_path, processes::Processes, remote_control::{Command, CommandOutput}};
+
+/// Connections are handled one at a time, so a client that stops sending or
+/// receiving mustn't block other clients for long.
+#[cfg(unix)]
+const CLIENT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// The socket that this instance of Mintaka is listening on.
+pub(crate) struct ControlSocket {
+    path: PathBuf,
+}
+
+impl ControlSocket {
+    /// Remove the socket when Mintaka exits normally.
+    pub(crate) fn remove(self) {
+        let _ = std::fs::remove_file(self.path);
+    }
+}
+
+/// The path of the socket that `mintaka ctl` uses to control the instance of
+/// Mintaka running with the given config.
+pub(crate) fn socket_path(config_path: &Path) -> io::Result<PathBuf> {
+    runtime_file_path(config_path, "sock")
+}
+
+/// Listen for commands from `mintaka ctl` on a background thread.
+///
+/// Each connection sends a single request line, such as `restart <name>`,
+/// `stop <name>` or `status`. The response starts with a line that is either
+/// `ok` or `error`, followed by the output of the command or the error.
+#[cfg(unix)]
+pub(crate) fn spawn(config_path: &Path, processes: Arc<Mutex<Processes>>) -> io::Result<ControlSocket> {
+    use std::{io::{BufRead, BufReader}, os::unix::net::{UnixListener, UnixStream}};
+
+    let socket_path = socket_path(config_path)?;
+    // A socket that can still be connected to belongs to another instance
+    // running with the same config, which keeps control of the socket.
+    if UnixStream::connect(&socket_path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "another instance of Mintaka is listening on the control socket",
+        ));
+    }
+    // A socket left behind by an instance that didn't exit cleanly would
+    // otherwise stop the new instance from listening.
+    let _ = std::fs::remove_file(&socket_path);
+    let listener = UnixListener::bind(&socket_path)?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            // Failures are limited to a single connection, so there's no need
+            // to stop serving other requests.
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            if stream.set_read_timeout(Some(CLIENT_TIMEOUT)).is_err()
+                || stream.set_write_timeout(Some(CLIENT_TIMEOUT)).is_err()
+            {
+                continue;
+            }
+            let mut request_line = String::new();
+            if BufReader::new(&stream).read_line(&mut request_line).is_err() {
+                continue;
+            }
+            let response = handle_request(request_line.trim_end(), &processes);
+            let _ = std::io::Write::write_all(&mut stream, response.as_bytes());
+        }
+    });
+
+    Ok(ControlSocket {
+        path: socket_path,
+    })
+}
+
+#[cfg(not(unix))]
+pub(crate) fn spawn(_config_path: &Path, _processes: Arc<Mutex<Processes>>) -> io::Result<ControlSocket> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+fn handle_request(request_line: &str, processes: &Arc<Mutex<Processes>>) -> String {
+    let command = match request_line.split_once(' ') {
+        None if request_line == "status" => Command::Status,
+        Some(("restart", name)) => Command::Restart { name: name.to_owned() },
+        Some(("stop", name)) => Command::Stop { name: name.to_owned() },
+        _ => return format!("error\nunknown request: {request_line}\n"),
+    };
+
+    match crate::remote_control::dispatch(processes, command) {
+        Ok(CommandOutput::Json(output) | CommandOutput::Text(output)) => format!("ok\n{output}"),
+        Err(error) => format!("error\n{error}\n"),
+    }
+}
+
+/// Send a request to the instance of Mintaka running with the given config,
+/// returning the output of the command.
+#[cfg(unix)]
+pub(crate) fn send_request(config_path: &Path, request_line: &str) -> Result<String, String> {
+    use std::{io::{Read, Write}, os::unix::net::UnixStream};
+
+    let socket_path = socket_path(config_path)
+        .map_err(|error| format!("failed to find socket: {error}"))?;
+    let mut stream = UnixStream::connect(&socket_path)
+        .map_err(|error| format!("failed to connect to {}: {error}", socket_path.display()))?;
+
+    let mut response = String::new();
+    writeln!(stream, "{request_line}")
+        .and_then(|_| stream.read_to_string(&mut response))
+        .map_err(|error| format!("failed to send request: {error}"))?;
+
+    match response.split_once('\n') {
+        Some(("ok", output)) => Ok(output.to_owned()),
+        Some(("error", error)) => Err(error.trim_end().to_owned()),
+        _ => Err("invalid response from Mintaka".to_owned()),
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn send_request(_config_path: &Path, _request_line: &str) -> Result<String, String> {
+    Err("ctl is only supported on Unix".to_owned())
+}
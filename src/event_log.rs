@@ -0,0 +1,39 @@
+use std::{collections::VecDeque, time::Instant};
+
+const MAX_EVENTS: usize = 1000;
+
+/// A log of the decisions that Mintaka makes on behalf of the user, such as
+/// restarting a process once its upstream process succeeds, so that the user
+/// can find out why something happened.
+pub(crate) struct EventLog {
+    events: VecDeque<Event>,
+}
+
+pub(crate) struct Event {
+    pub(crate) at: Instant,
+    pub(crate) message: String,
+}
+
+impl EventLog {
+    pub(crate) fn new() -> Self {
+        Self {
+            events: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, message: String) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+
+        self.events.push_back(Event {
+            at: Instant::now(),
+            message,
+        });
+    }
+
+    /// The events in the log, oldest first.
+    pub(crate) fn events(&self) -> impl DoubleEndedIterator<Item=&Event> + ExactSizeIterator {
+        self.events.iter()
+    }
+}
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+pub(crate) fn executable_exists(executable: &str, working_directory: &Path) -> bool {
+    find_executable(executable, working_directory).is_ok()
+}
+
+/// Why an executable can't be run.
+#[derive(Debug)]
+pub(crate) enum ExecutableError {
+    NotFound(String),
+
+    IsDirectory(PathBuf),
+
+    NotExecutable(PathBuf),
+}
+
+impl std::fmt::Display for ExecutableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutableError::NotFound(executable) => write!(f, "executable {executable} could not be found"),
+            ExecutableError::IsDirectory(path) => write!(f, "executable {} is a directory", path.display()),
+            ExecutableError::NotExecutable(path) => write!(f, "executable {} is not executable", path.display()),
+        }
+    }
+}
+
+/// Find the path of an executable, either relative to the working directory
+/// if the executable is a path, or on the `PATH` otherwise.
+pub(crate) fn find_executable(executable: &str, working_directory: &Path) -> Result<PathBuf, ExecutableError> {
+    if executable.contains(std::path::MAIN_SEPARATOR) || executable.contains('/') {
+        return find_executable_file(&working_directory.join(executable));
+    }
+
+    let Some(path) = std::env::var_os("PATH") else {
+        return Err(ExecutableError::NotFound(executable.to_owned()));
+    };
+
+    // Prefer reporting why a file on the PATH can't be run over reporting that
+    // the executable wasn't found at all.
+    let mut first_error = None;
+    for directory in std::env::split_paths(&path) {
+        match find_executable_file(&directory.join(executable)) {
+            Ok(executable_path) => return Ok(executable_path),
+            Err(ExecutableError::NotFound(_)) => {},
+            Err(error) => {
+                first_error.get_or_insert(error);
+            },
+        }
+    }
+
+    Err(first_error.unwrap_or_else(|| ExecutableError::NotFound(executable.to_owned())))
+}
+
+fn find_executable_file(path: &Path) -> Result<PathBuf, ExecutableError> {
+    let mut first_error = None;
+
+    for candidate_path in candidate_paths(path) {
+        let Ok(metadata) = candidate_path.metadata() else {
+            continue;
+        };
+
+        let error = if metadata.is_dir() {
+            ExecutableError::IsDirectory(candidate_path)
+        } else if !metadata.is_file() || !is_executable(&metadata) {
+            ExecutableError::NotExecutable(candidate_path)
+        } else {
+            return Ok(candidate_path);
+        };
+        first_error.get_or_insert(error);
+    }
+
+    Err(first_error.unwrap_or_else(|| ExecutableError::NotFound(path.display().to_string())))
+}
+
+#[cfg(unix)]
+fn candidate_paths(path: &Path) -> Vec<PathBuf> {
+    vec![path.to_owned()]
+}
+
+#[cfg(not(unix))]
+fn candidate_paths(path: &Path) -> Vec<PathBuf> {
+    vec![path.to_owned(), path.with_extension("exe")]
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
@@ -0,0 +1,304 @@
+use std::{collections::{HashMap, HashSet}, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc}, time::{Duration, SystemTime}};
+
+use regex::Regex;
+
+use crate::processes::ChangeNotifier;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches the files matching a set of glob patterns on a background thread.
+/// The modification times of the files are polled, rather than using
+/// notifications from the OS, so changes are noticed up to one interval late,
+/// and changes made within one interval are reported together.
+pub(crate) struct FileWatcher {
+    changed: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl FileWatcher {
+    /// Start watching. Patterns are relative to the base directory.
+    pub(crate) fn spawn(patterns: &[String], base_directory: &Path, on_change: ChangeNotifier) -> Self {
+        let changed = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let watch_patterns: Vec<WatchPattern> = patterns.iter()
+            .map(|pattern| WatchPattern::new(pattern, base_directory))
+            .collect();
+
+        std::thread::spawn({
+            let changed = Arc::clone(&changed);
+            let stopped = Arc::clone(&stopped);
+            move || {
+                let mut modified_times = find_modified_times(&watch_patterns);
+                loop {
+                    std::thread::sleep(POLL_INTERVAL);
+
+                    if stopped.load(Ordering::SeqCst) {
+                        return;
+                    }
+
+                    let new_modified_times = find_modified_times(&watch_patterns);
+                    if new_modified_times != modified_times {
+                        modified_times = new_modified_times;
+                        changed.store(true, Ordering::SeqCst);
+                        on_change.wake();
+                    }
+                }
+            }
+        });
+
+        Self { changed, stopped }
+    }
+
+    /// Whether any of the files has been changed, added or removed since the
+    /// last call.
+    pub(crate) fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::SeqCst)
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+/// The files that currently match any of the patterns, such as for checking
+/// whether the watches of processes overlap. Patterns are relative to the base
+/// directory, and the paths of the files are canonical.
+pub(crate) fn matching_files(patterns: &[String], base_directory: &Path) -> HashSet<PathBuf> {
+    let watch_patterns: Vec<WatchPattern> = patterns.iter()
+        .map(|pattern| WatchPattern::new(pattern, base_directory))
+        .collect();
+
+    find_modified_times(&watch_patterns)
+        .into_keys()
+        .filter_map(|path| path.canonicalize().ok())
+        .collect()
+}
+
+/// A glob pattern, where `*` matches any characters other than `/`, `?`
+/// matches a single character other than `/`, and `**` matches any number of
+/// directories.
+struct WatchPattern {
+    /// The directory to search for matching files, which is the part of the
+    /// pattern before the first wildcard.
+    root: PathBuf,
+
+    base_directory: PathBuf,
+
+    /// Matches paths relative to the base directory.
+    regex: Regex,
+}
+
+impl WatchPattern {
+    /// Relative patterns are relative to the base directory, while absolute
+    /// patterns, such as `/etc/hosts`, are matched against absolute paths.
+    fn new(pattern: &str, base_directory: &Path) -> Self {
+        let (pattern, base_directory) = match pattern.strip_prefix('/') {
+            Some(pattern) => (pattern, Path::new("/")),
+            None => (pattern.trim_start_matches("./"), base_directory),
+        };
+        let literal_segments: Vec<&str> = pattern.split('/')
+            .take_while(|segment| !segment.contains(['*', '?']))
+            .collect();
+
+        Self {
+            root: base_directory.join(literal_segments.join("/")),
+            base_directory: base_directory.to_owned(),
+            regex: glob_regex(pattern),
+        }
+    }
+
+    fn is_match(&self, path: &Path) -> bool {
+        let Ok(relative_path) = path.strip_prefix(&self.base_directory) else {
+            return false;
+        };
+        let relative_path: Vec<String> = relative_path.components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        self.regex.is_match(&relative_path.join("/"))
+    }
+}
+
+fn glob_regex(pattern: &str) -> Regex {
+    let mut regex_str = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex_str.push_str("(?:.*/)?");
+                } else {
+                    regex_str.push_str(".*");
+                }
+            },
+            '*' => regex_str.push_str("[^/]*"),
+            '?' => regex_str.push_str("[^/]"),
+            char => regex_str.push_str(&regex::escape(&char.to_string())),
+        }
+    }
+    regex_str.push('$');
+
+    // Every character other than the wildcards is escaped, so the regex is
+    // always valid.
+    Regex::new(&regex_str).unwrap()
+}
+
+/// Directories that are skipped when looking for files to watch, since they
+/// contain many files that are written by tools rather than edited, along with
+/// hidden directories such as `.git`. A pattern can still watch files in such
+/// a directory by naming it before any wildcards, such as `target/*.json`.
+const IGNORED_DIRECTORY_NAMES: [&str; 2] = ["node_modules", "target"];
+
+fn find_modified_times(watch_patterns: &[WatchPattern]) -> HashMap<PathBuf, SystemTime> {
+    let mut modified_times = HashMap::new();
+    for root in walk_roots(watch_patterns) {
+        visit_files(root, &mut |path, modified_time| {
+            if watch_patterns.iter().any(|watch_pattern| watch_pattern.is_match(path)) {
+                modified_times.insert(path.to_owned(), modified_time);
+            }
+        });
+    }
+    modified_times
+}
+
+/// The directories to walk to find the files matching the patterns, where a
+/// root inside another root is skipped, so that each file is only visited
+/// once however many patterns might match it.
+fn walk_roots(watch_patterns: &[WatchPattern]) -> Vec<&Path> {
+    let mut roots: Vec<&Path> = watch_patterns.iter()
+        .map(|watch_pattern| watch_pattern.root.as_path())
+        .collect();
+    roots.sort();
+    roots.dedup();
+
+    let mut walk_roots: Vec<&Path> = Vec::new();
+    for root in roots {
+        if !walk_roots.iter().any(|walk_root| root.starts_with(walk_root)) {
+            walk_roots.push(root);
+        }
+    }
+    walk_roots
+}
+
+/// Visit every file under the path, or the path itself if it's a file.
+/// Hidden and ignored directories are skipped, and symbolic links to
+/// directories aren't followed, so that links can't form a loop.
+fn visit_files(path: &Path, visit: &mut impl FnMut(&Path, SystemTime)) {
+    let Ok(metadata) = path.metadata() else {
+        return;
+    };
+    if !metadata.is_dir() {
+        if let Ok(modified_time) = metadata.modified() {
+            visit(path, modified_time);
+        }
+        return;
+    }
+
+    let Ok(entries) = path.read_dir() else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let entry_path = entry.path();
+        if file_type.is_dir() {
+            let directory_name = entry.file_name().to_string_lossy().into_owned();
+            if !directory_name.starts_with('.') && !IGNORED_DIRECTORY_NAMES.contains(&directory_name.as_str()) {
+                visit_files(&entry_path, visit);
+            }
+        } else if let Ok(modified_time) = entry_path.metadata().and_then(|metadata| metadata.modified()) {
+            visit(&entry_path, modified_time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_regex_star_matches_within_one_directory() {
+        let regex = glob_regex("src/*.rs");
+
+        assert!(regex.is_match("src/main.rs"));
+        assert!(!regex.is_match("src/ui/mod.rs"));
+        assert!(!regex.is_match("main.rs"));
+    }
+
+    #[test]
+    fn glob_regex_double_star_matches_any_number_of_directories() {
+        let regex = glob_regex("src/**/*.rs");
+
+        assert!(regex.is_match("src/main.rs"));
+        assert!(regex.is_match("src/ui/widgets/list.rs"));
+        assert!(!regex.is_match("tests/main.rs"));
+    }
+
+    #[test]
+    fn glob_regex_question_mark_matches_one_character() {
+        let regex = glob_regex("v?.txt");
+
+        assert!(regex.is_match("v1.txt"));
+        assert!(!regex.is_match("v10.txt"));
+        assert!(!regex.is_match("v/.txt"));
+    }
+
+    #[test]
+    fn glob_regex_escapes_other_characters() {
+        let regex = glob_regex("Cargo.toml");
+
+        assert!(regex.is_match("Cargo.toml"));
+        assert!(!regex.is_match("Cargo-toml"));
+    }
+
+    #[test]
+    fn watch_pattern_root_is_literal_prefix() {
+        let watch_pattern = WatchPattern::new("./src/**/*.rs", Path::new("web"));
+
+        assert_eq!(watch_pattern.root, Path::new("web/src"));
+        assert!(watch_pattern.is_match(Path::new("web/src/main.rs")));
+        assert!(!watch_pattern.is_match(Path::new("src/main.rs")));
+    }
+
+    #[test]
+    fn watch_pattern_matches_absolute_paths() {
+        let watch_pattern = WatchPattern::new("/etc/mintaka/*.toml", Path::new("web"));
+
+        assert_eq!(watch_pattern.root, Path::new("/etc/mintaka"));
+        assert!(watch_pattern.is_match(Path::new("/etc/mintaka/local.toml")));
+        assert!(!watch_pattern.is_match(Path::new("web/etc/mintaka/local.toml")));
+    }
+
+    #[test]
+    fn walk_roots_skips_roots_inside_other_roots() {
+        let watch_patterns = [
+            WatchPattern::new("src/**/*.rs", Path::new(".")),
+            WatchPattern::new("**/*.toml", Path::new(".")),
+            WatchPattern::new("src/*.json", Path::new(".")),
+        ];
+
+        assert_eq!(walk_roots(&watch_patterns), vec![Path::new(".")]);
+    }
+
+    #[test]
+    fn find_modified_times_skips_ignored_directories() {
+        let directory = std::env::temp_dir().join(format!("mintaka-file-watches-test-{}", std::process::id()));
+        for subdirectory in ["src", "node_modules/package", "target/debug", ".git"] {
+            std::fs::create_dir_all(directory.join(subdirectory)).unwrap();
+        }
+        for file in ["src/main.rs", "node_modules/package/index.rs", "target/debug/build.rs", ".git/hook.rs"] {
+            std::fs::write(directory.join(file), "").unwrap();
+        }
+
+        let watch_patterns = [WatchPattern::new("**/*.rs", &directory)];
+        let paths: Vec<PathBuf> = find_modified_times(&watch_patterns).into_keys().collect();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(paths, vec![directory.join("src/main.rs")]);
+    }
+}
@@ -0,0 +1,74 @@
+use std::{collections::{HashMap, HashSet}, path::{Path, PathBuf}, sync::{Arc, Mutex}, time::Duration};
+
+use crate::{processes::{ChangeNotifier, Process, Processes}, working_directories};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Poll the git branch of the working directory of each running process on a
+/// background thread, so that switching branches, such as in another
+/// worktree, is noticed while the process is running.
+pub(crate) fn spawn(processes: Arc<Mutex<Processes>>, on_change: ChangeNotifier) {
+    std::thread::spawn(move || {
+        // The git directory of each process is only found once per run, by
+        // process ID, since finding the working directory may be slow.
+        let mut git_directories: HashMap<u32, Option<PathBuf>> = HashMap::new();
+        let mut git_branches: HashMap<u32, String> = HashMap::new();
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let process_ids: HashSet<u32> = processes.lock().unwrap()
+                .processes()
+                .iter()
+                .filter_map(Process::process_id)
+                .collect();
+            git_directories.retain(|process_id, _| process_ids.contains(process_id));
+
+            let mut new_git_branches = HashMap::new();
+            for process_id in process_ids {
+                let git_directory = git_directories.entry(process_id).or_insert_with(|| {
+                    working_directories::working_directory_of(process_id)
+                        .and_then(|working_directory| find_git_directory(&working_directory))
+                });
+                if let Some(branch) = git_directory.as_deref().and_then(read_branch) {
+                    new_git_branches.insert(process_id, branch);
+                }
+            }
+
+            if new_git_branches != git_branches {
+                git_branches = new_git_branches;
+                processes.lock().unwrap().set_git_branches(git_branches.clone());
+                on_change.wake();
+            }
+        }
+    });
+}
+
+/// The git directory of the repository containing the given directory. In a
+/// worktree, `.git` is a file pointing to the git directory of the worktree,
+/// which has its own `HEAD`.
+fn find_git_directory(directory: &Path) -> Option<PathBuf> {
+    directory.ancestors().find_map(|ancestor| {
+        let dot_git = ancestor.join(".git");
+        if dot_git.is_dir() {
+            Some(dot_git)
+        } else if dot_git.is_file() {
+            let contents = std::fs::read_to_string(&dot_git).ok()?;
+            let git_directory = contents.trim().strip_prefix("gitdir: ")?;
+            Some(ancestor.join(git_directory))
+        } else {
+            None
+        }
+    })
+}
+
+/// The branch checked out in the given git directory, or the abbreviated
+/// commit when `HEAD` is detached.
+fn read_branch(git_directory: &Path) -> Option<String> {
+    let head = std::fs::read_to_string(git_directory.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: ") {
+        Some(reference) => Some(reference.strip_prefix("refs/heads/").unwrap_or(reference).to_owned()),
+        None => head.get(..7).map(str::to_owned),
+    }
+}
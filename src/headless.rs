@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use crate::{config::MintakaConfig, output_lines::EchoFormat, processes::{ChangeNotifier, ProcessStatus, Processes}};
+
+/// Changes to processes wake the loop, but processes are also checked
+/// periodically in case work is pending without a change, such as a restart.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Run the processes without the UI, printing their output to stdout, until
+/// none of them are running, returning whether they all succeeded. Processes
+/// that keep running, such as watchers, are stopped once they reach a success
+/// or error state.
+pub(crate) fn run(config: MintakaConfig, echo_format: EchoFormat) -> bool {
+    let (change_tx, change_rx) = std::sync::mpsc::channel();
+
+    let mut processes = Processes::new(ChangeNotifier::Channel(change_tx));
+    processes.enable_output_echo(echo_format);
+    processes.allow_watchers_to_exit();
+    for process_config in config.processes {
+        processes.start_process(process_config).unwrap();
+    }
+
+    loop {
+        processes.do_work().unwrap();
+
+        let is_finished = processes.processes()
+            .iter()
+            .all(|process| !matches!(process.status(), ProcessStatus::Running));
+        if is_finished {
+            break;
+        }
+
+        let _ = change_rx.recv_timeout(POLL_INTERVAL);
+    }
+
+    println!();
+    for process in processes.processes() {
+        println!("{} {}", process.name(), process.status().description());
+    }
+    let succeeded = processes.failure_count() == 0;
+
+    processes.stop_all();
+
+    succeeded
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+use termwiz::{cell::{CellAttributes, Intensity, Underline}, color::{ColorAttribute, RgbColor}};
+
+/// Restyles lines of process output that match regexes, such as showing
+/// warnings in yellow. This is purely cosmetic: it has no effect on the status
+/// of the process.
+#[derive(Default)]
+pub(crate) struct Highlighter {
+    rules: Vec<(Regex, HighlightStyle)>,
+}
+
+impl Highlighter {
+    /// Build the highlighter from a table mapping regexes to styles, or
+    /// describe the problems with the table.
+    pub(crate) fn new(highlights: &HashMap<String, String>) -> Result<Self, Vec<String>> {
+        let mut problems = Vec::new();
+        let mut rules = Vec::new();
+
+        // Rules are applied in a consistent order so that overlapping rules
+        // always combine in the same way.
+        let mut regexes: Vec<&String> = highlights.keys().collect();
+        regexes.sort();
+
+        for regex_str in regexes {
+            let style_str = &highlights[regex_str];
+
+            match (Regex::new(regex_str), HighlightStyle::parse(style_str)) {
+                (Ok(regex), Ok(style)) => rules.push((regex, style)),
+                (regex, style) => {
+                    if let Err(error) = regex {
+                        problems.push(format!("highlight regex {regex_str:?} is invalid: {error}"));
+                    }
+                    if let Err(error) = style {
+                        problems.push(format!("highlight style {style_str:?} is invalid: {error}"));
+                    }
+                },
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(Self { rules })
+        } else {
+            Err(problems)
+        }
+    }
+
+    pub(crate) fn apply(&self, lines: &mut [wezterm_term::Line]) {
+        if self.rules.is_empty() {
+            return;
+        }
+
+        for line in lines {
+            let text = line.as_str().into_owned();
+            for (regex, style) in &self.rules {
+                if regex.is_match(&text) {
+                    for cell in line.cells_mut_for_attr_changes_only() {
+                        style.apply(cell.attrs_mut());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct HighlightStyle {
+    foreground: Option<RgbColor>,
+    background: Option<RgbColor>,
+    intensity: Option<Intensity>,
+    italic: bool,
+    underline: bool,
+    reverse: bool,
+    strikethrough: bool,
+}
+
+impl HighlightStyle {
+    /// Parse a style such as `"yellow"`, `"dim"` or `"bold white on red"`.
+    fn parse(style_str: &str) -> Result<Self, String> {
+        let mut style = Self::default();
+        let mut words = style_str.split_whitespace();
+
+        while let Some(word) = words.next() {
+            match word {
+                "bold" => style.intensity = Some(Intensity::Bold),
+                "dim" => style.intensity = Some(Intensity::Half),
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "reverse" => style.reverse = true,
+                "strikethrough" => style.strikethrough = true,
+                "on" => {
+                    let color_str = words.next()
+                        .ok_or_else(|| "expected a color after \"on\"".to_owned())?;
+                    style.background = Some(parse_color(color_str)?);
+                },
+                color_str => style.foreground = Some(parse_color(color_str)?),
+            }
+        }
+
+        Ok(style)
+    }
+
+    fn apply(&self, attributes: &mut CellAttributes) {
+        if let Some(foreground) = self.foreground {
+            attributes.set_foreground(ColorAttribute::TrueColorWithDefaultFallback(foreground.into()));
+        }
+        if let Some(background) = self.background {
+            attributes.set_background(ColorAttribute::TrueColorWithDefaultFallback(background.into()));
+        }
+        if let Some(intensity) = self.intensity {
+            attributes.set_intensity(intensity);
+        }
+        if self.italic {
+            attributes.set_italic(true);
+        }
+        if self.underline {
+            attributes.set_underline(Underline::Single);
+        }
+        if self.reverse {
+            attributes.set_reverse(true);
+        }
+        if self.strikethrough {
+            attributes.set_strikethrough(true);
+        }
+    }
+}
+
+fn parse_color(color_str: &str) -> Result<RgbColor, String> {
+    RgbColor::from_named_or_rgb_string(color_str)
+        .ok_or_else(|| format!("unknown color or style {color_str:?}"))
+}
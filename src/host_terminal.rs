@@ -0,0 +1,75 @@
+use termwiz::{cell::{AttributeChange, CellAttributes, Underline}, surface::Change};
+
+/// Features of the terminal that Mintaka is running in that can't be safely
+/// assumed, and so are detected from the environment.
+#[derive(Clone, Copy)]
+pub(crate) struct HostTerminalFeatures {
+    /// Whether OSC 8 hyperlinks are supported.
+    pub(crate) hyperlinks: bool,
+
+    /// Whether double, curly, dotted and dashed underlines are supported.
+    pub(crate) styled_underlines: bool,
+}
+
+impl HostTerminalFeatures {
+    pub(crate) fn detect() -> Self {
+        let modern = is_modern_terminal();
+
+        Self {
+            hyperlinks: modern,
+            styled_underlines: modern,
+        }
+    }
+
+    /// Adjust the changes used to draw the output of a process so that they
+    /// only use features supported by the host terminal.
+    pub(crate) fn degrade_changes(&self, changes: Vec<Change>) -> Vec<Change> {
+        changes.into_iter()
+            .filter_map(|change| match change {
+                Change::AllAttributes(mut attributes) => {
+                    self.degrade_attributes(&mut attributes);
+                    Some(Change::AllAttributes(attributes))
+                },
+                Change::Attribute(AttributeChange::Underline(underline)) => {
+                    Some(Change::Attribute(AttributeChange::Underline(self.degrade_underline(underline))))
+                },
+                Change::Attribute(AttributeChange::Hyperlink(_)) if !self.hyperlinks => None,
+                change => Some(change),
+            })
+            .collect()
+    }
+
+    fn degrade_attributes(&self, attributes: &mut CellAttributes) {
+        attributes.set_underline(self.degrade_underline(attributes.underline()));
+
+        if !self.hyperlinks {
+            attributes.set_hyperlink(None);
+        }
+    }
+
+    fn degrade_underline(&self, underline: Underline) -> Underline {
+        match underline {
+            Underline::None | Underline::Single => underline,
+            // termwiz only renders single and double underlines, so other
+            // styles are always degraded rather than being dropped entirely.
+            Underline::Double if self.styled_underlines => underline,
+            Underline::Double | Underline::Curly | Underline::Dotted | Underline::Dashed => Underline::Single,
+        }
+    }
+}
+
+fn is_modern_terminal() -> bool {
+    let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+    if matches!(term_program.as_str(), "WezTerm" | "iTerm.app" | "vscode" | "ghostty") {
+        return true;
+    }
+
+    if std::env::var_os("KITTY_WINDOW_ID").is_some() || std::env::var_os("WT_SESSION").is_some() {
+        return true;
+    }
+
+    // VTE added support for hyperlinks in 0.50.
+    std::env::var("VTE_VERSION").ok()
+        .and_then(|version| version.parse::<u32>().ok())
+        .is_some_and(|version| version >= 5000)
+}
@@ -0,0 +1,418 @@
+use std::{io::{BufRead, BufReader, Read, Write}, net::{SocketAddr, TcpListener, TcpStream}, sync::{Arc, Mutex}, time::Duration};
+
+use crate::{config::{HttpApiConfig, ProcessConfig}, processes::Processes, remote_control::{self, Command, CommandError, CommandOutput}};
+
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The largest request body that's accepted, which is plenty for the config
+/// of a process.
+const MAX_BODY_LENGTH: usize = 64 * 1024;
+
+/// Serve the HTTP API on a background thread.
+///
+/// The API supports:
+///
+/// * `GET /status`: the status of every process as JSON.
+/// * `POST /processes/{name}/restart`: restart a process.
+/// * `POST /processes/{name}/stop`: stop a process.
+/// * `GET /processes/{name}/tail`: the current screen of a process as text.
+/// * `POST /processes`: add and start a process described by a TOML body, using
+///   the same keys as a process in the config file.
+///
+/// Every request must have the header `Authorization: Bearer {token}`, and a
+/// `Host` header matching the listen address. Requests with an `Origin` header
+/// are rejected, since they're sent by web pages rather than by tools.
+pub(crate) fn spawn(listener: TcpListener, http_api_config: HttpApiConfig, processes: Arc<Mutex<Processes>>) {
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            // Failures are limited to a single connection, so there's no need
+            // to stop serving other requests.
+            if let Ok(stream) = stream {
+                let _ = handle_connection(stream, &http_api_config, &processes);
+            }
+        }
+    });
+}
+
+/// Describe why the API can't be served, if it can't. Since the API can start
+/// any command, a token is always required: even a loopback address can be
+/// reached by other users of the machine, and by web pages in a browser.
+pub(crate) fn authentication_problem(http_api_config: &HttpApiConfig) -> Option<String> {
+    match http_api_config.token.as_deref() {
+        Some(token) if !token.is_empty() => None,
+        Some(_) => Some("token is empty".to_owned()),
+        None => Some("token is required".to_owned()),
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    host: Option<String>,
+    has_origin: bool,
+    body: String,
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn text(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: body.into(),
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, http_api_config: &HttpApiConfig, processes: &Arc<Mutex<Processes>>) -> std::io::Result<()> {
+    // Connections are handled one at a time, so a client that stops sending
+    // or receiving mustn't block other clients for long.
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader)? {
+        Err(response) => response,
+        Ok(request) => handle_request(request, http_api_config, processes),
+    };
+    write_response(stream, response)
+}
+
+/// Read a request, or the response to send if the request can't be handled.
+fn read_request(reader: &mut impl BufRead) -> std::io::Result<Result<Request, Response>> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut request_line_parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (request_line_parts.next(), request_line_parts.next()) else {
+        return Ok(Err(bad_request()));
+    };
+
+    let mut authorization = None;
+    let mut host = None;
+    let mut has_origin = false;
+    let mut content_length = 0;
+    loop {
+        let mut header_line = String::new();
+        if reader.read_line(&mut header_line)? == 0 {
+            break;
+        }
+        let header_line = header_line.trim_end();
+        if header_line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header_line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.to_owned());
+            } else if name.eq_ignore_ascii_case("host") {
+                host = Some(value.to_owned());
+            } else if name.eq_ignore_ascii_case("origin") {
+                has_origin = true;
+            } else if name.eq_ignore_ascii_case("content-length") {
+                let Ok(value) = value.parse() else {
+                    return Ok(Err(bad_request()));
+                };
+                content_length = value;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_LENGTH {
+        return Ok(Err(Response::text(413, "Payload too large\n")));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let Ok(body) = String::from_utf8(body) else {
+        return Ok(Err(bad_request()));
+    };
+
+    Ok(Ok(Request {
+        method: method.to_owned(),
+        path: path.to_owned(),
+        authorization,
+        host,
+        has_origin,
+        body,
+    }))
+}
+
+fn bad_request() -> Response {
+    Response::text(400, "Bad request\n")
+}
+
+fn handle_request(request: Request, http_api_config: &HttpApiConfig, processes: &Arc<Mutex<Processes>>) -> Response {
+    if let Err(response) = check_request(&request, http_api_config) {
+        return response;
+    }
+
+    let command = match parse_command(&request) {
+        Ok(command) => command,
+        Err(response) => return response,
+    };
+
+    match remote_control::dispatch(processes, command) {
+        Ok(CommandOutput::Json(body)) => Response {
+            status: 200,
+            content_type: "application/json",
+            body,
+        },
+        Ok(CommandOutput::Text(body)) => Response::text(200, body),
+        Err(error @ CommandError::UnknownProcess(_)) => Response::text(404, format!("{error}\n")),
+        Err(error) => Response::text(400, format!("{error}\n")),
+    }
+}
+
+/// Check that a request comes from a tool that knows the token, rather than
+/// from a web page. Browsers always send `Origin` with cross-origin requests,
+/// and checking `Host` stops DNS rebinding from making a page same-origin.
+fn check_request(request: &Request, http_api_config: &HttpApiConfig) -> Result<(), Response> {
+    if request.has_origin {
+        return Err(Response::text(403, "Forbidden\n"));
+    }
+    if !request.host.as_deref().is_some_and(|host| is_listen_address(host, &http_api_config.listen)) {
+        return Err(Response::text(403, "Forbidden\n"));
+    }
+
+    let Some(token) = http_api_config.token.as_deref() else {
+        return Err(Response::text(401, "Unauthorized\n"));
+    };
+    let expected_authorization = format!("Bearer {token}");
+    let authorization = request.authorization.as_deref().unwrap_or("");
+    if !constant_time_eq(authorization.as_bytes(), expected_authorization.as_bytes()) {
+        return Err(Response::text(401, "Unauthorized\n"));
+    }
+
+    Ok(())
+}
+
+/// Whether the `Host` of a request is the address being listened on. When
+/// listening on every interface, such as `0.0.0.0:7700`, any host with the
+/// same port is accepted, since the host could be any address of the machine.
+fn is_listen_address(host: &str, listen: &str) -> bool {
+    if host.eq_ignore_ascii_case(listen) {
+        return true;
+    }
+    match listen.parse::<SocketAddr>() {
+        Ok(listen_address) if listen_address.ip().is_unspecified() => {
+            host.rsplit_once(':')
+                .and_then(|(_, port)| port.parse::<u16>().ok())
+                .is_some_and(|port| port == listen_address.port())
+        },
+        _ => false,
+    }
+}
+
+/// Compare two byte strings in time that depends only on their lengths, so
+/// that the time taken doesn't reveal how much of the token was guessed.
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left.iter().zip(right).fold(0, |difference, (left, right)| difference | (left ^ right)) == 0
+}
+
+fn parse_command(request: &Request) -> Result<Command, Response> {
+    let path_segments: Vec<&str> = request.path
+        .trim_matches('/')
+        .split('/')
+        .collect();
+
+    match (request.method.as_str(), path_segments.as_slice()) {
+        ("GET", ["status"]) => Ok(Command::Status),
+        ("POST", ["processes", name, "restart"]) => Ok(Command::Restart {
+            name: percent_decode(name),
+        }),
+        ("POST", ["processes", name, "stop"]) => Ok(Command::Stop {
+            name: percent_decode(name),
+        }),
+        ("GET", ["processes", name, "tail"]) => Ok(Command::Tail {
+            name: percent_decode(name),
+        }),
+        ("POST", ["processes"]) => {
+            let process_config: ProcessConfig = toml::from_str(&request.body)
+                .map_err(|error| Response::text(400, format!("invalid process config: {error}\n")))?;
+            Ok(Command::Add { process_config })
+        },
+        _ => Err(Response::text(404, "Not found\n")),
+    }
+}
+
+fn write_response(mut stream: TcpStream, response: Response) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        _ => "",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len(),
+    )?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes.get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            },
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(request_str: &str) -> Result<Request, Response> {
+        read_request(&mut request_str.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn percent_decode_decodes_escaped_bytes() {
+        assert_eq!(percent_decode("web%20server"), "web server");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+    }
+
+    #[test]
+    fn percent_decode_leaves_invalid_escapes() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn read_request_reads_method_path_headers_and_body() {
+        let request = read(
+            "POST /processes HTTP/1.1\r\nAuthorization: Bearer secret\r\ncontent-length: 4\r\n\r\nbodyextra",
+        ).ok().unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/processes");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer secret"));
+        assert_eq!(request.body, "body");
+    }
+
+    #[test]
+    fn read_request_without_body_has_empty_body() {
+        let request = read("GET /status HTTP/1.1\r\n\r\n").ok().unwrap();
+
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.body, "");
+    }
+
+    #[test]
+    fn read_request_rejects_missing_path() {
+        let response = read("GET\r\n\r\n").err().unwrap();
+
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn read_request_rejects_invalid_content_length() {
+        let response = read("POST /processes HTTP/1.1\r\nContent-Length: lots\r\n\r\n").err().unwrap();
+
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn read_request_rejects_body_over_limit_without_reading_it() {
+        let response = read("POST /processes HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n").err().unwrap();
+
+        assert_eq!(response.status, 413);
+    }
+
+    fn request(host: Option<&str>, authorization: Option<&str>) -> Request {
+        Request {
+            method: "GET".to_owned(),
+            path: "/status".to_owned(),
+            authorization: authorization.map(str::to_owned),
+            host: host.map(str::to_owned),
+            has_origin: false,
+            body: String::new(),
+        }
+    }
+
+    fn http_api_config(listen: &str) -> HttpApiConfig {
+        HttpApiConfig { listen: listen.to_owned(), token: Some("secret".to_owned()) }
+    }
+
+    #[test]
+    fn read_request_reads_host_and_origin() {
+        let request = read("GET /status HTTP/1.1\r\nHost: 127.0.0.1:7700\r\nOrigin: http://example.com\r\n\r\n").ok().unwrap();
+
+        assert_eq!(request.host.as_deref(), Some("127.0.0.1:7700"));
+        assert!(request.has_origin);
+    }
+
+    #[test]
+    fn authentication_problem_requires_token() {
+        let without_token = HttpApiConfig { listen: "127.0.0.1:7700".to_owned(), token: None };
+        let with_empty_token = HttpApiConfig { listen: "127.0.0.1:7700".to_owned(), token: Some(String::new()) };
+
+        assert!(authentication_problem(&without_token).is_some());
+        assert!(authentication_problem(&with_empty_token).is_some());
+        assert_eq!(authentication_problem(&http_api_config("0.0.0.0:7700")), None);
+    }
+
+    #[test]
+    fn check_request_accepts_token_and_matching_host() {
+        let request = request(Some("127.0.0.1:7700"), Some("Bearer secret"));
+
+        assert!(check_request(&request, &http_api_config("127.0.0.1:7700")).is_ok());
+    }
+
+    #[test]
+    fn check_request_rejects_missing_or_wrong_token() {
+        let http_api_config = http_api_config("127.0.0.1:7700");
+
+        assert_eq!(check_request(&request(Some("127.0.0.1:7700"), None), &http_api_config).err().unwrap().status, 401);
+        assert_eq!(check_request(&request(Some("127.0.0.1:7700"), Some("Bearer secreT")), &http_api_config).err().unwrap().status, 401);
+        assert_eq!(check_request(&request(Some("127.0.0.1:7700"), Some("Bearer secret2")), &http_api_config).err().unwrap().status, 401);
+    }
+
+    #[test]
+    fn check_request_rejects_other_hosts() {
+        let http_api_config = http_api_config("127.0.0.1:7700");
+
+        assert_eq!(check_request(&request(Some("attacker.example:7700"), Some("Bearer secret")), &http_api_config).err().unwrap().status, 403);
+        assert_eq!(check_request(&request(None, Some("Bearer secret")), &http_api_config).err().unwrap().status, 403);
+    }
+
+    #[test]
+    fn check_request_rejects_requests_from_web_pages() {
+        let request = Request { has_origin: true, ..request(Some("127.0.0.1:7700"), Some("Bearer secret")) };
+
+        assert_eq!(check_request(&request, &http_api_config("127.0.0.1:7700")).err().unwrap().status, 403);
+    }
+
+    #[test]
+    fn is_listen_address_accepts_any_host_with_port_when_listening_on_every_interface() {
+        assert!(is_listen_address("192.168.1.2:7700", "0.0.0.0:7700"));
+        assert!(!is_listen_address("192.168.1.2:7701", "0.0.0.0:7700"));
+    }
+}
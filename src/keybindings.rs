@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use termwiz::input::{KeyCode, KeyEvent, Modifiers};
+
+/// An action that can be bound to a key.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Action {
+    FocusUp,
+    FocusDown,
+    CollapseGroup,
+    ExpandGroup,
+    ToggleAutofocus,
+    Restart,
+    SendCtrlD,
+    ToggleWaterfall,
+    ToggleEventLog,
+    ReloadConfig,
+    ExportScreen,
+    Profile,
+    CloneProcess,
+    Search,
+    SearchEarlier,
+    SearchLater,
+    CopyLastFailure,
+    EditUpstream,
+    TogglePrivacyMode,
+    Checkpoint,
+    ShowCheckpoints,
+    ToggleConfigSource,
+    ToggleStopped,
+    RestartAll,
+    ToggleZoom,
+    ToggleSplit,
+    ToggleSplitDirection,
+    ShrinkProcessList,
+    GrowProcessList,
+    SaveOutput,
+    SaveOutputWithColors,
+    ToggleReadonly,
+    PreviousRun,
+    NextRun,
+}
+
+/// The name of each action in the config, along with its default key.
+const ACTIONS: [(&str, Action, &str); 34] = [
+    ("focus_up", Action::FocusUp, "up"),
+    ("focus_down", Action::FocusDown, "down"),
+    ("collapse_group", Action::CollapseGroup, "left"),
+    ("expand_group", Action::ExpandGroup, "right"),
+    ("toggle_autofocus", Action::ToggleAutofocus, "a"),
+    ("restart", Action::Restart, "r"),
+    ("toggle_stopped", Action::ToggleStopped, "s"),
+    ("restart_all", Action::RestartAll, "R"),
+    ("send_ctrl_d", Action::SendCtrlD, "d"),
+    ("toggle_readonly", Action::ToggleReadonly, "L"),
+    ("toggle_waterfall", Action::ToggleWaterfall, "w"),
+    ("toggle_event_log", Action::ToggleEventLog, "l"),
+    ("reload_config", Action::ReloadConfig, "f5"),
+    ("export_screen", Action::ExportScreen, "x"),
+    ("save_output", Action::SaveOutput, "o"),
+    ("save_output_with_colors", Action::SaveOutputWithColors, "O"),
+    ("profile", Action::Profile, "p"),
+    ("clone_process", Action::CloneProcess, "C"),
+    ("search", Action::Search, "/"),
+    ("search_earlier", Action::SearchEarlier, "n"),
+    ("search_later", Action::SearchLater, "N"),
+    ("previous_run", Action::PreviousRun, "["),
+    ("next_run", Action::NextRun, "]"),
+    ("copy_last_failure", Action::CopyLastFailure, "c"),
+    ("edit_upstream", Action::EditUpstream, "u"),
+    ("toggle_privacy_mode", Action::TogglePrivacyMode, "P"),
+    ("checkpoint", Action::Checkpoint, "k"),
+    ("show_checkpoints", Action::ShowCheckpoints, "K"),
+    ("toggle_config_source", Action::ToggleConfigSource, "v"),
+    ("toggle_zoom", Action::ToggleZoom, "z"),
+    ("toggle_split", Action::ToggleSplit, "S"),
+    ("toggle_split_direction", Action::ToggleSplitDirection, "|"),
+    ("shrink_process_list", Action::ShrinkProcessList, "<"),
+    ("grow_process_list", Action::GrowProcessList, ">"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct KeyBinding {
+    key: KeyCode,
+    ctrl: bool,
+    alt: bool,
+}
+
+impl KeyBinding {
+    fn from_key_event(key_event: &KeyEvent) -> Self {
+        Self {
+            key: key_event.key,
+            ctrl: key_event.modifiers.contains(Modifiers::CTRL),
+            alt: key_event.modifiers.contains(Modifiers::ALT),
+        }
+    }
+
+    fn parse(binding: &str) -> Option<Self> {
+        let mut ctrl = false;
+        let mut alt = false;
+        let mut key = binding;
+
+        loop {
+            if let Some(rest) = strip_prefix_ignore_case(key, "ctrl+") {
+                ctrl = true;
+                key = rest;
+            } else if let Some(rest) = strip_prefix_ignore_case(key, "alt+") {
+                alt = true;
+                key = rest;
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = key.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(char), None) => KeyCode::Char(char),
+            _ => parse_named_key(&key.to_lowercase())?,
+        };
+
+        Some(Self { key, ctrl, alt })
+    }
+
+    fn is_quit(&self) -> bool {
+        self.key == KeyCode::Char('c') && self.ctrl && !self.alt
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    if value.len() > prefix.len() && value.is_char_boundary(prefix.len()) && value[..prefix.len()].eq_ignore_ascii_case(prefix) {
+        Some(&value[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+fn parse_named_key(name: &str) -> Option<KeyCode> {
+    let key = match name {
+        "up" => KeyCode::UpArrow,
+        "down" => KeyCode::DownArrow,
+        "left" => KeyCode::LeftArrow,
+        "right" => KeyCode::RightArrow,
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "escape" | "esc" => KeyCode::Escape,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        _ => {
+            let function_number: u8 = name.strip_prefix('f')?.parse().ok()?;
+            if !(1..=12).contains(&function_number) {
+                return None;
+            }
+            KeyCode::Function(function_number)
+        },
+    };
+    Some(key)
+}
+
+/// The keys bound to each action, built from the defaults and the
+/// `keybindings` table of the config.
+pub(crate) struct Keymap {
+    bindings: HashMap<KeyBinding, Action>,
+}
+
+impl Keymap {
+    /// Build the keymap, or describe the problems with the `keybindings`
+    /// table.
+    pub(crate) fn new(keybindings_config: &HashMap<String, String>) -> Result<Self, Vec<String>> {
+        let mut problems = Vec::new();
+        let mut configured_bindings: HashMap<KeyBinding, (&str, Action)> = HashMap::new();
+
+        let mut configured_action_names: Vec<&String> = keybindings_config.keys().collect();
+        configured_action_names.sort();
+
+        for action_name in configured_action_names {
+            let binding_str = &keybindings_config[action_name];
+
+            let Some((_, action, _)) = ACTIONS.iter().find(|(name, _, _)| *name == action_name.as_str()) else {
+                problems.push(format!("unknown action {action_name}"));
+                continue;
+            };
+
+            let Some(binding) = KeyBinding::parse(binding_str) else {
+                problems.push(format!("{action_name} has unknown key {binding_str:?}"));
+                continue;
+            };
+
+            if binding.is_quit() {
+                problems.push(format!("{action_name} can't use {binding_str:?}, which is reserved for quitting"));
+                continue;
+            }
+
+            if let Some((other_action_name, _)) = configured_bindings.insert(binding, (action_name.as_str(), *action)) {
+                problems.push(format!("{other_action_name} and {action_name} are both bound to {binding_str:?}"));
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+
+        let mut bindings: HashMap<KeyBinding, Action> = HashMap::new();
+
+        // Configured bindings take precedence over default bindings, so an
+        // action can be bound to the default key of another action without
+        // rebinding that other action too.
+        for (action_name, action, default_binding_str) in ACTIONS {
+            if !keybindings_config.contains_key(action_name) {
+                bindings.insert(KeyBinding::parse(default_binding_str).unwrap(), action);
+            }
+        }
+        for (binding, (_action_name, action)) in configured_bindings {
+            bindings.insert(binding, action);
+        }
+
+        Ok(Self { bindings })
+    }
+
+    pub(crate) fn action(&self, key_event: &KeyEvent) -> Option<Action> {
+        self.bindings.get(&KeyBinding::from_key_event(key_event)).copied()
+    }
+
+    /// The name of the action bound to the key when it's pressed without
+    /// modifiers, if any.
+    pub(crate) fn action_name(&self, key: char) -> Option<&'static str> {
+        let action = self.bindings.get(&KeyBinding { key: KeyCode::Char(key), ctrl: false, alt: false })?;
+        ACTIONS.iter()
+            .find(|(_, other_action, _)| other_action == action)
+            .map(|(action_name, _, _)| *action_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_event(key: KeyCode, modifiers: Modifiers) -> KeyEvent {
+        KeyEvent { key, modifiers }
+    }
+
+    fn keymap(keybindings: &[(&str, &str)]) -> Result<Keymap, Vec<String>> {
+        let keybindings_config = keybindings.iter()
+            .map(|(action_name, binding_str)| (action_name.to_string(), binding_str.to_string()))
+            .collect();
+        Keymap::new(&keybindings_config)
+    }
+
+    #[test]
+    fn single_character_is_parsed_as_key() {
+        let binding = KeyBinding::parse("r");
+
+        assert!(binding == Some(KeyBinding { key: KeyCode::Char('r'), ctrl: false, alt: false }));
+    }
+
+    #[test]
+    fn modifiers_are_parsed_ignoring_case() {
+        let binding = KeyBinding::parse("Ctrl+ALT+x");
+
+        assert!(binding == Some(KeyBinding { key: KeyCode::Char('x'), ctrl: true, alt: true }));
+    }
+
+    #[test]
+    fn named_keys_are_parsed_ignoring_case() {
+        assert!(KeyBinding::parse("PageUp").map(|binding| binding.key) == Some(KeyCode::PageUp));
+        assert!(KeyBinding::parse("esc").map(|binding| binding.key) == Some(KeyCode::Escape));
+        assert!(KeyBinding::parse("space").map(|binding| binding.key) == Some(KeyCode::Char(' ')));
+        assert!(KeyBinding::parse("F12").map(|binding| binding.key) == Some(KeyCode::Function(12)));
+    }
+
+    #[test]
+    fn unknown_keys_are_not_parsed() {
+        assert!(KeyBinding::parse("hello").is_none());
+        assert!(KeyBinding::parse("f13").is_none());
+        assert!(KeyBinding::parse("ctrl+").is_none());
+    }
+
+    #[test]
+    fn default_bindings_are_used_when_not_configured() {
+        let keymap = keymap(&[]).unwrap();
+
+        assert!(keymap.action(&key_event(KeyCode::Char('r'), Modifiers::NONE)) == Some(Action::Restart));
+        assert!(keymap.action(&key_event(KeyCode::Char('R'), Modifiers::NONE)) == Some(Action::RestartAll));
+    }
+
+    #[test]
+    fn configured_binding_replaces_default_binding_of_action() {
+        let keymap = keymap(&[("restart", "ctrl+r")]).unwrap();
+
+        assert!(keymap.action(&key_event(KeyCode::Char('r'), Modifiers::CTRL)) == Some(Action::Restart));
+        assert!(keymap.action(&key_event(KeyCode::Char('r'), Modifiers::NONE)).is_none());
+    }
+
+    #[test]
+    fn configured_binding_takes_precedence_over_default_binding_of_other_action() {
+        let keymap = keymap(&[("restart", "x")]).unwrap();
+
+        assert!(keymap.action(&key_event(KeyCode::Char('x'), Modifiers::NONE)) == Some(Action::Restart));
+    }
+
+    #[test]
+    fn problems_are_reported_for_invalid_bindings() {
+        let problems = keymap(&[
+            ("export_screen", "r"),
+            ("frobnicate", "f"),
+            ("restart", "ctrl+c"),
+            ("restart_all", "r"),
+            ("toggle_zoom", "hello"),
+        ]).err().unwrap();
+
+        assert_eq!(problems, vec![
+            "unknown action frobnicate".to_owned(),
+            "restart can't use \"ctrl+c\", which is reserved for quitting".to_owned(),
+            "export_screen and restart_all are both bound to \"r\"".to_owned(),
+            "toggle_zoom has unknown key \"hello\"".to_owned(),
+        ]);
+    }
+}
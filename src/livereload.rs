@@ -0,0 +1,257 @@
+use std::{io::{BufRead, BufReader, Write}, net::{TcpListener, TcpStream}, sync::{mpsc::Sender, Arc, Mutex}, time::Duration};
+
+/// The GUID that is appended to the key sent by the client when accepting a
+/// WebSocket connection, as described in RFC 6455.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Connects to the server that served the script, and reloads the page when a
+/// reload message is received. If the connection is lost, such as when
+/// Mintaka is restarted, the script keeps trying to reconnect.
+const CLIENT_SCRIPT: &str = r#"(function () {
+    var url = new URL(document.currentScript.src);
+    url.protocol = "ws:";
+    url.pathname = "/";
+
+    function connect() {
+        var socket = new WebSocket(url);
+        socket.onmessage = function (event) {
+            if (event.data === "reload") {
+                location.reload();
+            }
+        };
+        socket.onclose = function () {
+            setTimeout(connect, 1000);
+        };
+    }
+
+    connect();
+})();
+"#;
+
+/// Tells browsers to reload when a process with `livereload` enabled
+/// succeeds, such as after a rebuild. Browsers connect using WebSockets by
+/// loading the script served at `/livereload.js`.
+pub(crate) struct LivereloadServer {
+    reload_tx: Sender<()>,
+}
+
+impl LivereloadServer {
+    /// Accept connections from browsers on a background thread, handling each
+    /// connection on its own thread so that a slow browser doesn't hold up
+    /// the others.
+    pub(crate) fn spawn(listener: TcpListener) -> Self {
+        let clients = Arc::new(Mutex::new(Vec::new()));
+
+        let listener_clients = Arc::clone(&clients);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                // Failures are limited to a single connection, so there's no
+                // need to stop accepting other connections.
+                if let Ok(stream) = stream {
+                    let clients = Arc::clone(&listener_clients);
+                    std::thread::spawn(move || {
+                        let _ = handle_connection(stream, &clients);
+                    });
+                }
+            }
+        });
+
+        // Reloads are sent from their own thread, since writing to a browser
+        // that has stopped reading blocks until the write times out.
+        let (reload_tx, reload_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for () in reload_rx {
+                send_reload(&clients);
+            }
+        });
+
+        Self { reload_tx }
+    }
+
+    /// Tell every connected browser to reload, without waiting for the
+    /// message to be sent.
+    pub(crate) fn broadcast_reload(&self) {
+        let _ = self.reload_tx.send(());
+    }
+}
+
+/// Send a reload message to every connected browser. Browsers that can't be
+/// sent the message are assumed to have disconnected.
+fn send_reload(clients: &Mutex<Vec<TcpStream>>) {
+    let message = "reload";
+    // A single unmasked text frame with the final fragment bit set.
+    let mut frame = vec![0x81, message.len() as u8];
+    frame.extend_from_slice(message.as_bytes());
+
+    // The clients are taken so that browsers can keep connecting while the
+    // message is sent.
+    let mut sent_clients = std::mem::take(&mut *clients.lock().unwrap());
+    sent_clients.retain_mut(|client| client.write_all(&frame).and_then(|_| client.flush()).is_ok());
+    clients.lock().unwrap().extend(sent_clients);
+}
+
+fn handle_connection(mut stream: TcpStream, clients: &Mutex<Vec<TcpStream>>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or("");
+
+    let mut websocket_key = None;
+    loop {
+        let mut header_line = String::new();
+        if reader.read_line(&mut header_line)? == 0 {
+            break;
+        }
+        let header_line = header_line.trim_end();
+        if header_line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header_line.split_once(':') {
+            if name.eq_ignore_ascii_case("sec-websocket-key") {
+                websocket_key = Some(value.trim().to_owned());
+            }
+        }
+    }
+
+    match (path, websocket_key) {
+        ("/", Some(websocket_key)) => {
+            let accept = websocket_accept(&websocket_key);
+            write!(
+                stream,
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n",
+            )?;
+            stream.flush()?;
+            clients.lock().unwrap().push(stream);
+            Ok(())
+        },
+        ("/livereload.js", _) => write_response(stream, "200 OK", "application/javascript", CLIENT_SCRIPT),
+        _ => write_response(stream, "404 Not Found", "text/plain; charset=utf-8", "Not found\n"),
+    }
+}
+
+fn write_response(mut stream: TcpStream, status: &str, content_type: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len(),
+    )?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+/// The value of the `Sec-WebSocket-Accept` header that accepts a WebSocket
+/// connection with the given `Sec-WebSocket-Key`.
+fn websocket_accept(websocket_key: &str) -> String {
+    base64_encode(&sha1(format!("{websocket_key}{WEBSOCKET_GUID}").as_bytes()))
+}
+
+/// SHA-1 is only used for the WebSocket handshake, so a simple implementation
+/// is enough.
+fn sha1(bytes: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = bytes.to_vec();
+    let bit_length = (bytes.len() as u64) * 8;
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&bit_length.to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (word, word_bytes) in words.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes(word_bytes.try_into().unwrap());
+        }
+        for index in 16..80 {
+            words[index] = (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (state_word, word) in state.iter_mut().zip([a, b, c, d, e]) {
+            *state_word = state_word.wrapping_add(word);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (digest_bytes, state_word) in digest.chunks_mut(4).zip(state) {
+        digest_bytes.copy_from_slice(&state_word.to_be_bytes());
+    }
+    digest
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = (u32::from(chunk[0]) << 16)
+            | (u32::from(chunk.get(1).copied().unwrap_or(0)) << 8)
+            | u32::from(chunk.get(2).copied().unwrap_or(0));
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[((group >> (18 - 6 * index)) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn websocket_accept_matches_example_from_rfc_6455() {
+        assert_eq!(websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn sha1_matches_test_vectors() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hex(&sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            hex(&sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+        );
+    }
+
+    #[test]
+    fn base64_encode_pads_partial_groups() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -1,22 +1,147 @@
-use std::sync::{Arc, Mutex};
+use std::{net::TcpListener, sync::{Arc, Mutex}, time::Duration};
 
+use child_registry::ChildRegistry;
+use config::HttpApiConfig;
+use host_terminal::HostTerminalFeatures;
+use keybindings::{Action, Keymap};
+use livereload::LivereloadServer;
+use messages::Locale;
+use notifications::Notifier;
+use output_lines::EchoFormat;
 use ratatui::backend::TermwizBackend;
-use termwiz::{caps::ProbeHints, input::{InputEvent, KeyEvent}, surface::Change, terminal::{buffered::BufferedTerminal, SystemTerminal, Terminal}};
-use ui::render_ui;
+use redactions::Redactor;
+use status_file::StatusFile;
+use theme::Theme;
+use termwiz::{caps::ProbeHints, input::{InputEvent, KeyEvent, MouseButtons, MouseEvent}, surface::Change, terminal::{buffered::BufferedTerminal, SystemTerminal, Terminal}};
+use ui::{render_ui, ProcessListScroll, UiLayout};
+use user_vars::StatusUserVar;
 use wezterm_term::{KeyCode, KeyModifiers};
 
-use crate::processes::Processes;
+use crate::processes::{ChangeNotifier, ProcessListEntry, Processes};
 
+mod child_registry;
+mod checkpoints;
 mod cli;
+mod clipboard;
+mod combined_log;
 mod config;
+mod config_validation;
+mod control_socket;
+mod event_log;
+mod executables;
+mod file_watches;
+mod git_branches;
+mod highlights;
+mod headless;
+mod host_terminal;
+mod http_api;
+mod keybindings;
+mod livereload;
+mod messages;
+mod notifications;
+mod output_lines;
+mod output_normalization;
+mod pane_background;
 mod processes;
 mod process_statuses;
+mod ready_checks;
+mod redactions;
+mod resource_usage;
+mod remote_control;
+mod sandbox;
+mod screen_export;
+mod secret_warnings;
+mod search;
+mod self_check;
+mod shell_environment;
+mod signals;
+mod status_file;
+mod theme;
+mod trace;
 mod ui;
+mod upstream_picker;
+mod user_vars;
+mod working_directories;
 
 fn main() {
-    let config = cli::load_config().unwrap();
+    let args = cli::parse_args();
+    if let Some(trace_path) = &args.trace {
+        trace::start(trace_path).unwrap();
+    }
+
+    match args.command {
+        None | Some(cli::CliCommand::Run { ci: false, .. }) => {},
+        Some(cli::CliCommand::Run { ci: true, color, ref prefix_format }) => {
+            let echo_format = EchoFormat {
+                color: color.use_color(),
+                prefix_format: prefix_format.clone(),
+            };
+            run_headless(&args, echo_format);
+            return;
+        },
+        Some(cli::CliCommand::Recover) => {
+            recover(&args);
+            return;
+        },
+        Some(cli::CliCommand::Ctl { ref command }) => {
+            ctl(&args, command);
+            return;
+        },
+        Some(cli::CliCommand::Check) => {
+            check(&args);
+            return;
+        },
+        Some(cli::CliCommand::NotifyTest) => {
+            notify_test(&args);
+            return;
+        },
+        Some(cli::CliCommand::SelfCheck { check_for_updates }) => {
+            self_check::run(check_for_updates);
+            return;
+        },
+    }
+
+    let config = args.load_config().unwrap();
+
+    let validation_report = config_validation::validate_config(&config);
+    if validation_report.has_problems() {
+        eprint!("{validation_report}");
+        std::process::exit(1);
+    }
+    if config.startup_summary {
+        // The summary is shown before entering the alternate screen, so it's
+        // left in the scrollback of the terminal.
+        print!("{validation_report}");
+        println!();
+        println!("No problems found");
+        std::thread::sleep(STARTUP_SUMMARY_DURATION);
+    }
 
-    let terminal_capabilities = termwiz::caps::Capabilities::new_with_hints(ProbeHints::new_from_env().mouse_reporting(Some(false))).unwrap();
+    // Listeners are bound before the terminal is set up, so that an address
+    // that's already in use is reported without leaving the terminal in raw
+    // mode.
+    let livereload_listener = config.livereload.as_ref()
+        .map(|livereload_config| bind_or_exit(&livereload_config.listen, "the livereload server"));
+    let http_api_config = match (config.http_api.clone(), args.listen.clone()) {
+        (Some(http_api_config), Some(listen)) => Some(HttpApiConfig { listen, ..http_api_config }),
+        (None, Some(_)) => {
+            eprintln!("Couldn't serve the HTTP API: --listen requires the http_api option, with a token, in the config");
+            std::process::exit(1);
+        },
+        (http_api_config, None) => http_api_config,
+    };
+    if let Some(authentication_problem) = http_api_config.as_ref().and_then(http_api::authentication_problem) {
+        eprintln!("Couldn't serve the HTTP API: {authentication_problem}");
+        std::process::exit(1);
+    }
+    let http_api_listener = http_api_config.as_ref()
+        .map(|http_api_config| bind_or_exit(&http_api_config.listen, "the HTTP API"));
+
+    let host_terminal_features = HostTerminalFeatures::detect();
+    let probe_hints = ProbeHints::new_from_env()
+        .mouse_reporting(Some(config.mouse()))
+        .hyperlinks(Some(host_terminal_features.hyperlinks));
+    let terminal_capabilities = termwiz::caps::Capabilities::new_with_hints(probe_hints).unwrap();
     let mut terminal = SystemTerminal::new(terminal_capabilities).unwrap();
     terminal.set_raw_mode().unwrap();
     terminal.enter_alternate_screen().unwrap();
@@ -25,61 +150,665 @@ fn main() {
 
     let mut terminal = ratatui::Terminal::new(TermwizBackend::with_buffered_terminal(buffered_terminal)).unwrap();
 
-    let mut processes = Processes::new(terminal_waker);
+    let confirm_quit = config.confirm_quit();
+
+    let keymap = Keymap::new(&config.keybindings).unwrap();
+
+    let profiler_command = config.profiler_command();
+
+    let process_list_max_width = config.process_list_max_width();
+
+    let redactor = Redactor::new(&config.redactions).unwrap();
+
+    let theme = Theme::new(&config.theme).unwrap();
+
+    let locale = Locale::detect(config.locale.as_deref());
+
+    let mut status_user_var = if config.status_user_var {
+        Some(StatusUserVar::new())
+    } else {
+        None
+    };
+
+    let mut status_file = config.status_file.clone().map(StatusFile::spawn);
+
+    let mut child_registry = ChildRegistry::new(args.config_path());
+
+    let mut notifier = config.notifications.as_ref()
+        .map(|notifications_config| Notifier::spawn(notifications_config, ChangeNotifier::Terminal(terminal_waker.clone())));
+
+    // The UI is woken every minute so that the uptimes of processes are kept
+    // up to date.
+    std::thread::spawn({
+        let on_change = ChangeNotifier::Terminal(terminal_waker.clone());
+        move || loop {
+            std::thread::sleep(UPTIME_REFRESH_INTERVAL);
+            on_change.wake();
+        }
+    });
+
+    let mut processes = Processes::new(ChangeNotifier::Terminal(terminal_waker.clone()));
+    if config.secret_warnings {
+        processes.enable_secret_warnings();
+    }
+    if config.process_list_git_branches {
+        processes.show_git_branches_in_process_list();
+    }
+    if let Some(livereload_listener) = livereload_listener {
+        processes.set_livereload_server(LivereloadServer::spawn(livereload_listener));
+    }
+    if let Some(warning) = validation_report.first_warning() {
+        processes.set_status_message(format!("Config warning: {warning}"));
+    }
+    let orphaned_children = child_registry.orphaned_children();
+    if !orphaned_children.is_empty() {
+        processes.set_status_message(format!(
+            "Processes from a previous run are still running: {}. Run `mintaka --config {} recover` after quitting to stop them.",
+            orphaned_children.join(", "),
+            args.config_path().display(),
+        ));
+    }
     for process_config in config.processes {
         processes.start_process(process_config).unwrap();
     }
     let processes = Arc::new(Mutex::new(processes));
+    resource_usage::spawn(Arc::clone(&processes), ChangeNotifier::Terminal(terminal_waker.clone()));
+    git_branches::spawn(Arc::clone(&processes), ChangeNotifier::Terminal(terminal_waker));
+
+    if let (Some(http_api_listener), Some(http_api_config)) = (http_api_listener, http_api_config) {
+        http_api::spawn(http_api_listener, http_api_config, Arc::clone(&processes));
+    }
+    // The control socket is a convenience, so failing to listen on it, such as
+    // on platforms without Unix domain sockets, shouldn't stop Mintaka.
+    let control_socket = control_socket::spawn(args.config_path(), Arc::clone(&processes)).ok();
+
+    // When the host terminal stops responding, such as when an SSH connection
+    // drops, the processes are kept running until the terminal can be used
+    // again.
+    let mut terminal_detached = false;
+
+    let mut ui_layout = UiLayout::default();
+    let mut process_list_scroll = ProcessListScroll::default();
 
     loop {
         {
+            let lock_span = trace::Span::start();
             let mut processes_locked = processes.lock().unwrap();
+            lock_span.end("processes_lock_wait", &[]);
             processes_locked.do_work().unwrap();
+            child_registry.update(&processes_locked);
+            if processes_locked.is_shut_down() {
+                break;
+            }
         }
 
-        render_ui(&processes, &mut terminal);
+        if terminal_detached {
+            std::thread::sleep(TERMINAL_REATTACH_INTERVAL);
+            if reattach_terminal(&mut terminal).is_err() {
+                continue;
+            }
+            trace::event("terminal_reattached", &[]);
+            terminal_detached = false;
+        }
+
+        let frame_span = trace::Span::start();
+        let render_result = render_ui(&processes, args.config_path(), process_list_max_width, &host_terminal_features, &redactor, locale, &theme, &mut process_list_scroll, &mut terminal);
+        frame_span.end("frame_rendered", &[]);
+        trace::flush();
+        match render_result {
+            Ok(rendered_ui_layout) => {
+                ui_layout = rendered_ui_layout;
+            },
+            Err(error) => {
+                trace::event("terminal_detached", &[("error", &error)]);
+                terminal_detached = true;
+                continue;
+            },
+        }
 
         let buffered_terminal = terminal.backend_mut().buffered_terminal_mut();
-        match buffered_terminal.terminal().poll_input(None).unwrap() {
+
+        if let Some(status_user_var) = &mut status_user_var {
+            let failure_count = processes.lock().unwrap().alerting_failure_count();
+            status_user_var.update(failure_count, buffered_terminal.terminal());
+        }
+
+        if let Some(status_file) = &mut status_file {
+            status_file.update(&processes.lock().unwrap());
+        }
+
+        if let Some(notifier) = &mut notifier {
+            let mut processes = processes.lock().unwrap();
+            notifier.update(&processes);
+            if let Some(status_message) = notifier.take_status_message() {
+                processes.set_status_message(status_message);
+            }
+        }
+
+        let input = match buffered_terminal.terminal().poll_input(None) {
+            Ok(input) => input,
+            Err(error) => {
+                trace::event("terminal_detached", &[("error", &error)]);
+                terminal_detached = true;
+                continue;
+            },
+        };
+
+        match input {
             Some(InputEvent::Resized { rows, cols }) => {
                 // FIXME: this is working around a bug where we don't realize
                 // that we should redraw everything on resize in BufferedTerminal.
                 buffered_terminal.add_change(Change::ClearScreen(Default::default()));
                 buffered_terminal.resize(cols, rows);
             }
+            Some(InputEvent::Mouse(mouse_event)) => {
+                let mut processes = processes.lock().unwrap();
+                handle_mouse_event(mouse_event, &ui_layout, &mut process_list_scroll, &mut processes);
+            }
             Some(input) => {
                 if let InputEvent::Key(key_event) = input {
                     if matches!(
                         key_event,
                         KeyEvent { key: KeyCode::Char('c'), modifiers: KeyModifiers::CTRL}
                     ) {
-                        return;
+                        let mut processes = processes.lock().unwrap();
+                        if processes.shutdown().is_some() {
+                            // Quit without waiting for the processes to stop.
+                            break;
+                        }
+                        if !confirm_quit || processes.request_quit() {
+                            processes.start_shutdown();
+                        }
+                        continue;
+                    }
+
+                    if processes.lock().unwrap().shutdown().is_some() {
+                        continue;
                     }
 
-                    match key_event.key {
-                        wezterm_term::KeyCode::UpArrow => {
+                    if processes.lock().unwrap().cancel_quit() {
+                        continue;
+                    }
+
+                    processes.lock().unwrap().clear_status_message();
+
+                    if handle_upstream_picker_key(&key_event, &mut processes.lock().unwrap()) {
+                        continue;
+                    }
+
+                    if handle_checkpoint_key(&key_event, &mut processes.lock().unwrap()) {
+                        continue;
+                    }
+
+                    if handle_clone_key(&key_event, &mut processes.lock().unwrap()) {
+                        continue;
+                    }
+
+                    if handle_search_key(&key_event, &mut processes.lock().unwrap()) {
+                        continue;
+                    }
+
+                    if let KeyCode::Char(key) = key_event.key {
+                        if !key_event.modifiers.intersects(KeyModifiers::CTRL | KeyModifiers::ALT) && processes.lock().unwrap().send_quick_key_to_focused(key) {
+                            continue;
+                        }
+                    }
+
+                    match keymap.action(&key_event) {
+                        Some(Action::FocusUp) => {
                             let mut processes = processes.lock().unwrap();
                             processes.disable_autofocus();
                             processes.move_focus_up();
                         },
-                        wezterm_term::KeyCode::DownArrow => {
+                        Some(Action::FocusDown) => {
                             let mut processes = processes.lock().unwrap();
                             processes.disable_autofocus();
                             processes.move_focus_down();
                         },
-                        wezterm_term::KeyCode::Char('a') => {
+                        Some(Action::CollapseGroup) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.collapse_focused_group();
+                        },
+                        Some(Action::ExpandGroup) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.expand_focused_group();
+                        },
+                        Some(Action::ToggleAutofocus) => {
                             let mut processes = processes.lock().unwrap();
                             processes.toggle_autofocus();
                         }
-                        wezterm_term::KeyCode::Char('r') => {
+                        Some(Action::Restart) => {
                             let mut processes = processes.lock().unwrap();
                             processes.restart_focused();
                         }
-                        _ => {},
+                        Some(Action::SendCtrlD) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.send_ctrl_d_to_focused();
+                        }
+                        Some(Action::ToggleReadonly) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.toggle_focused_readonly();
+                        }
+                        Some(Action::ToggleWaterfall) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.toggle_waterfall();
+                        }
+                        Some(Action::ToggleEventLog) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.toggle_event_log();
+                        }
+                        Some(Action::ReloadConfig) => {
+                            let mut processes = processes.lock().unwrap();
+                            reload_config(&args, &mut processes);
+                        }
+                        Some(Action::ExportScreen) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.export_focused_screen();
+                        }
+                        Some(Action::SaveOutput) => {
+                            let mut processes = processes.lock().unwrap();
+                            let path = processes.focused_export_path("log");
+                            processes.dump_focused_scrollback(&path, false);
+                        }
+                        Some(Action::SaveOutputWithColors) => {
+                            let mut processes = processes.lock().unwrap();
+                            let path = processes.focused_export_path("ansi.log");
+                            processes.dump_focused_scrollback(&path, true);
+                        }
+                        Some(Action::Profile) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.profile_focused(&profiler_command);
+                        }
+                        Some(Action::CloneProcess) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.start_clone();
+                        }
+                        Some(Action::Search) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.start_search();
+                        }
+                        Some(Action::SearchEarlier) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.jump_to_search_match(true);
+                        }
+                        Some(Action::SearchLater) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.jump_to_search_match(false);
+                        }
+                        Some(Action::PreviousRun) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.jump_to_run(true);
+                        }
+                        Some(Action::NextRun) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.jump_to_run(false);
+                        }
+                        Some(Action::CopyLastFailure) => {
+                            let mut processes = processes.lock().unwrap();
+                            let status_message = match processes.focused_last_failure_output() {
+                                None => "No failed run to copy".to_owned(),
+                                Some(output) => match clipboard::copy_to_clipboard(&output, buffered_terminal.terminal()) {
+                                    Ok(()) => "Copied output of last failed run".to_owned(),
+                                    Err(error) => format!("Failed to copy output: {error}"),
+                                },
+                            };
+                            processes.set_status_message(status_message);
+                        }
+                        Some(Action::EditUpstream) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.start_upstream_picker();
+                        }
+                        Some(Action::TogglePrivacyMode) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.toggle_privacy_mode();
+                        }
+                        Some(Action::Checkpoint) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.start_checkpoint();
+                        }
+                        Some(Action::ShowCheckpoints) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.open_checkpoint_browser();
+                        }
+                        Some(Action::ToggleConfigSource) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.toggle_config_source();
+                        }
+                        Some(Action::ToggleStopped) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.toggle_focused_stopped();
+                        }
+                        Some(Action::RestartAll) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.restart_all();
+                        }
+                        Some(Action::ToggleZoom) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.toggle_zoomed();
+                        }
+                        Some(Action::ToggleSplit) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.toggle_split();
+                        }
+                        Some(Action::ToggleSplitDirection) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.toggle_split_direction();
+                        }
+                        Some(Action::ShrinkProcessList) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.set_process_list_width(ui_layout.process_list_label_width().saturating_sub(PROCESS_LIST_WIDTH_STEP));
+                        }
+                        Some(Action::GrowProcessList) => {
+                            let mut processes = processes.lock().unwrap();
+                            processes.set_process_list_width(ui_layout.process_list_label_width() + PROCESS_LIST_WIDTH_STEP);
+                        }
+                        None => {
+                            if let Some(process_index) = process_number_key(&key_event) {
+                                processes.lock().unwrap().focus(process_index);
+                            }
+                        },
                     }
                 }
             },
             None => {}
         }
     }
+
+    trace::flush();
+    child_registry.remove();
+    if let Some(control_socket) = control_socket {
+        control_socket.remove();
+    }
+    if let Some(status_file) = status_file {
+        status_file.remove();
+    }
+}
+
+/// Handle a key while choosing an upstream process, returning whether the key
+/// was used by the picker.
+fn handle_upstream_picker_key(key_event: &KeyEvent, processes: &mut Processes) -> bool {
+    if processes.upstream_picker().is_none() {
+        return false;
+    }
+
+    match key_event.key {
+        KeyCode::UpArrow => processes.move_upstream_picker_selection_up(),
+        KeyCode::DownArrow => processes.move_upstream_picker_selection_down(),
+        KeyCode::Enter => processes.finish_upstream_picker(),
+        KeyCode::Escape => processes.cancel_upstream_picker(),
+        _ => {},
+    }
+    true
+}
+
+/// Handle a key while naming a checkpoint or browsing checkpoints, returning
+/// whether the key was used.
+fn handle_checkpoint_key(key_event: &KeyEvent, processes: &mut Processes) -> bool {
+    if processes.checkpoint_name().is_some() {
+        match key_event.key {
+            KeyCode::Char(char) if !key_event.modifiers.intersects(KeyModifiers::CTRL | KeyModifiers::ALT) => {
+                processes.push_checkpoint_name_char(char);
+            },
+            KeyCode::Backspace => processes.pop_checkpoint_name_char(),
+            KeyCode::Enter => processes.finish_checkpoint(),
+            KeyCode::Escape => processes.cancel_checkpoint(),
+            _ => {},
+        }
+        true
+    } else if processes.checkpoint_browser().is_some() {
+        match key_event.key {
+            KeyCode::UpArrow => processes.move_checkpoint_selection_up(),
+            KeyCode::DownArrow => processes.move_checkpoint_selection_down(),
+            KeyCode::Enter => processes.show_checkpoint_diff(),
+            KeyCode::Escape => processes.back_from_checkpoint_browser(),
+            _ => {},
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// Handle a key while typing the changes for cloning a process, returning
+/// whether the key was used.
+fn handle_clone_key(key_event: &KeyEvent, processes: &mut Processes) -> bool {
+    if processes.clone_changes().is_none() {
+        return false;
+    }
+
+    match key_event.key {
+        KeyCode::Char(char) if !key_event.modifiers.intersects(KeyModifiers::CTRL | KeyModifiers::ALT) => {
+            processes.push_clone_char(char);
+        },
+        KeyCode::Backspace => processes.pop_clone_char(),
+        KeyCode::Enter => processes.finish_clone(),
+        KeyCode::Escape => processes.cancel_clone(),
+        _ => {},
+    }
+    true
+}
+
+/// The index of the process chosen by pressing its number in the process list,
+/// from `1` to `9`.
+fn process_number_key(key_event: &KeyEvent) -> Option<usize> {
+    if key_event.modifiers.intersects(KeyModifiers::CTRL | KeyModifiers::ALT) {
+        return None;
+    }
+    match key_event.key {
+        KeyCode::Char(char @ '1'..='9') => char.to_digit(10).map(|digit| digit as usize - 1),
+        _ => None,
+    }
+}
+
+/// Handle a key while searching, returning whether the key was used by the
+/// search.
+fn handle_search_key(key_event: &KeyEvent, processes: &mut Processes) -> bool {
+    let Some(search) = processes.search() else {
+        return false;
+    };
+
+    if search.editing {
+        match key_event.key {
+            KeyCode::Char(char) if !key_event.modifiers.intersects(KeyModifiers::CTRL | KeyModifiers::ALT) => {
+                processes.push_search_char(char);
+            },
+            KeyCode::Backspace => processes.pop_search_char(),
+            KeyCode::Enter => processes.finish_search_query(),
+            KeyCode::Escape => processes.cancel_search(),
+            _ => {},
+        }
+        true
+    } else if key_event.key == KeyCode::Escape {
+        processes.cancel_search();
+        true
+    } else {
+        false
+    }
+}
+
+/// How many rows to scroll the process pane for each step of the mouse wheel.
+const MOUSE_WHEEL_SCROLL_ROWS: usize = 3;
+
+fn handle_mouse_event(mouse_event: MouseEvent, ui_layout: &UiLayout, process_list_scroll: &mut ProcessListScroll, processes: &mut Processes) {
+    // Mouse positions are reported starting from one.
+    let x = mouse_event.x.saturating_sub(1);
+    let y = mouse_event.y.saturating_sub(1);
+
+    if mouse_event.mouse_buttons == MouseButtons::LEFT {
+        match ui_layout.process_list_entry_at(processes, x, y) {
+            Some(ProcessListEntry::CombinedLog) => processes.focus_combined_log(),
+            Some(ProcessListEntry::Group(group)) => processes.toggle_group_collapsed(&group),
+            Some(ProcessListEntry::Process(process_index)) => processes.focus(process_index),
+            None => {},
+        }
+    } else if mouse_event.mouse_buttons.contains(MouseButtons::VERT_WHEEL) && ui_layout.is_in_process_list(x, y) {
+        if mouse_event.mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+            process_list_scroll.scroll_by(-1);
+        } else {
+            process_list_scroll.scroll_by(1);
+        }
+    } else if mouse_event.mouse_buttons.contains(MouseButtons::VERT_WHEEL) && ui_layout.is_in_process_pane(x, y) {
+        if mouse_event.mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+            processes.scroll_focused_back(MOUSE_WHEEL_SCROLL_ROWS);
+        } else {
+            processes.scroll_focused_forward(MOUSE_WHEEL_SCROLL_ROWS);
+        }
+    }
+}
+
+/// How many columns the process list grows or shrinks by each time.
+const PROCESS_LIST_WIDTH_STEP: usize = 2;
+
+const TERMINAL_REATTACH_INTERVAL: Duration = Duration::from_millis(500);
+
+const UPTIME_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long the startup summary is shown before the UI appears.
+const STARTUP_SUMMARY_DURATION: Duration = Duration::from_secs(2);
+
+/// Stop the processes left running by a previous run of Mintaka.
+fn recover(args: &cli::CliArgs) {
+    match child_registry::recover(args.config_path()) {
+        Ok(report) if report.is_empty() => println!("No processes to stop"),
+        Ok(report) => {
+            for line in report {
+                println!("{line}");
+            }
+        },
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        },
+    }
+}
+
+fn ctl(args: &cli::CliArgs, command: &cli::CtlCommand) {
+    match control_socket::send_request(args.config_path(), &command.request_line()) {
+        Ok(output) => print!("{output}"),
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        },
+    }
+}
+
+fn run_headless(args: &cli::CliArgs, echo_format: EchoFormat) {
+    let config = args.load_config().unwrap();
+
+    let validation_report = config_validation::validate_config(&config);
+    if validation_report.has_problems() {
+        eprint!("{validation_report}");
+        std::process::exit(1);
+    }
+
+    let succeeded = headless::run(config, echo_format);
+    trace::flush();
+    if !succeeded {
+        std::process::exit(1);
+    }
+}
+
+fn check(args: &cli::CliArgs) {
+    let config = match args.load_config() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}: {error}", args.config_path().display());
+            std::process::exit(1);
+        },
+    };
+
+    let validation_report = config_validation::validate_config(&config);
+    print!("{validation_report}");
+    if validation_report.has_problems() {
+        std::process::exit(1);
+    }
+    println!();
+    println!("No problems found");
+}
+
+fn notify_test(args: &cli::CliArgs) {
+    let config = match args.load_config() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}: {error}", args.config_path().display());
+            std::process::exit(1);
+        },
+    };
+
+    let Some(notifications_config) = &config.notifications else {
+        eprintln!("No notification backends are configured");
+        std::process::exit(1);
+    };
+    let backends = notifications::notification_backends(notifications_config, Arc::new(Mutex::new(None)), None);
+    if backends.is_empty() {
+        eprintln!("No notification backends are configured");
+        std::process::exit(1);
+    }
+
+    let notification = notifications::Notification {
+        event: config::NotificationEvent::Failure,
+        process_name: "notify-test".to_owned(),
+    };
+    let mut any_failed = false;
+    for (backend, _backend_config) in backends {
+        match backend.send(&notification) {
+            Ok(()) => println!("{}: sent", backend.name()),
+            Err(error) => {
+                println!("{}: failed: {error}", backend.name());
+                any_failed = true;
+            },
+        }
+    }
+    if any_failed {
+        std::process::exit(1);
+    }
+}
+
+fn bind_or_exit(address: &str, description: &str) -> TcpListener {
+    match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Couldn't listen on {address} for {description}: {error}");
+            std::process::exit(1);
+        },
+    }
+}
+
+/// Restore the state of the host terminal, and redraw everything, since
+/// anything could have happened to the terminal while it was detached.
+fn reattach_terminal(terminal: &mut ratatui::Terminal<TermwizBackend>) -> termwiz::Result<()> {
+    let buffered_terminal = terminal.backend_mut().buffered_terminal_mut();
+    let system_terminal = buffered_terminal.terminal();
+    system_terminal.set_raw_mode()?;
+    system_terminal.enter_alternate_screen()?;
+    let screen_size = system_terminal.get_screen_size()?;
+
+    buffered_terminal.add_change(Change::ClearScreen(Default::default()));
+    buffered_terminal.resize(screen_size.cols, screen_size.rows);
+    buffered_terminal.repaint()?;
+
+    terminal.clear()?;
+
+    Ok(())
+}
+
+fn reload_config(args: &cli::CliArgs, processes: &mut Processes) {
+    let config = match args.load_config() {
+        Ok(config) => config,
+        Err(error) => {
+            processes.set_status_message(format!("Failed to reload config: {error}"));
+            return;
+        },
+    };
+
+    let validation_report = config_validation::validate_config(&config);
+    if let Some(problem) = validation_report.first_problem() {
+        processes.set_status_message(format!("Failed to reload config: {problem}"));
+        return;
+    }
+
+    match processes.replace_processes(config.processes) {
+        Ok(()) => processes.set_status_message("Reloaded config".to_owned()),
+        Err(error) => processes.set_status_message(format!("Failed to reload config: {error}")),
+    }
 }
@@ -0,0 +1,240 @@
+/// The language that the UI is shown in.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Locale {
+    #[default]
+    English,
+    German,
+}
+
+impl Locale {
+    /// Use the locale set in the config, falling back to the locale of the
+    /// environment, and then to English.
+    pub(crate) fn detect(configured_locale: Option<&str>) -> Self {
+        if let Some(configured_locale) = configured_locale {
+            return Self::from_tag(configured_locale).unwrap_or(Locale::English);
+        }
+
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|variable_name| std::env::var(variable_name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_tag(&value))
+            .unwrap_or(Locale::English)
+    }
+
+    /// Parse a tag such as `de`, `de-AT` or `de_DE.UTF-8`, using only the
+    /// language.
+    pub(crate) fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['_', '-', '.', '@']).next().unwrap_or("");
+        match language.to_ascii_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Locale::English),
+            "de" => Some(Locale::German),
+            _ => None,
+        }
+    }
+}
+
+/// A string shown in the UI. Translations may be longer than the English
+/// text, so the width of a message should be measured after translating it,
+/// rather than assumed.
+pub(crate) enum Message<'a> {
+    AllProcessesLabel,
+    StatusInactive,
+    StatusWaiting,
+    StatusRunning,
+    StatusSuccess,
+    StatusReady,
+    StatusErrors,
+    StatusExited { exit_code: u32 },
+    StatusExitedUnexpectedly { exit_code: u32 },
+    StatusStartFailed,
+    StatusStartsIn { seconds: u64 },
+    Sandboxed,
+    FailedToStart { error: &'a str },
+
+    FocusAuto,
+    FocusManual,
+    FocusLabel { focus: &'a str },
+    ConfigLabel { path: &'a str },
+    ProcessLabel { process_name: &'a str },
+    PrivacyMode,
+    Zoomed,
+    ScrolledBack { line_count: usize },
+    ModeMain,
+    ModeHistory { percentage: usize },
+    ModeTyping,
+    ModeMenu,
+    ProcessStats { uptime: &'a str, restart_count: usize },
+    CheckpointNamePrompt { name: &'a str },
+    ClonePrompt { process_name: &'a str, changes: &'a str },
+    SearchPrompt { query: &'a str },
+    SearchHint { query: &'a str },
+
+    QuitWillInterrupt,
+    QuitHint,
+    Stopping,
+    StoppingHint,
+
+    StartupWaterfall,
+    NotStarted,
+    ReadyIn { started_seconds: f64, ready_seconds: f64 },
+    NotReady { started_seconds: f64 },
+    After { upstream: &'a str },
+
+    RunAfter { process_name: &'a str },
+    NoUpstream,
+    ChooseOrCancel,
+
+    ChangesSince { process_name: &'a str, checkpoint_name: &'a str },
+    ProcessMissingFromCheckpoint,
+    Back,
+    Checkpoints,
+    CompareWithCheckpoint { process_name: &'a str },
+
+    EventLog,
+    AllProcesses,
+
+    ConfigOf { process_name: &'a str },
+    NotLoadedFromConfigFile,
+    WorkingDirectory { path: &'a str },
+    GitBranch { branch: &'a str },
+}
+
+impl Message<'_> {
+    pub(crate) fn text(&self, locale: Locale) -> String {
+        match locale {
+            Locale::English => self.english(),
+            Locale::German => self.german(),
+        }
+    }
+
+    fn english(&self) -> String {
+        match self {
+            Message::AllProcessesLabel => "All".to_owned(),
+            Message::StatusInactive => "INACTIVE".to_owned(),
+            Message::StatusWaiting => "WAITING".to_owned(),
+            Message::StatusRunning => "RUNNING".to_owned(),
+            Message::StatusSuccess => "SUCCESS".to_owned(),
+            Message::StatusReady => "READY".to_owned(),
+            Message::StatusErrors => "ERR".to_owned(),
+            Message::StatusExited { exit_code } => format!("EXIT {exit_code}"),
+            Message::StatusExitedUnexpectedly { exit_code } => format!("UNEXPECTED EXIT {exit_code}"),
+            Message::StatusStartFailed => "START FAILED".to_owned(),
+            Message::StatusStartsIn { seconds } => format!("STARTS IN {seconds}s"),
+            Message::Sandboxed => "SANDBOXED".to_owned(),
+            Message::FailedToStart { error } => format!("Failed to start: {error}"),
+
+            Message::FocusAuto => "Auto".to_owned(),
+            Message::FocusManual => "Manual".to_owned(),
+            Message::FocusLabel { focus } => format!("Focus: {focus}"),
+            Message::ConfigLabel { path } => format!("Config: {path}"),
+            Message::ProcessLabel { process_name } => format!("Process: {process_name}"),
+            Message::PrivacyMode => "Privacy mode".to_owned(),
+            Message::Zoomed => "Zoomed".to_owned(),
+            Message::ScrolledBack { line_count } => format!("Scrolled back: {line_count} lines"),
+            Message::ModeMain => "MAIN".to_owned(),
+            Message::ModeHistory { percentage } => format!("HISTORY {percentage}%"),
+            Message::ModeTyping => "TYPING".to_owned(),
+            Message::ModeMenu => "MENU".to_owned(),
+            Message::ProcessStats { uptime, restart_count } => format!("Up {uptime}, restarted {restart_count} times"),
+            Message::CheckpointNamePrompt { name } => format!("Checkpoint name: {name}"),
+            Message::ClonePrompt { process_name, changes } => format!("Clone {process_name} with arguments or NAME=value: {changes}"),
+            Message::SearchPrompt { query } => format!("Search: {query}"),
+            Message::SearchHint { query } => format!("Search: {query} (n: earlier, N: later)"),
+
+            Message::QuitWillInterrupt => "Quitting will interrupt:".to_owned(),
+            Message::QuitHint => "Press Ctrl+c again to quit, or any other key to cancel.".to_owned(),
+            Message::Stopping => "Stopping…".to_owned(),
+            Message::StoppingHint => "Press Ctrl+c again to quit without waiting.".to_owned(),
+
+            Message::StartupWaterfall => "Startup waterfall".to_owned(),
+            Message::NotStarted => "not started".to_owned(),
+            Message::ReadyIn { started_seconds, ready_seconds } => format!("+{started_seconds:.1}s, ready in {ready_seconds:.1}s"),
+            Message::NotReady { started_seconds } => format!("+{started_seconds:.1}s, not ready"),
+            Message::After { upstream } => format!("(after {upstream})"),
+
+            Message::RunAfter { process_name } => format!("Run {process_name} after:"),
+            Message::NoUpstream => "(no upstream)".to_owned(),
+            Message::ChooseOrCancel => "Enter: choose, Escape: cancel".to_owned(),
+
+            Message::ChangesSince { process_name, checkpoint_name } => format!("Changes to {process_name} since {checkpoint_name}"),
+            Message::ProcessMissingFromCheckpoint => "The process didn't exist when the checkpoint was saved.".to_owned(),
+            Message::Back => "Escape: back".to_owned(),
+            Message::Checkpoints => "Checkpoints".to_owned(),
+            Message::CompareWithCheckpoint { process_name } => format!("Enter: compare {process_name} with checkpoint, Escape: close"),
+
+            Message::EventLog => "Event log".to_owned(),
+            Message::AllProcesses => "All processes".to_owned(),
+
+            Message::ConfigOf { process_name } => format!("Config of {process_name}"),
+            Message::NotLoadedFromConfigFile => "This process wasn't loaded from a config file.".to_owned(),
+            Message::WorkingDirectory { path } => format!("Working directory: {path}"),
+            Message::GitBranch { branch } => format!("Git branch: {branch}"),
+        }
+    }
+
+    fn german(&self) -> String {
+        match self {
+            Message::AllProcessesLabel => "Alle".to_owned(),
+            Message::StatusInactive => "INAKTIV".to_owned(),
+            Message::StatusWaiting => "WARTET".to_owned(),
+            Message::StatusRunning => "LÄUFT".to_owned(),
+            Message::StatusSuccess => "ERFOLGREICH".to_owned(),
+            Message::StatusReady => "BEREIT".to_owned(),
+            Message::StatusErrors => "FEHLER".to_owned(),
+            Message::StatusExited { exit_code } => format!("BEENDET {exit_code}"),
+            Message::StatusExitedUnexpectedly { exit_code } => format!("UNERWARTET BEENDET {exit_code}"),
+            Message::StatusStartFailed => "START FEHLGESCHLAGEN".to_owned(),
+            Message::StatusStartsIn { seconds } => format!("START IN {seconds}s"),
+            Message::Sandboxed => "IN SANDBOX".to_owned(),
+            Message::FailedToStart { error } => format!("Start fehlgeschlagen: {error}"),
+
+            Message::FocusAuto => "Automatisch".to_owned(),
+            Message::FocusManual => "Manuell".to_owned(),
+            Message::FocusLabel { focus } => format!("Fokus: {focus}"),
+            Message::ConfigLabel { path } => format!("Konfiguration: {path}"),
+            Message::ProcessLabel { process_name } => format!("Prozess: {process_name}"),
+            Message::PrivacyMode => "Datenschutzmodus".to_owned(),
+            Message::Zoomed => "Vergrößert".to_owned(),
+            Message::ScrolledBack { line_count } => format!("Zurückgescrollt: {line_count} Zeilen"),
+            Message::ModeMain => "HAUPT".to_owned(),
+            Message::ModeHistory { percentage } => format!("VERLAUF {percentage}%"),
+            Message::ModeTyping => "EINGABE".to_owned(),
+            Message::ModeMenu => "MENÜ".to_owned(),
+            Message::ProcessStats { uptime, restart_count } => format!("Läuft seit {uptime}, {restart_count} Mal neu gestartet"),
+            Message::CheckpointNamePrompt { name } => format!("Name des Checkpoints: {name}"),
+            Message::ClonePrompt { process_name, changes } => format!("{process_name} klonen mit Argumenten oder NAME=Wert: {changes}"),
+            Message::SearchPrompt { query } => format!("Suche: {query}"),
+            Message::SearchHint { query } => format!("Suche: {query} (n: früher, N: später)"),
+
+            Message::QuitWillInterrupt => "Beenden unterbricht:".to_owned(),
+            Message::QuitHint => "Zum Beenden erneut Strg+c drücken, zum Abbrechen eine andere Taste.".to_owned(),
+            Message::Stopping => "Wird beendet…".to_owned(),
+            Message::StoppingHint => "Zum Beenden ohne Warten erneut Strg+c drücken.".to_owned(),
+
+            Message::StartupWaterfall => "Startverlauf".to_owned(),
+            Message::NotStarted => "nicht gestartet".to_owned(),
+            Message::ReadyIn { started_seconds, ready_seconds } => format!("+{started_seconds:.1}s, bereit nach {ready_seconds:.1}s"),
+            Message::NotReady { started_seconds } => format!("+{started_seconds:.1}s, nicht bereit"),
+            Message::After { upstream } => format!("(nach {upstream})"),
+
+            Message::RunAfter { process_name } => format!("{process_name} ausführen nach:"),
+            Message::NoUpstream => "(kein Vorgänger)".to_owned(),
+            Message::ChooseOrCancel => "Enter: auswählen, Escape: abbrechen".to_owned(),
+
+            Message::ChangesSince { process_name, checkpoint_name } => format!("Änderungen an {process_name} seit {checkpoint_name}"),
+            Message::ProcessMissingFromCheckpoint => "Der Prozess existierte nicht, als der Checkpoint gespeichert wurde.".to_owned(),
+            Message::Back => "Escape: zurück".to_owned(),
+            Message::Checkpoints => "Checkpoints".to_owned(),
+            Message::CompareWithCheckpoint { process_name } => format!("Enter: {process_name} mit Checkpoint vergleichen, Escape: schließen"),
+
+            Message::EventLog => "Ereignisprotokoll".to_owned(),
+            Message::AllProcesses => "Alle Prozesse".to_owned(),
+
+            Message::ConfigOf { process_name } => format!("Konfiguration von {process_name}"),
+            Message::NotLoadedFromConfigFile => "Dieser Prozess wurde nicht aus einer Konfigurationsdatei geladen.".to_owned(),
+            Message::WorkingDirectory { path } => format!("Arbeitsverzeichnis: {path}"),
+            Message::GitBranch { branch } => format!("Git-Branch: {branch}"),
+        }
+    }
+}
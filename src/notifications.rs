@@ -0,0 +1,283 @@
+use std::{collections::HashSet, io::{Read, Write}, process::{Command, Stdio}, sync::{mpsc::Sender, Arc, Mutex}};
+
+use crate::{config::{NotificationBackendConfig, NotificationEvent, NotificationsConfig}, processes::{ChangeNotifier, Processes}, ready_checks, remote_control::json_string};
+
+/// Something that happened to a process that the user may want to know about
+/// without looking at Mintaka.
+pub(crate) struct Notification {
+    pub(crate) event: NotificationEvent,
+    pub(crate) process_name: String,
+}
+
+impl Notification {
+    pub(crate) fn message(&self) -> String {
+        match self.event {
+            NotificationEvent::Failure => format!("{} failed", self.process_name),
+            NotificationEvent::Recovery => format!("{} recovered", self.process_name),
+        }
+    }
+}
+
+/// A way of delivering notifications, such as a desktop notification.
+pub(crate) trait NotificationBackend: Send {
+    /// The name of the backend in the config.
+    fn name(&self) -> &'static str;
+
+    /// Deliver the notification, or describe why it couldn't be delivered.
+    fn send(&self, notification: &Notification) -> Result<(), String>;
+}
+
+/// Shows the notification in the status bar.
+struct StatusBarBackend {
+    status_message: Arc<Mutex<Option<String>>>,
+    on_change: Option<ChangeNotifier>,
+}
+
+impl NotificationBackend for StatusBarBackend {
+    fn name(&self) -> &'static str {
+        "status_bar"
+    }
+
+    fn send(&self, notification: &Notification) -> Result<(), String> {
+        *self.status_message.lock().unwrap() = Some(notification.message());
+        if let Some(on_change) = &self.on_change {
+            on_change.wake();
+        }
+        Ok(())
+    }
+}
+
+/// Shows the notification using the notification service of the desktop.
+struct DesktopBackend;
+
+impl NotificationBackend for DesktopBackend {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    #[cfg(target_os = "macos")]
+    fn send(&self, notification: &Notification) -> Result<(), String> {
+        let script = format!(
+            "display notification {} with title \"Mintaka\"",
+            json_string(&notification.message()),
+        );
+        run_command(&["osascript".to_owned(), "-e".to_owned(), script])
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn send(&self, notification: &Notification) -> Result<(), String> {
+        run_command(&["notify-send".to_owned(), "Mintaka".to_owned(), notification.message()])
+    }
+
+    #[cfg(not(unix))]
+    fn send(&self, _notification: &Notification) -> Result<(), String> {
+        Err("desktop notifications aren't supported on this platform".to_owned())
+    }
+}
+
+/// Rings the bell of the terminal that Mintaka is running in.
+struct BellBackend;
+
+impl NotificationBackend for BellBackend {
+    fn name(&self) -> &'static str {
+        "bell"
+    }
+
+    fn send(&self, _notification: &Notification) -> Result<(), String> {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(b"\x07")
+            .and_then(|()| stdout.flush())
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// POSTs the notification as JSON to a URL.
+struct WebhookBackend {
+    url: String,
+}
+
+impl NotificationBackend for WebhookBackend {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, notification: &Notification) -> Result<(), String> {
+        let http_url = ready_checks::parse_http_url(&self.url)?;
+        let Some(mut stream) = ready_checks::connect(&http_url.address) else {
+            return Err(format!("couldn't connect to {}", http_url.address));
+        };
+        let _ = stream.set_read_timeout(Some(ready_checks::PROBE_TIMEOUT));
+        let _ = stream.set_write_timeout(Some(ready_checks::PROBE_TIMEOUT));
+
+        let body = format!(
+            "{{\"event\":{},\"process\":{},\"message\":{}}}",
+            json_string(notification.event.name()),
+            json_string(&notification.process_name),
+            json_string(&notification.message()),
+        );
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            http_url.path,
+            http_url.host,
+            body.len(),
+        );
+        stream.write_all(request.as_bytes()).map_err(|error| error.to_string())?;
+
+        // Only the status line is needed.
+        let mut response = [0; 12];
+        stream.read_exact(&mut response).map_err(|error| error.to_string())?;
+        let response = String::from_utf8_lossy(&response);
+        if response.starts_with("HTTP/") && response.get(9..10) == Some("2") {
+            Ok(())
+        } else {
+            Err(format!("received response {}", response.trim()))
+        }
+    }
+}
+
+/// Runs a command, where `{name}`, `{event}` and `{message}` are replaced in
+/// each argument.
+struct CommandBackend {
+    command: Vec<String>,
+}
+
+impl NotificationBackend for CommandBackend {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn send(&self, notification: &Notification) -> Result<(), String> {
+        let message = notification.message();
+        let command: Vec<String> = self.command.iter()
+            .map(|argument| {
+                argument
+                    .replace("{name}", &notification.process_name)
+                    .replace("{event}", notification.event.name())
+                    .replace("{message}", &message)
+            })
+            .collect();
+        run_command(&command)
+    }
+}
+
+fn run_command(command: &[String]) -> Result<(), String> {
+    let Some((executable, arguments)) = command.split_first() else {
+        return Err("command is empty".to_owned());
+    };
+    let status = Command::new(executable)
+        .args(arguments)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|error| format!("couldn't run {executable}: {error}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{executable} failed with {status}"))
+    }
+}
+
+/// The backends enabled in the config, along with the config of each.
+pub(crate) fn notification_backends(
+    notifications_config: &NotificationsConfig,
+    status_message: Arc<Mutex<Option<String>>>,
+    on_change: Option<ChangeNotifier>,
+) -> Vec<(Box<dyn NotificationBackend>, NotificationBackendConfig)> {
+    let mut backends: Vec<(Box<dyn NotificationBackend>, NotificationBackendConfig)> = Vec::new();
+    if let Some(backend_config) = &notifications_config.status_bar {
+        backends.push((Box::new(StatusBarBackend { status_message, on_change }), backend_config.clone()));
+    }
+    if let Some(backend_config) = &notifications_config.desktop {
+        backends.push((Box::new(DesktopBackend), backend_config.clone()));
+    }
+    if let Some(backend_config) = &notifications_config.bell {
+        backends.push((Box::new(BellBackend), backend_config.clone()));
+    }
+    if let Some(backend_config) = &notifications_config.webhook {
+        let url = backend_config.url.clone().unwrap_or_default();
+        backends.push((Box::new(WebhookBackend { url }), backend_config.clone()));
+    }
+    if let Some(backend_config) = &notifications_config.command {
+        let command = backend_config.command.clone().unwrap_or_default();
+        backends.push((Box::new(CommandBackend { command }), backend_config.clone()));
+    }
+    backends
+}
+
+/// Sends notifications when processes start failing and when they recover.
+/// Notifications are sent on a background thread, since backends such as
+/// webhooks may be slow.
+pub(crate) struct Notifier {
+    /// The processes that have failed and haven't yet succeeded.
+    failing_process_names: HashSet<String>,
+
+    notification_tx: Sender<Notification>,
+
+    status_message: Arc<Mutex<Option<String>>>,
+}
+
+impl Notifier {
+    pub(crate) fn spawn(notifications_config: &NotificationsConfig, on_change: ChangeNotifier) -> Self {
+        let status_message = Arc::new(Mutex::new(None));
+        let backends = notification_backends(notifications_config, Arc::clone(&status_message), Some(on_change.clone()));
+
+        let (notification_tx, notification_rx) = std::sync::mpsc::channel::<Notification>();
+        std::thread::spawn({
+            let status_message = Arc::clone(&status_message);
+            move || {
+                while let Ok(notification) = notification_rx.recv() {
+                    for (backend, backend_config) in &backends {
+                        if !backend_config.sends(notification.event) {
+                            continue;
+                        }
+                        if let Err(error) = backend.send(&notification) {
+                            *status_message.lock().unwrap() = Some(format!(
+                                "Failed to send {} notification: {error}",
+                                backend.name(),
+                            ));
+                            on_change.wake();
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            failing_process_names: HashSet::new(),
+            notification_tx,
+            status_message,
+        }
+    }
+
+    pub(crate) fn update(&mut self, processes: &Processes) {
+        for process in processes.processes() {
+            // Errors during the startup grace period of a process aren't
+            // treated as failures.
+            let status = process.alerting_status();
+            let was_failing = self.failing_process_names.contains(process.name());
+
+            let event = if status.is_failure() && !was_failing {
+                self.failing_process_names.insert(process.name().to_owned());
+                NotificationEvent::Failure
+            } else if status.is_success() && was_failing {
+                self.failing_process_names.remove(process.name());
+                NotificationEvent::Recovery
+            } else {
+                continue;
+            };
+
+            if process.notifies(event) {
+                let _ = self.notification_tx.send(Notification {
+                    event,
+                    process_name: process.name().to_owned(),
+                });
+            }
+        }
+    }
+
+    /// The message to show in the status bar, if there's a new one.
+    pub(crate) fn take_status_message(&self) -> Option<String> {
+        self.status_message.lock().unwrap().take()
+    }
+}
@@ -0,0 +1,156 @@
+use std::{io::Write, time::{SystemTime, UNIX_EPOCH}};
+
+use termwiz::escape::{Action, ControlCode, CSI};
+
+/// A line of output of a process, outside of the terminal of the process.
+pub(crate) struct OutputLine {
+    /// The text of the line, without any styles.
+    pub(crate) text: String,
+
+    /// The text of the line, with the escape sequences for its styles.
+    pub(crate) styled_text: String,
+}
+
+/// Splits the output of a process into lines, for showing the output outside
+/// of the terminal of the process. Styles are kept, but other escape
+/// sequences are dropped.
+pub(crate) struct LineSplitter {
+    text: String,
+
+    styled_text: String,
+
+    /// Whether the line has any styles, in which case the styles are reset at
+    /// the end of the line.
+    styled: bool,
+
+    /// Whether the line has been ended by a carriage return, in which case any
+    /// further text replaces the line, such as when a progress bar is updated.
+    carriage_returned: bool,
+}
+
+impl LineSplitter {
+    pub(crate) fn new() -> Self {
+        Self {
+            text: String::new(),
+            styled_text: String::new(),
+            styled: false,
+            carriage_returned: false,
+        }
+    }
+
+    /// Return the lines completed by the actions.
+    pub(crate) fn split(&mut self, actions: &[Action]) -> Vec<OutputLine> {
+        let mut lines = Vec::new();
+
+        for action in actions {
+            match action {
+                Action::Print(char) => {
+                    self.clear_if_carriage_returned();
+                    self.text.push(*char);
+                    self.styled_text.push(*char);
+                },
+                Action::PrintString(string) => {
+                    self.clear_if_carriage_returned();
+                    self.text.push_str(string);
+                    self.styled_text.push_str(string);
+                },
+                Action::CSI(CSI::Sgr(_)) => {
+                    self.styled_text.push_str(&action.to_string());
+                    self.styled = true;
+                },
+                Action::Control(ControlCode::CarriageReturn) => {
+                    self.carriage_returned = true;
+                },
+                Action::Control(ControlCode::LineFeed) => {
+                    lines.push(self.take_line());
+                },
+                _ => {},
+            }
+        }
+
+        lines
+    }
+
+    /// Return the last line when the output ends, even if it wasn't ended by a
+    /// line feed.
+    pub(crate) fn finish(&mut self) -> Option<OutputLine> {
+        if self.text.is_empty() {
+            None
+        } else {
+            Some(self.take_line())
+        }
+    }
+
+    fn clear_if_carriage_returned(&mut self) {
+        if self.carriage_returned {
+            self.text.clear();
+            self.styled_text.clear();
+            self.styled = false;
+            self.carriage_returned = false;
+        }
+    }
+
+    fn take_line(&mut self) -> OutputLine {
+        if self.styled {
+            self.styled_text.push_str("\x1b[0m");
+        }
+        self.styled = false;
+        self.carriage_returned = false;
+        OutputLine {
+            text: std::mem::take(&mut self.text),
+            styled_text: std::mem::take(&mut self.styled_text),
+        }
+    }
+}
+
+/// How lines are printed when Mintaka is running without the UI.
+#[derive(Clone)]
+pub(crate) struct EchoFormat {
+    /// Whether to color the prefix and keep the styles of the output.
+    pub(crate) color: bool,
+
+    /// The prefix of each line, where `{name}`, `{pid}` and `{time}` are
+    /// replaced with the name of the process, its process ID and the current
+    /// UTC time.
+    pub(crate) prefix_format: String,
+}
+
+/// Colors for prefixes, chosen for each process by its name so that a process
+/// keeps the same color between runs.
+const PREFIX_COLORS: [u8; 6] = [36, 33, 32, 35, 34, 31];
+
+/// Print lines to stdout, prefixed with the name of the process, for when
+/// Mintaka is running without the UI. The lines are written together so that
+/// they aren't interleaved with the output of other processes.
+pub(crate) fn echo_lines(echo_format: &EchoFormat, process_name: &str, process_id: Option<u32>, lines: &[OutputLine]) {
+    let mut prefix = echo_format.prefix_format
+        .replace("{name}", process_name)
+        .replace("{pid}", &process_id.map(|process_id| process_id.to_string()).unwrap_or_default())
+        .replace("{time}", &utc_time());
+    if echo_format.color {
+        let name_hash = process_name.bytes().fold(0usize, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte.into()));
+        let color = PREFIX_COLORS[name_hash % PREFIX_COLORS.len()];
+        prefix = format!("\x1b[{color}m{prefix}\x1b[0m");
+    }
+
+    let mut output = String::new();
+    for line in lines {
+        let line_text = if echo_format.color {
+            &line.styled_text
+        } else {
+            &line.text
+        };
+        output.push_str(&format!("{prefix} {line_text}\n"));
+    }
+    let _ = std::io::stdout().lock().write_all(output.as_bytes());
+}
+
+/// The current time as `HH:MM:SS` in UTC, since there's no time zone
+/// database to find the local time.
+fn utc_time() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    format!("{:02}:{:02}:{:02}", seconds / 3600 % 24, seconds / 60 % 60, seconds % 60)
+}
@@ -0,0 +1,160 @@
+use termwiz::{cell::unicode_column_width, escape::{csi::{Cursor, Edit, EraseInDisplay}, Action, ControlCode, CSI}};
+
+/// Collapses lines that are overwritten using carriage returns, such as
+/// progress bars.
+///
+/// A carriage return only moves the cursor to the start of the current row, so
+/// when a line is wider than the terminal and wraps, overwriting the line
+/// leaves the earlier rows of the line behind. Each redraw of a progress bar
+/// then leaves another copy of the bar behind. To avoid this, a carriage return
+/// that ends a wrapped line and is followed by more output moves the cursor
+/// back to the start of the line, and erases the rest of the display.
+///
+/// A carriage return followed by a line feed ends the line without
+/// overwriting it, which is how the PTY sends every line feed, so whether a
+/// carriage return overwrites the line isn't known until the next action.
+pub(crate) struct CarriageReturnCollapser {
+    line_width: usize,
+    pending_carriage_return: bool,
+}
+
+impl CarriageReturnCollapser {
+    pub(crate) fn new() -> Self {
+        Self {
+            line_width: 0,
+            pending_carriage_return: false,
+        }
+    }
+
+    pub(crate) fn normalize(&mut self, actions: Vec<Action>, columns: usize) -> Vec<Action> {
+        let mut normalized_actions = Vec::with_capacity(actions.len());
+
+        for action in actions {
+            if self.pending_carriage_return {
+                match &action {
+                    Action::Control(ControlCode::CarriageReturn) => continue,
+                    Action::Control(ControlCode::LineFeed) => {
+                        normalized_actions.push(Action::Control(ControlCode::CarriageReturn));
+                    },
+                    _ => self.overwrite_line(&mut normalized_actions, columns),
+                }
+                self.pending_carriage_return = false;
+                self.line_width = 0;
+            }
+
+            match &action {
+                Action::Print(char) => {
+                    self.line_width += unicode_column_width(char.encode_utf8(&mut [0; 4]), None);
+                },
+                Action::PrintString(string) => {
+                    self.line_width += unicode_column_width(string, None);
+                },
+                Action::Control(ControlCode::LineFeed) => {
+                    self.line_width = 0;
+                },
+                Action::Control(ControlCode::CarriageReturn) => {
+                    self.pending_carriage_return = true;
+                    continue;
+                },
+                _ => {},
+            }
+
+            normalized_actions.push(action);
+        }
+
+        normalized_actions
+    }
+
+    fn overwrite_line(&self, normalized_actions: &mut Vec<Action>, columns: usize) {
+        let wrapped_rows = if columns == 0 || self.line_width == 0 {
+            0
+        } else {
+            (self.line_width - 1) / columns
+        };
+
+        if wrapped_rows > 0 {
+            normalized_actions.push(Action::CSI(CSI::Cursor(Cursor::Up(wrapped_rows as u32))));
+        }
+        normalized_actions.push(Action::Control(ControlCode::CarriageReturn));
+        if wrapped_rows > 0 {
+            normalized_actions.push(Action::CSI(CSI::Edit(Edit::EraseInDisplay(
+                EraseInDisplay::EraseToEndOfDisplay,
+            ))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARRIAGE_RETURN: Action = Action::Control(ControlCode::CarriageReturn);
+    const LINE_FEED: Action = Action::Control(ControlCode::LineFeed);
+
+    fn print(string: &str) -> Action {
+        Action::PrintString(string.to_owned())
+    }
+
+    fn overwrite_wrapped_line(wrapped_rows: u32) -> Vec<Action> {
+        vec![
+            Action::CSI(CSI::Cursor(Cursor::Up(wrapped_rows))),
+            CARRIAGE_RETURN,
+            Action::CSI(CSI::Edit(Edit::EraseInDisplay(EraseInDisplay::EraseToEndOfDisplay))),
+        ]
+    }
+
+    #[test]
+    fn carriage_return_before_line_feed_keeps_wrapped_line() {
+        let mut collapser = CarriageReturnCollapser::new();
+
+        let actions = collapser.normalize(vec![print(&"a".repeat(15)), CARRIAGE_RETURN, LINE_FEED, print("b")], 10);
+
+        assert_eq!(actions, vec![print(&"a".repeat(15)), CARRIAGE_RETURN, LINE_FEED, print("b")]);
+    }
+
+    #[test]
+    fn redrawn_progress_bar_overwrites_wrapped_line() {
+        let mut collapser = CarriageReturnCollapser::new();
+
+        let actions = collapser.normalize(vec![print(&"#".repeat(25)), CARRIAGE_RETURN, print(&"#".repeat(25))], 10);
+
+        let mut expected_actions = vec![print(&"#".repeat(25))];
+        expected_actions.extend(overwrite_wrapped_line(2));
+        expected_actions.push(print(&"#".repeat(25)));
+        assert_eq!(actions, expected_actions);
+    }
+
+    #[test]
+    fn carriage_return_at_end_of_output_waits_for_next_output() {
+        let mut collapser = CarriageReturnCollapser::new();
+
+        let first_actions = collapser.normalize(vec![print(&"#".repeat(15)), CARRIAGE_RETURN], 10);
+        let second_actions = collapser.normalize(vec![print(&"#".repeat(15))], 10);
+
+        assert_eq!(first_actions, vec![print(&"#".repeat(15))]);
+        let mut expected_second_actions = overwrite_wrapped_line(1);
+        expected_second_actions.push(print(&"#".repeat(15)));
+        assert_eq!(second_actions, expected_second_actions);
+    }
+
+    #[test]
+    fn carriage_return_on_unwrapped_line_is_unchanged() {
+        let mut collapser = CarriageReturnCollapser::new();
+
+        let actions = collapser.normalize(vec![print("50%"), CARRIAGE_RETURN, print("60%")], 10);
+
+        assert_eq!(actions, vec![print("50%"), CARRIAGE_RETURN, print("60%")]);
+    }
+
+    #[test]
+    fn line_width_counts_display_columns() {
+        let mut collapser = CarriageReturnCollapser::new();
+
+        let actions = collapser.normalize(vec![print(&"界".repeat(6)), CARRIAGE_RETURN, print("done")], 10);
+
+        let mut expected_actions = vec![print(&"界".repeat(6))];
+        expected_actions.extend(overwrite_wrapped_line(1));
+        expected_actions.push(print("done"));
+        assert_eq!(actions, expected_actions);
+    }
+}
@@ -0,0 +1,59 @@
+use termwiz::{cell::CellAttributes, color::{ColorAttribute, RgbColor}, surface::Change};
+
+/// A background that replaces the default background of the host terminal
+/// when drawing the output of a process, for processes that assume a dark
+/// background on a light terminal or vice versa.
+pub(crate) struct PaneBackground {
+    background: ColorAttribute,
+    foreground: ColorAttribute,
+}
+
+impl PaneBackground {
+    pub(crate) fn new(background: RgbColor) -> Self {
+        let (red, green, blue) = background.to_tuple_rgb8();
+        let luminance = 0.2126 * f64::from(red) + 0.7152 * f64::from(green) + 0.0722 * f64::from(blue);
+        let foreground = if luminance < 128.0 {
+            RgbColor::new_8bpc(0xd0, 0xd0, 0xd0)
+        } else {
+            RgbColor::new_8bpc(0x20, 0x20, 0x20)
+        };
+
+        Self {
+            background: ColorAttribute::TrueColorWithDefaultFallback(background.into()),
+            foreground: ColorAttribute::TrueColorWithDefaultFallback(foreground.into()),
+        }
+    }
+
+    /// The attributes to use for cells that have default attributes.
+    pub(crate) fn default_attributes(&self) -> CellAttributes {
+        let mut attributes = CellAttributes::blank();
+        attributes.set_background(self.background);
+        attributes.set_foreground(self.foreground);
+        attributes
+    }
+
+    /// Replace the default colors used by the changes with the colors of the
+    /// background.
+    pub(crate) fn apply(&self, changes: Vec<Change>) -> Vec<Change> {
+        changes.into_iter()
+            .map(|change| match change {
+                Change::AllAttributes(mut attributes) => {
+                    if attributes.background() == ColorAttribute::Default {
+                        attributes.set_background(self.background);
+                    }
+                    if attributes.foreground() == ColorAttribute::Default {
+                        attributes.set_foreground(self.foreground);
+                    }
+                    Change::AllAttributes(attributes)
+                },
+                Change::ClearToEndOfLine(ColorAttribute::Default) => {
+                    Change::ClearToEndOfLine(self.background)
+                },
+                Change::ClearScreen(ColorAttribute::Default) => {
+                    Change::ClearScreen(self.background)
+                },
+                change => change,
+            })
+            .collect()
+    }
+}
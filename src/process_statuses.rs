@@ -1,38 +1,276 @@
+use std::sync::{Arc, Mutex};
+
 use regex::Regex;
 
 use crate::processes::ProcessStatus;
 
-pub(crate) struct ProcessStatusAnalyzer {
-    pub(crate) success_regex: Option<Regex>,
-    pub(crate) error_regex: Option<Regex>,
+pub(crate) enum ProcessStatusAnalyzer {
+    Regexes {
+        success_regex: Option<Regex>,
+        error_regex: Option<Regex>,
+    },
+
+    Jest(JestAnalyzer),
+
+    Patterns(PatternAnalyzer),
+
+    /// Ignores the output, such as when the status is determined by a ready
+    /// check instead.
+    Ignore,
 }
 
 impl ProcessStatusAnalyzer {
-    pub(crate) fn analyze_line(&self, last_line: &str) -> Option<ProcessStatus> {
+    pub(crate) fn analyze_line(&mut self, last_line: &str) -> Option<ProcessStatus> {
         if last_line.trim().is_empty() {
             return None;
         }
 
-        if let Some(error_regex) = &self.error_regex {
-            match error_regex.captures(last_line) {
-                None => {},
-                Some(captures) => {
-                    let error_count: Option<u64> = captures.get(1).and_then(|capture| capture.as_str().parse().ok());
-                    if error_count == Some(0) {
+        match self {
+            ProcessStatusAnalyzer::Regexes { success_regex, error_regex } => {
+                if let Some(error_regex) = error_regex {
+                    match error_regex.captures(last_line) {
+                        None => {},
+                        Some(captures) => {
+                            let error_count: Option<u64> = captures.get(1).and_then(|capture| capture.as_str().parse().ok());
+                            if error_count == Some(0) {
+                                return Some(ProcessStatus::Success);
+                            } else {
+                                return Some(ProcessStatus::Errors { error_count })
+                            }
+                        }
+                    }
+                }
+
+                if let Some(success_regex) = success_regex {
+                    if success_regex.is_match(last_line) {
                         return Some(ProcessStatus::Success);
-                    } else {
-                        return Some(ProcessStatus::Errors { error_count })
                     }
                 }
+
+                Some(ProcessStatus::Running)
+            },
+            ProcessStatusAnalyzer::Jest(jest_analyzer) => jest_analyzer.analyze_line(last_line),
+            ProcessStatusAnalyzer::Patterns(pattern_analyzer) => pattern_analyzer.analyze_line(last_line),
+            ProcessStatusAnalyzer::Ignore => None,
+        }
+    }
+
+    /// Analyze the process clearing the screen, which some processes do
+    /// before starting a new run.
+    pub(crate) fn analyze_screen_clear(&mut self) -> Option<ProcessStatus> {
+        match self {
+            ProcessStatusAnalyzer::Regexes { .. } | ProcessStatusAnalyzer::Patterns(_) | ProcessStatusAnalyzer::Ignore => None,
+            ProcessStatusAnalyzer::Jest(_) => Some(ProcessStatus::Running),
+        }
+    }
+}
+
+/// Analyzes the summary that Jest prints at the end of each run, such as:
+///
+/// ```text
+/// Test Suites: 1 failed, 2 passed, 3 total
+/// Tests:       2 failed, 10 passed, 12 total
+/// ```
+///
+/// Jest prints more output after the summary, so other lines leave the status
+/// unchanged. In watch mode, Jest clears the screen before each run.
+pub(crate) struct JestAnalyzer {
+    failed_suite_count: u64,
+}
+
+impl JestAnalyzer {
+    pub(crate) fn new() -> Self {
+        Self {
+            failed_suite_count: 0,
+        }
+    }
+
+    fn analyze_line(&mut self, last_line: &str) -> Option<ProcessStatus> {
+        let last_line = last_line.trim();
+
+        if JEST_RUNNING_REGEX.is_match(last_line) {
+            return Some(ProcessStatus::Running);
+        }
+
+        if let Some(captures) = JEST_TEST_SUITES_REGEX.captures(last_line) {
+            self.failed_suite_count = Self::failed_count(&captures);
+            return None;
+        }
+
+        if let Some(captures) = JEST_TESTS_REGEX.captures(last_line) {
+            // A suite that fails to run, such as because of a syntax error,
+            // has no failed tests, so count failed suites too.
+            let error_count = Self::failed_count(&captures).max(self.failed_suite_count);
+            self.failed_suite_count = 0;
+
+            return if error_count == 0 {
+                Some(ProcessStatus::Success)
+            } else {
+                Some(ProcessStatus::Errors { error_count: Some(error_count) })
+            };
+        }
+
+        None
+    }
+
+    fn failed_count(captures: &regex::Captures) -> u64 {
+        captures.get(1)
+            .and_then(|capture| capture.as_str().parse().ok())
+            .unwrap_or(0)
+    }
+}
+
+/// Analyzes the output of long-running processes, such as dev servers, that
+/// print messages when they start and finish each build. Unlike the regexes
+/// in the config, lines that don't match any pattern leave the status
+/// unchanged, so that other logging doesn't reset the status to "Running".
+pub(crate) struct PatternAnalyzer {
+    running_regex: Regex,
+    success_regex: Regex,
+    error_regex: Regex,
+}
+
+impl PatternAnalyzer {
+    pub(crate) fn webpack() -> Self {
+        Self {
+            running_regex: WEBPACK_RUNNING_REGEX.clone(),
+            success_regex: WEBPACK_SUCCESS_REGEX.clone(),
+            error_regex: WEBPACK_ERROR_REGEX.clone(),
+        }
+    }
+
+    pub(crate) fn vite() -> Self {
+        Self {
+            running_regex: VITE_RUNNING_REGEX.clone(),
+            success_regex: VITE_SUCCESS_REGEX.clone(),
+            error_regex: VITE_ERROR_REGEX.clone(),
+        }
+    }
+
+    fn analyze_line(&self, last_line: &str) -> Option<ProcessStatus> {
+        if let Some(captures) = self.error_regex.captures(last_line) {
+            let error_count: Option<u64> = captures.get(1).and_then(|capture| capture.as_str().parse().ok());
+            return Some(ProcessStatus::Errors { error_count });
+        }
+
+        if self.success_regex.is_match(last_line) {
+            return Some(ProcessStatus::Success);
+        }
+
+        if self.running_regex.is_match(last_line) {
+            return Some(ProcessStatus::Running);
+        }
+
+        None
+    }
+}
+
+/// Captures a URL, such as the address of a dev server, from the output of a
+/// process. The URL is the first capture group of the regex, or the whole
+/// match if the regex has no capture groups.
+pub(crate) struct UrlPublisher {
+    regex: Regex,
+    published_url: Arc<Mutex<Option<String>>>,
+}
+
+impl UrlPublisher {
+    pub(crate) fn new(regex: Regex, published_url: Arc<Mutex<Option<String>>>) -> Self {
+        Self {
+            regex,
+            published_url,
+        }
+    }
+
+    pub(crate) fn analyze_line(&self, last_line: &str) {
+        let Some(captures) = self.regex.captures(last_line) else {
+            return;
+        };
+        let Some(url) = captures.get(1).or_else(|| captures.get(0)) else {
+            return;
+        };
+        *self.published_url.lock().unwrap() = Some(url.as_str().to_owned());
+    }
+}
+
+/// Records the output of each run of a process, so that the output of the last
+/// run that finished with errors can be copied. A run starts when the status
+/// of the process changes to "Running".
+pub(crate) struct FailureOutputRecorder {
+    running: bool,
+    current_run_lines: Vec<String>,
+    pending_line: Option<String>,
+    last_failure_output: Arc<Mutex<Option<String>>>,
+}
+
+const MAX_RECORDED_LINES: usize = 10_000;
+
+impl FailureOutputRecorder {
+    pub(crate) fn new(last_failure_output: Arc<Mutex<Option<String>>>) -> Self {
+        Self {
+            running: false,
+            current_run_lines: Vec::new(),
+            pending_line: None,
+            last_failure_output,
+        }
+    }
+
+    /// Record a line of output, along with the status that the line resulted
+    /// in, if any. A line that ends in a carriage return is only kept if it
+    /// isn't overwritten before the next line feed.
+    pub(crate) fn record_line(&mut self, line: &str, is_line_feed: bool, status: Option<ProcessStatus>) {
+        if matches!(status, Some(ProcessStatus::Running)) {
+            self.record_status(ProcessStatus::Running);
+        }
+
+        if !line.is_empty() {
+            self.pending_line = Some(line.to_owned());
+        }
+        if is_line_feed {
+            let line = self.pending_line.take().unwrap_or_default();
+            if self.current_run_lines.len() < MAX_RECORDED_LINES {
+                self.current_run_lines.push(line);
             }
         }
 
-        if let Some(success_regex) = &self.success_regex {
-            if success_regex.is_match(last_line) {
-                return Some(ProcessStatus::Success);
+        if let Some(status) = status {
+            if !matches!(status, ProcessStatus::Running) {
+                self.record_status(status);
             }
         }
+    }
 
-        Some(ProcessStatus::Running)
+    pub(crate) fn record_status(&mut self, status: ProcessStatus) {
+        match status {
+            ProcessStatus::Running => {
+                if !self.running {
+                    self.current_run_lines.clear();
+                    self.running = true;
+                }
+            },
+            ProcessStatus::Errors { .. } => {
+                if let Some(pending_line) = self.pending_line.take() {
+                    self.current_run_lines.push(pending_line);
+                }
+                *self.last_failure_output.lock().unwrap() = Some(self.current_run_lines.join("\n"));
+                self.running = false;
+            },
+            _ => {
+                self.running = false;
+            },
+        }
     }
 }
+
+lazy_static::lazy_static! {
+    static ref JEST_RUNNING_REGEX: Regex = Regex::new("^(?:RUNS |Determining test suites to run)").unwrap();
+    static ref JEST_TEST_SUITES_REGEX: Regex = Regex::new("^Test Suites:\\s+(?:([0-9]+) failed)?").unwrap();
+    static ref JEST_TESTS_REGEX: Regex = Regex::new("^Tests:\\s+(?:([0-9]+) failed)?").unwrap();
+
+    static ref WEBPACK_RUNNING_REGEX: Regex = Regex::new("^(?:webpack: )?Compiling\\b|\\[webpack-cli\\] Compiler starting").unwrap();
+    static ref WEBPACK_SUCCESS_REGEX: Regex = Regex::new("(?i)compiled successfully|compiled with (?:[0-9]+ )?warnings?").unwrap();
+    static ref WEBPACK_ERROR_REGEX: Regex = Regex::new("compiled with ([0-9]+) errors?|^ERROR in |^Failed to compile").unwrap();
+
+    static ref VITE_RUNNING_REGEX: Regex = Regex::new("^build started").unwrap();
+    static ref VITE_SUCCESS_REGEX: Regex = Regex::new("\\bready in [0-9]|\\[vite\\] (?:hmr update|hmr invalidate|page reload)|^built in [0-9]").unwrap();
+    static ref VITE_ERROR_REGEX: Regex = Regex::new("\\[vite\\] Internal server error|^\\[plugin:|\\[ERROR\\]|^error during build").unwrap();
+}
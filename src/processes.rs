@@ -1,17 +1,19 @@
-use std::sync::{Arc, Mutex};
+use std::{collections::{HashMap, HashSet, VecDeque}, ffi::OsString, io::Write, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, time::{Duration, Instant, SystemTime}};
 
 use multimap::MultiMap;
 use portable_pty::{ChildKiller, ExitStatus, PtyPair, PtySize, PtySystem};
-use termwiz::{escape::{parser::Parser, Esc, EscCode}, terminal::TerminalWaker};
-use wezterm_term::{TerminalSize, VisibleRowIndex};
+use termwiz::{color::RgbColor, escape::{csi::{Edit, EraseInDisplay, CSI}, parser::Parser, Esc, EscCode}, terminal::TerminalWaker};
+use wezterm_term::{StableRowIndex, TerminalSize, VisibleRowIndex};
 
-use crate::{config::ProcessConfig, process_statuses::ProcessStatusAnalyzer};
+use crate::{checkpoints::{Checkpoint, CheckpointBrowser}, combined_log::CombinedLog, config::{ConfigSource, NotificationEvent, ProcessConfig}, event_log::EventLog, executables::{self, ExecutableError}, file_watches::FileWatcher, highlights::Highlighter, livereload::LivereloadServer, output_lines::{self, EchoFormat, LineSplitter, OutputLine}, output_normalization::CarriageReturnCollapser, process_statuses::{FailureOutputRecorder, ProcessStatusAnalyzer, UrlPublisher}, ready_checks, resource_usage::ResourceUsage, sandbox::{self, SandboxError}, screen_export, secret_warnings::SecretScanner, search::{self, Search}, shell_environment, signals, trace, upstream_picker::UpstreamPicker, working_directories};
 
 type SharedPtySystem = Arc<Box<dyn PtySystem + Send>>;
 
 pub(crate) struct Processes {
     autofocus: bool,
 
+    output_sinks: OutputSinks,
+
     pty_system: SharedPtySystem,
 
     pty_size: portable_pty::PtySize,
@@ -20,13 +22,86 @@ pub(crate) struct Processes {
 
     pub(crate) focused_process_index: usize,
 
-    on_change: TerminalWaker,
+    /// Whether the combined output of all processes is shown instead of the
+    /// output of the focused process.
+    combined_log_focused: bool,
+
+    /// The groups whose processes are hidden in the process list.
+    collapsed_groups: HashSet<String>,
+
+    /// Whether watchers exiting is treated as a failure, since they're
+    /// expected to keep running.
+    watcher_exits_are_failures: bool,
+
+    on_change: ChangeNotifier,
 
     after: MultiMap<String, usize>,
+
+    started_at: Instant,
+
+    waterfall_visible: bool,
+
+    event_log: EventLog,
+
+    event_log_visible: bool,
+
+    config_source_visible: bool,
+
+    /// The width of the labels in the process list chosen by the user, if
+    /// any, rather than the width of the longest label.
+    process_list_width: Option<usize>,
+
+    /// Whether the process list is hidden so that the process pane takes the
+    /// full width of the terminal.
+    zoomed: bool,
+
+    split: Option<Split>,
+
+    /// The most recent resource usage of each running process, by process ID.
+    resource_usages: HashMap<u32, ResourceUsage>,
+
+    /// The git branch of the working directory of each running process, by
+    /// process ID.
+    git_branches: HashMap<u32, String>,
+
+    git_branches_in_process_list: bool,
+
+    privacy_mode: bool,
+
+    quit_confirmation: Option<Vec<String>>,
+
+    status_message: Option<String>,
+
+    search: Option<Search>,
+
+    upstream_picker: Option<UpstreamPicker>,
+
+    livereload_server: Option<LivereloadServer>,
+
+    checkpoints: Vec<Checkpoint>,
+
+    /// The name of the checkpoint being typed, if any.
+    checkpoint_name: Option<String>,
+
+    checkpoint_browser: Option<CheckpointBrowser>,
+
+    /// The changes being typed when cloning the focused process, if any.
+    clone_changes: Option<String>,
+
+    /// The names of the processes whose `on_ready` command has been run.
+    ready_hooks_run: HashSet<String>,
+
+    /// Whether restarts caused by watched files are paused because too many
+    /// happened at once, such as when processes write files that they or
+    /// other processes watch.
+    watch_restarts_paused: bool,
+
+    /// Stopping the processes before quitting, once quitting has started.
+    shutdown: Option<Shutdown>,
 }
 
 impl Processes {
-    pub(crate) fn new(on_change: TerminalWaker) -> Self {
+    pub(crate) fn new(on_change: ChangeNotifier) -> Self {
         let pty_system = Arc::new(portable_pty::native_pty_system());
         let pty_size = portable_pty::PtySize {
             rows: 24,
@@ -37,15 +112,83 @@ impl Processes {
 
         Self {
             autofocus: true,
+            output_sinks: OutputSinks {
+                echo: None,
+                combined_log: CombinedLog::new(),
+                secret_scanner: None,
+            },
             pty_system,
             pty_size,
             processes: Vec::new(),
             focused_process_index: 0,
+            combined_log_focused: false,
             on_change,
             after: MultiMap::new(),
+            started_at: Instant::now(),
+            waterfall_visible: false,
+            event_log: EventLog::new(),
+            event_log_visible: false,
+            config_source_visible: false,
+            process_list_width: None,
+            zoomed: false,
+            split: None,
+            resource_usages: HashMap::new(),
+            git_branches: HashMap::new(),
+            git_branches_in_process_list: false,
+            privacy_mode: false,
+            quit_confirmation: None,
+            status_message: None,
+            search: None,
+            upstream_picker: None,
+            livereload_server: None,
+            checkpoints: Vec::new(),
+            checkpoint_name: None,
+            checkpoint_browser: None,
+            clone_changes: None,
+            ready_hooks_run: HashSet::new(),
+            watch_restarts_paused: false,
+            shutdown: None,
+            collapsed_groups: HashSet::new(),
+            watcher_exits_are_failures: true,
         }
     }
 
+    /// Print each line of output to stdout, prefixed with the name of the
+    /// process. Only affects processes started afterwards.
+    pub(crate) fn enable_output_echo(&mut self, echo_format: EchoFormat) {
+        self.output_sinks.echo = Some(echo_format);
+    }
+
+    /// Treat watchers that exit with an exit code of zero as succeeding, such
+    /// as when running once without the UI.
+    pub(crate) fn allow_watchers_to_exit(&mut self) {
+        self.watcher_exits_are_failures = false;
+    }
+
+    /// Show the git branch of each process in the process list, as well as in
+    /// the config view.
+    pub(crate) fn show_git_branches_in_process_list(&mut self) {
+        self.git_branches_in_process_list = true;
+    }
+
+    pub(crate) fn git_branches_in_process_list(&self) -> bool {
+        self.git_branches_in_process_list
+    }
+
+    /// Warn when a process prints something that looks like a credential.
+    /// Only affects processes started afterwards.
+    pub(crate) fn enable_secret_warnings(&mut self) {
+        self.output_sinks.secret_scanner = Some(SecretScanner::new());
+    }
+
+    pub(crate) fn combined_log(&self) -> &CombinedLog {
+        &self.output_sinks.combined_log
+    }
+
+    pub(crate) fn set_livereload_server(&mut self, livereload_server: LivereloadServer) {
+        self.livereload_server = Some(livereload_server);
+    }
+
     pub(crate) fn disable_autofocus(&mut self) {
         self.autofocus = false;
     }
@@ -58,6 +201,162 @@ impl Processes {
         self.autofocus
     }
 
+    pub(crate) fn toggle_waterfall(&mut self) {
+        self.waterfall_visible = !self.waterfall_visible;
+        self.event_log_visible = false;
+        self.config_source_visible = false;
+    }
+
+    pub(crate) fn waterfall_visible(&self) -> bool {
+        self.waterfall_visible
+    }
+
+    pub(crate) fn toggle_event_log(&mut self) {
+        self.event_log_visible = !self.event_log_visible;
+        self.waterfall_visible = false;
+        self.config_source_visible = false;
+    }
+
+    pub(crate) fn event_log_visible(&self) -> bool {
+        self.event_log_visible
+    }
+
+    pub(crate) fn toggle_config_source(&mut self) {
+        self.config_source_visible = !self.config_source_visible;
+        self.waterfall_visible = false;
+        self.event_log_visible = false;
+    }
+
+    pub(crate) fn config_source_visible(&self) -> bool {
+        self.config_source_visible
+    }
+
+    pub(crate) fn process_list_width(&self) -> Option<usize> {
+        self.process_list_width
+    }
+
+    pub(crate) fn set_process_list_width(&mut self, process_list_width: usize) {
+        self.process_list_width = Some(process_list_width);
+    }
+
+    pub(crate) fn toggle_zoomed(&mut self) {
+        self.zoomed = !self.zoomed;
+    }
+
+    pub(crate) fn zoomed(&self) -> bool {
+        self.zoomed
+    }
+
+    pub(crate) fn resource_usage(&self, process: &Process) -> Option<ResourceUsage> {
+        self.resource_usages.get(&process.process_id()?).copied()
+    }
+
+    pub(crate) fn set_resource_usages(&mut self, resource_usages: HashMap<u32, ResourceUsage>) {
+        self.resource_usages = resource_usages;
+    }
+
+    pub(crate) fn git_branch(&self, process: &Process) -> Option<&str> {
+        self.git_branches.get(&process.process_id()?).map(String::as_str)
+    }
+
+    pub(crate) fn set_git_branches(&mut self, git_branches: HashMap<u32, String>) {
+        self.git_branches = git_branches;
+    }
+
+    pub(crate) fn split(&self) -> Option<&Split> {
+        self.split.as_ref()
+    }
+
+    /// The process shown in the second pane when the process pane is split.
+    pub(crate) fn split_process(&self) -> Option<&Process> {
+        self.split.as_ref().map(|split| &self.processes[split.process_index])
+    }
+
+    /// Split the process pane, keeping the focused process in the second pane
+    /// so that another process can be focused alongside it, or stop
+    /// splitting the process pane.
+    pub(crate) fn toggle_split(&mut self) {
+        self.split = match self.split {
+            Some(_) => None,
+            None => Some(Split {
+                process_index: self.focused_process_index,
+                direction: SplitDirection::SideBySide,
+            }),
+        };
+    }
+
+    pub(crate) fn toggle_split_direction(&mut self) {
+        match &mut self.split {
+            Some(split) => {
+                split.direction = match split.direction {
+                    SplitDirection::SideBySide => SplitDirection::Stacked,
+                    SplitDirection::Stacked => SplitDirection::SideBySide,
+                };
+            },
+            None => {
+                self.status_message = Some("The process pane isn't split".to_owned());
+            },
+        }
+    }
+
+    pub(crate) fn toggle_privacy_mode(&mut self) {
+        self.privacy_mode = !self.privacy_mode;
+    }
+
+    pub(crate) fn privacy_mode(&self) -> bool {
+        self.privacy_mode
+    }
+
+    pub(crate) fn event_log(&self) -> &EventLog {
+        &self.event_log
+    }
+
+    pub(crate) fn started_at(&self) -> Instant {
+        self.started_at
+    }
+
+    /// Request that Mintaka quits, returning whether it should quit
+    /// immediately. If any processes are busy, the user must confirm the
+    /// request by requesting to quit again.
+    pub(crate) fn request_quit(&mut self) -> bool {
+        if self.quit_confirmation.is_some() {
+            return true;
+        }
+
+        let busy_process_names: Vec<String> = self.processes.iter()
+            .filter(|process| process.is_busy())
+            .map(|process| process.name().to_owned())
+            .collect();
+
+        if busy_process_names.is_empty() {
+            true
+        } else {
+            self.quit_confirmation = Some(busy_process_names);
+            false
+        }
+    }
+
+    /// Cancel any request to quit, returning whether there was a request.
+    pub(crate) fn cancel_quit(&mut self) -> bool {
+        self.quit_confirmation.take().is_some()
+    }
+
+    pub(crate) fn quit_confirmation(&self) -> Option<&[String]> {
+        self.quit_confirmation.as_deref()
+    }
+
+    pub(crate) fn status_message(&self) -> Option<&str> {
+        self.status_message.as_deref()
+    }
+
+    pub(crate) fn set_status_message(&mut self, status_message: String) {
+        self.status_message = Some(status_message);
+    }
+
+    pub(crate) fn clear_status_message(&mut self) {
+        self.status_message = None;
+    }
+
     pub(crate) fn start_process(
         &mut self,
         process_config: ProcessConfig,
@@ -71,6 +370,7 @@ impl Processes {
             Arc::clone(&self.pty_system),
             self.pty_size,
             self.on_change.clone(),
+            self.output_sinks.clone(),
         );
 
         process.do_work()?;
@@ -80,139 +380,1098 @@ impl Processes {
         Ok(())
     }
 
+    pub(crate) fn stop_all(&mut self) {
+        for process in &mut self.processes {
+            process.kill(ProcessInstanceState::NotStarted);
+        }
+    }
+
+    /// Start stopping the processes before quitting. Processes are stopped in
+    /// phases, in reverse dependency order: each process is stopped, and
+    /// waited for, before the processes that it runs after.
+    pub(crate) fn start_shutdown(&mut self) {
+        self.quit_confirmation = None;
+        self.event_log.push("stopping all processes to quit".to_owned());
+        self.shutdown = Some(Shutdown {
+            phases: self.shutdown_phases(),
+            stopping: Vec::new(),
+            phase_started_at: Instant::now(),
+            phase_timeout: Duration::ZERO,
+        });
+        self.advance_shutdown();
+    }
+
+    pub(crate) fn shutdown(&self) -> Option<&Shutdown> {
+        self.shutdown.as_ref()
+    }
+
+    /// Whether every process has been stopped since quitting started.
+    pub(crate) fn is_shut_down(&self) -> bool {
+        self.shutdown.as_ref()
+            .is_some_and(|shutdown| shutdown.phases.is_empty() && shutdown.is_phase_finished())
+    }
+
+    /// Group the processes into phases, where each phase contains the
+    /// processes that no remaining process runs after. If the remaining
+    /// processes run after each other in a cycle, they're stopped together.
+    fn shutdown_phases(&self) -> VecDeque<Vec<usize>> {
+        let mut remaining_process_indexes: Vec<usize> = (0..self.processes.len()).collect();
+        let mut phases = VecDeque::new();
+
+        while !remaining_process_indexes.is_empty() {
+            let (mut phase, mut later_process_indexes): (Vec<usize>, Vec<usize>) = remaining_process_indexes.iter()
+                .copied()
+                .partition(|process_index| {
+                    let downstream_process_indexes = self.after.get_vec(self.processes[*process_index].name());
+                    !downstream_process_indexes.is_some_and(|downstream_process_indexes| {
+                        downstream_process_indexes.iter().any(|downstream_process_index| remaining_process_indexes.contains(downstream_process_index))
+                    })
+                });
+            if phase.is_empty() {
+                phase = std::mem::take(&mut later_process_indexes);
+            }
+            phases.push_back(phase);
+            remaining_process_indexes = later_process_indexes;
+        }
+
+        phases
+    }
+
+    /// Move on to the next phase of stopping the processes once the processes
+    /// in the current phase have exited, or have taken too long to exit.
+    fn advance_shutdown(&mut self) {
+        let Some(shutdown) = &mut self.shutdown else {
+            return;
+        };
+
+        while shutdown.is_phase_finished() {
+            let Some(phase) = shutdown.phases.pop_front() else {
+                return;
+            };
+
+            // Processes with a stop timeout are waited for until they've been
+            // forcibly killed, so that upstream processes, such as a database,
+            // aren't stopped while they're still running, and so that Mintaka
+            // doesn't exit before killing them.
+            shutdown.phase_timeout = phase.iter()
+                .map(|process_index| {
+                    self.processes[*process_index].process_config.stop_timeout()
+                        .map_or(SHUTDOWN_PHASE_TIMEOUT, |stop_timeout| stop_timeout + SHUTDOWN_KILL_MARGIN)
+                })
+                .max()
+                .unwrap_or(Duration::ZERO);
+
+            shutdown.stopping = phase.into_iter()
+                .filter_map(|process_index| {
+                    let process = &mut self.processes[process_index];
+                    let exited = process.stop_for_shutdown()?;
+                    self.event_log.push(format!("stopped {} to quit", process.name()));
+                    Some((process.name().to_owned(), exited))
+                })
+                .collect();
+            shutdown.phase_started_at = Instant::now();
+
+            if !shutdown.stopping.is_empty() {
+                let on_change = self.on_change.clone();
+                let phase_timeout = shutdown.phase_timeout;
+                std::thread::spawn(move || {
+                    std::thread::sleep(phase_timeout);
+                    on_change.wake();
+                });
+            }
+        }
+    }
+
+    /// Stop all of the current processes, and replace them with processes
+    /// using the given configs.
+    pub(crate) fn replace_processes(
+        &mut self,
+        process_configs: Vec<ProcessConfig>,
+    ) -> Result<(), ProcessError> {
+        self.stop_all();
+        self.processes.clear();
+        self.after = MultiMap::new();
+        self.split = None;
+        self.event_log.push("stopped all processes to reload config".to_owned());
+
+        for process_config in process_configs {
+            self.start_process(process_config)?;
+        }
+
+        self.focused_process_index = self.focused_process_index.min(self.processes.len().saturating_sub(1));
+
+        Ok(())
+    }
+
     pub(crate) fn do_work(&mut self) -> Result<(), ProcessError> {
         self.handle_status_updates();
+        self.handle_secret_warnings();
+        self.advance_shutdown();
 
         for process in &mut self.processes {
-            process.do_work()?;
+            if process.take_watched_file_change() && !self.watch_restarts_paused && self.shutdown.is_none() {
+                if process.record_watch_restart() {
+                    let message = "Restart storm detected: paused restarting processes when watched files change".to_owned();
+                    self.event_log.push(message.clone());
+                    self.status_message = Some(message);
+                    self.watch_restarts_paused = true;
+                } else {
+                    self.event_log.push(format!("restarted {} because a watched file changed", process.name()));
+                    process.restart();
+                }
+            }
+            if let Err(error) = process.do_work() {
+                self.event_log.push(format!("failed to start {}: {error}", process.name()));
+            }
         }
 
         if self.autofocus {
-            self.focused_process_index = self.processes.iter()
+            // When several processes are failing, prefer the highest priority,
+            // and then the most recent failure.
+            let failed_process_index = self.processes.iter()
                 .enumerate()
-                .find(|(_process_index, process)| process.status().is_failure())
-                .map(|(process_index, _process)| process_index)
-                .unwrap_or(self.focused_process_index);
+                .filter(|(_, process)| process.alerting_status().is_failure())
+                .min_by_key(|(_, process)| (
+                    process.process_config.autofocus_priority(),
+                    std::cmp::Reverse(process.status_changed_at),
+                ))
+                .map(|(process_index, _)| process_index);
+
+            if let Some(failed_process_index) = failed_process_index {
+                if failed_process_index != self.focused_process_index {
+                    let failed_process = &self.processes[failed_process_index];
+                    self.event_log.push(format!(
+                        "autofocus moved to {} ({})",
+                        failed_process.name(),
+                        failed_process.status().description(),
+                    ));
+                    self.focused_process_index = failed_process_index;
+                    self.combined_log_focused = false;
+                }
+            }
         }
 
         Ok(())
     }
 
+    fn handle_secret_warnings(&mut self) {
+        let Some(secret_scanner) = &self.output_sinks.secret_scanner else {
+            return;
+        };
+
+        for secret_warning in secret_scanner.take_warnings() {
+            let message = format!(
+                "Warning: {} printed what looks like {}",
+                secret_warning.process_name,
+                secret_warning.secret_description,
+            );
+            self.event_log.push(message.clone());
+            self.status_message = Some(message);
+        }
+    }
+
     fn handle_status_updates(&mut self) {
         let mut new_statuses = Vec::new();
+        let mut livereload_process_names = Vec::new();
 
         for process in &mut self.processes {
-            let new_status = process.handle_status_updates();
+            let old_status = process.status();
+            let exits_are_failures = self.watcher_exits_are_failures
+                && process.process_config.is_watcher(self.after.contains_key(process.name()));
+            let new_status = process.handle_status_updates(exits_are_failures);
             if let Some(new_status) = new_status {
+                self.event_log.push(format!("{} {}", process.name(), new_status.description()));
+                if self.shutdown.is_none() {
+                    if let Some(message) = process.run_status_hook(old_status, new_status) {
+                        self.event_log.push(message);
+                    }
+                }
+                if let (ProcessStatus::Exited { exit_code, .. }, None) = (new_status, &self.shutdown) {
+                    if let Some(message) = process.apply_restart_policy(exit_code, new_status.is_failure()) {
+                        self.event_log.push(message);
+                    }
+                }
                 new_statuses.push((process.name().to_string(), new_status));
+                if new_status.is_success() && process.process_config.livereload {
+                    livereload_process_names.push(process.name().to_owned());
+                }
+            }
+        }
+
+        if let Some(livereload_server) = &self.livereload_server {
+            if !livereload_process_names.is_empty() {
+                livereload_server.broadcast_reload();
+                self.event_log.push(format!(
+                    "told browsers to reload because {} succeeded",
+                    livereload_process_names.join(", "),
+                ));
             }
         }
 
         for (before_process_name, before_new_status) in new_statuses {
+            if matches!(before_new_status, ProcessStatus::Success | ProcessStatus::Ready) {
+                self.run_ready_hook(&before_process_name);
+            }
+
             if let Some(after_process_indexes) = self.after.get_vec_mut(&before_process_name) {
                 if before_new_status.is_success() {
                     for process_index in after_process_indexes {
-                        self.processes[*process_index].restart()
+                        let process = &mut self.processes[*process_index];
+                        if process.is_stopped() {
+                            continue;
+                        }
+                        process.restart();
+                        self.event_log.push(format!(
+                            "restarted {} because {before_process_name} succeeded",
+                            process.name(),
+                        ));
                     }
                 } else {
                     for process_index in after_process_indexes {
-                        self.processes[*process_index].mark_waiting_for_upstream()
+                        let process = &mut self.processes[*process_index];
+                        if process.is_stopped() {
+                            continue;
+                        }
+                        process.mark_waiting_for_upstream();
+                        self.event_log.push(format!(
+                            "stopped {} to wait for {before_process_name}",
+                            process.name(),
+                        ));
                     }
                 }
             }
         }
     }
 
+    /// Run the `on_ready` command of a process the first time that the process
+    /// succeeds, rather than every time, such as on every recompilation.
+    fn run_ready_hook(&mut self, process_name: &str) {
+        if self.ready_hooks_run.contains(process_name) {
+            return;
+        }
+        let Some(process_index) = self.process_index(process_name) else {
+            return;
+        };
+        let process = &self.processes[process_index];
+        let Some(on_ready) = process.on_ready() else {
+            return;
+        };
+
+        let url = process.url();
+        if on_ready.iter().any(|argument| argument.contains("{url}")) && url.is_none() {
+            self.event_log.push(format!("skipped on_ready for {process_name} since its URL isn't known"));
+            return;
+        }
+
+        let command: Vec<String> = on_ready.iter()
+            .map(|argument| argument.replace("{url}", url.as_deref().unwrap_or_default()))
+            .collect();
+        self.ready_hooks_run.insert(process_name.to_owned());
+
+        match spawn_hook(&command, &[]) {
+            Ok(()) => self.event_log.push(format!("ran on_ready for {process_name}: {}", command.join(" "))),
+            Err(error) => self.event_log.push(format!("failed to run on_ready for {process_name}: {error}")),
+        }
+    }
+
     pub(crate) fn processes(&self) -> &[Process] {
         &self.processes
     }
 
-    pub(crate) fn lines(&self) -> Vec<wezterm_term::Line> {
-        self.processes[self.focused_process_index].lines()
+    pub(crate) fn failure_count(&self) -> usize {
+        self.processes.iter()
+            .filter(|process| process.status().is_failure())
+            .count()
     }
 
-    pub(crate) fn move_focus_up(&mut self) {
-        if self.focused_process_index > 0 {
-            self.focused_process_index -= 1;
-        } else {
-            self.focused_process_index = self.processes.len() - 1;
+    /// The number of failing processes, not counting errors during the
+    /// startup grace period of a process.
+    pub(crate) fn alerting_failure_count(&self) -> usize {
+        self.processes.iter()
+            .filter(|process| process.alerting_status().is_failure())
+            .count()
+    }
+
+    pub(crate) fn focused_process(&self) -> &Process {
+        &self.processes[self.focused_process_index]
+    }
+
+    /// The entries shown in the process list, starting with the combined log.
+    /// The processes in a group are shown together below the heading of the
+    /// group, which is placed where the first process in the group would be,
+    /// unless the group is collapsed.
+    pub(crate) fn process_list_entries(&self) -> Vec<ProcessListEntry> {
+        let mut entries = vec![ProcessListEntry::CombinedLog];
+        let mut listed_groups = HashSet::new();
+
+        for (process_index, process) in self.processes.iter().enumerate() {
+            let Some(group) = process.group() else {
+                entries.push(ProcessListEntry::Process(process_index));
+                continue;
+            };
+            if !listed_groups.insert(group) {
+                continue;
+            }
+
+            entries.push(ProcessListEntry::Group(group.to_owned()));
+            if !self.collapsed_groups.contains(group) {
+                entries.extend(
+                    self.processes.iter()
+                        .enumerate()
+                        .filter(|(_, group_process)| group_process.group() == Some(group))
+                        .map(|(group_process_index, _)| ProcessListEntry::Process(group_process_index))
+                );
+            }
+        }
+
+        entries
+    }
+
+    /// The entry of the process list that is selected, which is the heading
+    /// of the group of the focused process when that group is collapsed.
+    pub(crate) fn focused_process_list_entry(&self) -> ProcessListEntry {
+        if self.combined_log_focused {
+            return ProcessListEntry::CombinedLog;
+        }
+        match self.focused_process().group() {
+            Some(group) if self.collapsed_groups.contains(group) => ProcessListEntry::Group(group.to_owned()),
+            _ => ProcessListEntry::Process(self.focused_process_index),
         }
     }
 
+    pub(crate) fn is_group_collapsed(&self, group: &str) -> bool {
+        self.collapsed_groups.contains(group)
+    }
+
+    /// Move the focus up the process list, where the combined log is above
+    /// the first process.
+    pub(crate) fn move_focus_up(&mut self) {
+        self.move_focus(false);
+    }
+
     pub(crate) fn move_focus_down(&mut self) {
-        if self.focused_process_index + 1 < self.processes.len() {
-            self.focused_process_index += 1;
-        } else {
-            self.focused_process_index = 0;
+        self.move_focus(true);
+    }
+
+    /// Move the focus to the next entry of the process list that can be
+    /// focused, wrapping around at either end. The heading of an expanded
+    /// group can't be focused, while focusing the heading of a collapsed group
+    /// focuses the first process in the group.
+    fn move_focus(&mut self, down: bool) {
+        let entries = self.process_list_entries();
+        let focused_entry = self.focused_process_list_entry();
+        let mut entry_index = entries.iter()
+            .position(|entry| *entry == focused_entry)
+            .unwrap_or(0);
+
+        for _ in 0..entries.len() {
+            entry_index = if down {
+                (entry_index + 1) % entries.len()
+            } else {
+                (entry_index + entries.len() - 1) % entries.len()
+            };
+
+            match &entries[entry_index] {
+                ProcessListEntry::CombinedLog => {
+                    self.combined_log_focused = true;
+                    return;
+                },
+                ProcessListEntry::Group(group) if self.collapsed_groups.contains(group) => {
+                    if let Some(process_index) = self.processes.iter().position(|process| process.group() == Some(group)) {
+                        self.combined_log_focused = false;
+                        self.focused_process_index = process_index;
+                        return;
+                    }
+                },
+                ProcessListEntry::Group(_) => {},
+                ProcessListEntry::Process(process_index) => {
+                    self.combined_log_focused = false;
+                    self.focused_process_index = *process_index;
+                    return;
+                },
+            }
+        }
+    }
+
+    /// Hide the other processes in the group of the focused process.
+    pub(crate) fn collapse_focused_group(&mut self) {
+        if self.combined_log_focused {
+            return;
+        }
+        if let Some(group) = self.focused_process().group().map(str::to_owned) {
+            self.collapsed_groups.insert(group);
+        }
+    }
+
+    /// Show the processes in the group of the focused process.
+    pub(crate) fn expand_focused_group(&mut self) {
+        if self.combined_log_focused {
+            return;
+        }
+        if let Some(group) = self.focused_process().group().map(str::to_owned) {
+            self.collapsed_groups.remove(&group);
+        }
+    }
+
+    pub(crate) fn toggle_group_collapsed(&mut self, group: &str) {
+        if !self.collapsed_groups.remove(group) {
+            self.collapsed_groups.insert(group.to_owned());
         }
     }
 
-    pub(crate) fn resize(&mut self, size: (usize, usize)) {
-        let pty_size = PtySize {
+    pub(crate) fn focus_combined_log(&mut self) {
+        self.disable_autofocus();
+        self.combined_log_focused = true;
+    }
+
+    pub(crate) fn combined_log_focused(&self) -> bool {
+        self.combined_log_focused
+    }
+
+    /// Resize the processes to fit the process pane, except for the process
+    /// in the second pane when the process pane is split, which is resized to
+    /// fit the second pane instead.
+    pub(crate) fn resize(&mut self, size: (usize, usize), split_size: Option<(usize, usize)>) {
+        self.pty_size = PtySize {
             cols: size.0 as u16,
             rows: size.1 as u16,
             ..self.pty_size
         };
 
-        if self.pty_size != pty_size {
-            self.pty_size = pty_size;
-
-            for process in &mut self.processes {
-                process.resize(self.pty_size);
-            }
+        // When the process in the second pane is also focused, it's sized to
+        // fit the main pane.
+        let split_process_index = self.split.as_ref()
+            .map(|split| split.process_index)
+            .filter(|process_index| *process_index != self.focused_process_index);
+
+        for (process_index, process) in self.processes.iter_mut().enumerate() {
+            let pty_size = match split_size {
+                Some(split_size) if Some(process_index) == split_process_index => PtySize {
+                    cols: split_size.0 as u16,
+                    rows: split_size.1 as u16,
+                    ..self.pty_size
+                },
+                _ => self.pty_size,
+            };
+            process.resize(pty_size);
         }
     }
 
     pub(crate) fn restart_focused(&mut self) {
-        self.processes[self.focused_process_index].restart();
+        self.restart(self.focused_process_index);
     }
-}
 
-#[derive(Clone, Copy)]
-pub(crate) enum ProcessStatus {
-    /// The process has not been started.
-    NotStarted,
+    pub(crate) fn process_index(&self, name: &str) -> Option<usize> {
+        self.processes.iter().position(|process| process.name() == name)
+    }
 
-    /// The process will run once an upstream process reaches a success state.
-    WaitingForUpstream,
+    pub(crate) fn restart(&mut self, process_index: usize) {
+        let process = &mut self.processes[process_index];
+        process.restart();
+        self.event_log.push(format!("restarted {} on request", process.name()));
+    }
 
-    /// The process is running and has not reached a success or error state.
-    Running,
+    pub(crate) fn stop(&mut self, process_index: usize) {
+        let process = &mut self.processes[process_index];
+        process.kill(ProcessInstanceState::Stopped);
+        self.event_log.push(format!("stopped {} on request", process.name()));
+    }
 
-    /// The process is running and has reached a success state.
-    Success,
+    /// Restart every process that has been started and isn't stopped.
+    /// Processes that run after another restarted process wait for it to
+    /// succeed again, so that the processes are restarted in dependency order.
+    /// Restarts caused by watched files are resumed if they were paused.
+    pub(crate) fn restart_all(&mut self) {
+        let restarted_names: HashSet<String> = self.processes.iter()
+            .filter(|process| process.status() != ProcessStatus::NotStarted)
+            .map(|process| process.name().to_owned())
+            .collect();
 
-    /// The process is running and has reached an error state.
-    Errors {
-        error_count: Option<u64>,
-    },
+        for process in &mut self.processes {
+            if !restarted_names.contains(process.name()) {
+                continue;
+            }
 
-    /// The process has exited.
-    Exited {
-        exit_code: u32,
-    },
-}
+            let waits_for_upstream = process.after().is_some_and(|after| restarted_names.contains(after));
+            if waits_for_upstream {
+                process.mark_waiting_for_upstream();
+            } else {
+                process.restart();
+            }
+        }
 
-impl ProcessStatus {
-    fn is_failure(&self) -> bool {
-        match self {
-            ProcessStatus::NotStarted => false,
-            ProcessStatus::WaitingForUpstream => false,
-            ProcessStatus::Running => false,
-            ProcessStatus::Success => false,
-            ProcessStatus::Errors { .. } => true,
-            ProcessStatus::Exited { exit_code } => *exit_code != 0,
+        self.event_log.push("restarted all processes on request".to_owned());
+
+        if self.watch_restarts_paused {
+            self.watch_restarts_paused = false;
+            for process in &mut self.processes {
+                process.watch_restart_times.clear();
+            }
+            self.event_log.push("resumed restarting processes when watched files change".to_owned());
         }
     }
 
-    fn is_success(&self) -> bool {
-        match self {
-            ProcessStatus::NotStarted => false,
-            ProcessStatus::WaitingForUpstream => false,
-            ProcessStatus::Running => false,
-            ProcessStatus::Success => true,
-            ProcessStatus::Errors { .. } => false,
-            ProcessStatus::Exited { exit_code } => *exit_code == 0,
+    /// Stop the focused process, or start it again if it's stopped.
+    pub(crate) fn toggle_focused_stopped(&mut self) {
+        if self.focused_process().is_stopped() {
+            self.restart_focused();
+        } else {
+            self.stop(self.focused_process_index);
+        }
+    }
+
+    /// Wake the main loop so that changes made outside of the main loop, such
+    /// as by remote control, are acted on and rendered.
+    pub(crate) fn wake(&self) {
+        self.on_change.wake();
+    }
+
+    /// Write the current screen of the focused process to an HTML file in the
+    /// current directory.
+    pub(crate) fn export_focused_screen(&mut self) {
+        let process = self.focused_process();
+        let path = self.focused_export_path("html");
+
+        let result = screen_export::export_html(process.name(), &process.lines(), &path);
+
+        self.status_message = Some(match result {
+            Ok(()) => format!("Exported screen to {}", path.display()),
+            Err(error) => format!("Failed to export screen: {error}"),
+        });
+    }
+
+    /// Write the entire output of the focused process, including the
+    /// scrollback, to a text file, optionally keeping colors and styles as
+    /// ANSI escape sequences.
+    pub(crate) fn dump_focused_scrollback(&mut self, path: &Path, with_colors: bool) {
+        let result = screen_export::export_text(&self.focused_process().all_lines(), path, with_colors);
+
+        self.status_message = Some(match result {
+            Ok(()) => format!("Saved output to {}", path.display()),
+            Err(error) => format!("Failed to save output: {error}"),
+        });
+    }
+
+    /// A path in the current directory for exporting the output of the
+    /// focused process, named after the process and the current time.
+    pub(crate) fn focused_export_path(&self, extension: &str) -> PathBuf {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let file_name_stem: String = self.focused_process().name()
+            .chars()
+            .map(|char| if char.is_alphanumeric() { char } else { '-' })
+            .collect();
+        PathBuf::from(format!("mintaka-{file_name_stem}-{timestamp}.{extension}"))
+    }
+
+    pub(crate) fn focused_last_failure_output(&self) -> Option<String> {
+        self.focused_process().last_failure_output()
+    }
+
+    /// Start a profiler against the focused process as a new process, replacing
+    /// `{pid}` in the profiler command with the process ID of the focused
+    /// process.
+    pub(crate) fn profile_focused(&mut self, profiler_command: &[String]) {
+        let process = self.focused_process();
+        let Some(process_id) = process.process_id() else {
+            self.status_message = Some(format!("Can't profile {}: not running", process.name()));
+            return;
+        };
+
+        let profiler_name = format!("profile {} ({process_id})", process.name());
+        let command = profiler_command.iter()
+            .map(|argument| argument.replace("{pid}", &process_id.to_string()))
+            .collect();
+
+        match self.start_process(ProcessConfig::transient(profiler_name.clone(), command)) {
+            Ok(()) => {
+                self.event_log.push(format!("started {profiler_name}"));
+                self.focused_process_index = self.processes.len() - 1;
+                self.combined_log_focused = false;
+            },
+            Err(error) => {
+                self.status_message = Some(format!("Failed to start profiler: {error}"));
+            },
+        }
+    }
+
+    pub(crate) fn scroll_focused_back(&mut self, rows: usize) {
+        self.processes[self.focused_process_index].scroll_back(rows);
+    }
+
+    pub(crate) fn scroll_focused_forward(&mut self, rows: usize) {
+        self.processes[self.focused_process_index].scroll_forward(rows);
+    }
+
+    pub(crate) fn search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    pub(crate) fn upstream_picker(&self) -> Option<&UpstreamPicker> {
+        self.upstream_picker.as_ref()
+    }
+
+    /// Start choosing the upstream process of the focused process. Processes
+    /// that would create a cycle of dependencies aren't offered as choices.
+    pub(crate) fn start_upstream_picker(&mut self) {
+        let process_index = self.focused_process_index;
+        let choices = std::iter::once(None)
+            .chain(
+                self.processes.iter()
+                    .enumerate()
+                    .filter(|(candidate_index, _)| !self.is_downstream_of(*candidate_index, process_index))
+                    .map(|(_, candidate)| Some(candidate.name().to_owned()))
+            )
+            .collect();
+
+        self.upstream_picker = Some(UpstreamPicker::new(
+            process_index,
+            choices,
+            self.processes[process_index].after(),
+        ));
+    }
+
+    pub(crate) fn cancel_upstream_picker(&mut self) {
+        self.upstream_picker = None;
+    }
+
+    pub(crate) fn move_upstream_picker_selection_up(&mut self) {
+        if let Some(upstream_picker) = &mut self.upstream_picker {
+            upstream_picker.move_selection_up();
+        }
+    }
+
+    pub(crate) fn move_upstream_picker_selection_down(&mut self) {
+        if let Some(upstream_picker) = &mut self.upstream_picker {
+            upstream_picker.move_selection_down();
+        }
+    }
+
+    pub(crate) fn finish_upstream_picker(&mut self) {
+        if let Some(upstream_picker) = self.upstream_picker.take() {
+            self.set_upstream(upstream_picker.process_index, upstream_picker.selected_upstream());
+        }
+    }
+
+    /// Whether the process at `process_index` is the process at
+    /// `upstream_index` or (indirectly) runs after it.
+    fn is_downstream_of(&self, process_index: usize, upstream_index: usize) -> bool {
+        let mut current_index = process_index;
+        // Following at most one link per process avoids looping forever if
+        // the existing dependencies already have a cycle.
+        for _ in 0..=self.processes.len() {
+            if current_index == upstream_index {
+                return true;
+            }
+            let Some(next_index) = self.processes[current_index].after()
+                .and_then(|after| self.process_index(after)) else {
+                return false;
+            };
+            current_index = next_index;
+        }
+        false
+    }
+
+    /// Change which process the given process runs after, and start or stop
+    /// the process to match the status of its new upstream.
+    fn set_upstream(&mut self, process_index: usize, upstream: Option<String>) {
+        let process = &mut self.processes[process_index];
+        if process.after() == upstream.as_deref() {
+            return;
+        }
+        process.set_after(upstream.clone());
+
+        self.after = MultiMap::new();
+        for (process_index, process) in self.processes.iter().enumerate() {
+            if let Some(after) = process.after() {
+                self.after.insert(after.to_owned(), process_index);
+            }
+        }
+
+        let upstream_status = upstream.as_deref()
+            .and_then(|upstream| self.process_index(upstream))
+            .map(|upstream_index| self.processes[upstream_index].status());
+
+        let process = &mut self.processes[process_index];
+        match (&upstream, upstream_status) {
+            (None, _) => {
+                self.event_log.push(format!("removed upstream of {}", process.name()));
+                if matches!(process.status(), ProcessStatus::NotStarted | ProcessStatus::WaitingForUpstream) {
+                    process.restart();
+                }
+            },
+            (Some(upstream), Some(upstream_status)) if upstream_status.is_success() => {
+                self.event_log.push(format!("changed upstream of {} to {upstream}", process.name()));
+                process.restart();
+            },
+            (Some(upstream), _) => {
+                self.event_log.push(format!("changed upstream of {} to {upstream}", process.name()));
+                process.mark_waiting_for_upstream();
+            },
+        }
+    }
+
+    pub(crate) fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    pub(crate) fn checkpoint_name(&self) -> Option<&str> {
+        self.checkpoint_name.as_deref()
+    }
+
+    /// Start typing the name of a new checkpoint.
+    pub(crate) fn start_checkpoint(&mut self) {
+        self.checkpoint_name = Some(String::new());
+    }
+
+    pub(crate) fn cancel_checkpoint(&mut self) {
+        self.checkpoint_name = None;
+    }
+
+    pub(crate) fn push_checkpoint_name_char(&mut self, char: char) {
+        if let Some(checkpoint_name) = &mut self.checkpoint_name {
+            checkpoint_name.push(char);
+        }
+    }
+
+    pub(crate) fn pop_checkpoint_name_char(&mut self) {
+        if let Some(checkpoint_name) = &mut self.checkpoint_name {
+            checkpoint_name.pop();
+        }
+    }
+
+    /// Save the current screen of every process under the typed name.
+    pub(crate) fn finish_checkpoint(&mut self) {
+        let Some(checkpoint_name) = self.checkpoint_name.take() else {
+            return;
+        };
+        let checkpoint_name = if checkpoint_name.trim().is_empty() {
+            format!("Checkpoint {}", self.checkpoints.len() + 1)
+        } else {
+            checkpoint_name.trim().to_owned()
+        };
+
+        let screens: HashMap<String, Vec<String>> = self.processes.iter()
+            .map(|process| (process.name().to_owned(), process.screen_text_lines()))
+            .collect();
+        self.event_log.push(format!("saved checkpoint {checkpoint_name}"));
+        self.status_message = Some(format!("Saved checkpoint {checkpoint_name}"));
+        self.checkpoints.push(Checkpoint::new(checkpoint_name, screens));
+    }
+
+    pub(crate) fn clone_changes(&self) -> Option<&str> {
+        self.clone_changes.as_deref()
+    }
+
+    pub(crate) fn start_clone(&mut self) {
+        self.clone_changes = Some(String::new());
+    }
+
+    pub(crate) fn cancel_clone(&mut self) {
+        self.clone_changes = None;
+    }
+
+    pub(crate) fn push_clone_char(&mut self, char: char) {
+        if let Some(clone_changes) = &mut self.clone_changes {
+            clone_changes.push(char);
+        }
+    }
+
+    pub(crate) fn pop_clone_char(&mut self) {
+        if let Some(clone_changes) = &mut self.clone_changes {
+            clone_changes.pop();
+        }
+    }
+
+    /// Start a copy of the focused process with the typed changes: words such
+    /// as `PORT=3001` set environment variables, and any other words are
+    /// added to the end of the command.
+    pub(crate) fn finish_clone(&mut self) {
+        let Some(clone_changes) = self.clone_changes.take() else {
+            return;
+        };
+
+        let (extra_environment_variables, extra_arguments): (Vec<&str>, Vec<&str>) = clone_changes
+            .split_whitespace()
+            .partition(|word| parse_environment_variable(word).is_some());
+        let extra_environment_variables = extra_environment_variables.into_iter()
+            .filter_map(parse_environment_variable)
+            .collect();
+        let extra_arguments = extra_arguments.into_iter().map(str::to_owned).collect();
+
+        let process = self.focused_process();
+        let clone_name = (1..)
+            .map(|clone_number| format!("{} (clone {clone_number})", process.name()))
+            .find(|clone_name| self.processes.iter().all(|process| process.name() != clone_name))
+            .unwrap();
+        let clone_config = process.cloned_config(clone_name.clone(), extra_arguments, extra_environment_variables);
+
+        match self.start_process(clone_config) {
+            Ok(()) => {
+                self.event_log.push(format!("started {clone_name}"));
+                self.focused_process_index = self.processes.len() - 1;
+                self.combined_log_focused = false;
+            },
+            Err(error) => {
+                self.status_message = Some(format!("Failed to start clone: {error}"));
+            },
+        }
+    }
+
+    pub(crate) fn checkpoint_browser(&self) -> Option<&CheckpointBrowser> {
+        self.checkpoint_browser.as_ref()
+    }
+
+    pub(crate) fn open_checkpoint_browser(&mut self) {
+        if self.checkpoints.is_empty() {
+            self.status_message = Some("No checkpoints have been saved".to_owned());
+        } else {
+            self.checkpoint_browser = Some(CheckpointBrowser::new(self.checkpoints.len()));
+        }
+    }
+
+    pub(crate) fn move_checkpoint_selection_up(&mut self) {
+        if let Some(checkpoint_browser) = &mut self.checkpoint_browser {
+            checkpoint_browser.move_selection_up();
+        }
+    }
+
+    pub(crate) fn move_checkpoint_selection_down(&mut self) {
+        if let Some(checkpoint_browser) = &mut self.checkpoint_browser {
+            checkpoint_browser.move_selection_down(self.checkpoints.len());
+        }
+    }
+
+    /// Compare the focused process with the selected checkpoint.
+    pub(crate) fn show_checkpoint_diff(&mut self) {
+        if let Some(checkpoint_browser) = &mut self.checkpoint_browser {
+            checkpoint_browser.showing_diff = true;
+        }
+    }
+
+    /// Go back from the comparison to the list of checkpoints, or from the
+    /// list of checkpoints to the output of the focused process.
+    pub(crate) fn back_from_checkpoint_browser(&mut self) {
+        match &mut self.checkpoint_browser {
+            Some(checkpoint_browser) if checkpoint_browser.showing_diff => {
+                checkpoint_browser.showing_diff = false;
+            },
+            _ => self.checkpoint_browser = None,
+        }
+    }
+
+    pub(crate) fn start_search(&mut self) {
+        self.search = Some(Search::new());
+    }
+
+    pub(crate) fn cancel_search(&mut self) {
+        self.search = None;
+    }
+
+    pub(crate) fn push_search_char(&mut self, char: char) {
+        if let Some(search) = &mut self.search {
+            search.query.push(char);
+        }
+    }
+
+    pub(crate) fn pop_search_char(&mut self) {
+        if let Some(search) = &mut self.search {
+            search.query.pop();
+        }
+    }
+
+    /// Finish typing the search query, and jump to the most recent match.
+    pub(crate) fn finish_search_query(&mut self) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+
+        if search.query.is_empty() {
+            self.search = None;
+            return;
+        }
+
+        search.editing = false;
+        search.current_match = None;
+        self.jump_to_search_match(true);
+    }
+
+    /// Jump to the next match of the search in the focused process, either
+    /// backwards to earlier output or forwards to later output.
+    pub(crate) fn jump_to_search_match(&mut self, backwards: bool) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        if search.editing {
+            return;
+        }
+
+        let process = &mut self.processes[self.focused_process_index];
+        let current_row = search.current_match
+            .filter(|(process_index, _row)| *process_index == self.focused_process_index)
+            .map(|(_process_index, row)| row);
+
+        match process.find_row(&search.query, current_row, backwards) {
+            Some(row) => {
+                process.scroll_to_row(row);
+                search.current_match = Some((self.focused_process_index, row));
+            },
+            None => {
+                self.status_message = Some(format!("No more matches for {:?}", search.query));
+            },
+        }
+    }
+
+    /// Scroll the focused process to the output of the previous or next run.
+    pub(crate) fn jump_to_run(&mut self, backwards: bool) {
+        if !self.processes[self.focused_process_index].scroll_to_run(backwards) {
+            let message = if backwards { "No earlier runs" } else { "No later runs" };
+            self.status_message = Some(message.to_owned());
+        }
+    }
+
+    /// Focus the process at the given index, as chosen by the user.
+    pub(crate) fn focus(&mut self, process_index: usize) {
+        if process_index < self.processes.len() {
+            self.disable_autofocus();
+            self.focused_process_index = process_index;
+            self.combined_log_focused = false;
+        }
+    }
+
+    pub(crate) fn send_ctrl_d_to_focused(&mut self) {
+        if self.refuse_input_if_readonly() {
+            return;
+        }
+        self.processes[self.focused_process_index].send_ctrl_d();
+    }
+
+    /// Send the input for a quick key of the focused process, returning
+    /// whether the focused process has a quick key for the given key.
+    pub(crate) fn send_quick_key_to_focused(&mut self, key: char) -> bool {
+        if self.focused_process().process_config.quick_key_input(key).is_none() {
+            return false;
+        }
+        if !self.refuse_input_if_readonly() {
+            self.processes[self.focused_process_index].send_quick_key(key);
+        }
+        true
+    }
+
+    /// Allow or refuse input to the focused process.
+    pub(crate) fn toggle_focused_readonly(&mut self) {
+        let process = &mut self.processes[self.focused_process_index];
+        process.readonly = !process.readonly;
+        self.status_message = Some(if process.readonly {
+            format!("{} is now read-only", process.name())
+        } else {
+            format!("{} now accepts input", process.name())
+        });
+    }
+
+    /// Show a notification instead of sending input if the focused process is
+    /// read-only, returning whether it is.
+    fn refuse_input_if_readonly(&mut self) -> bool {
+        let process = self.focused_process();
+        if !process.readonly {
+            return false;
+        }
+        self.status_message = Some(format!("{} is read-only", process.name()));
+        true
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProcessStatus {
+    /// The process has not been started.
+    NotStarted,
+
+    /// The process will run once an upstream process reaches a success state.
+    WaitingForUpstream,
+
+    /// The process is running and has not reached a success or error state.
+    Running,
+
+    /// The process is running and has reached a success state.
+    Success,
+
+    /// The process is running and has passed its ready check.
+    Ready,
+
+    /// The process is running and has reached an error state.
+    Errors {
+        error_count: Option<u64>,
+    },
+
+    /// The process has exited.
+    Exited {
+        exit_code: u32,
+
+        /// Whether the process was expected to keep running, such as a
+        /// watcher, in which case exiting is a failure whatever the exit code.
+        unexpected: bool,
+    },
+
+    /// The process couldn't be started, such as when its executable couldn't
+    /// be found.
+    StartFailed,
+}
+
+impl ProcessStatus {
+    pub(crate) fn is_failure(&self) -> bool {
+        match self {
+            ProcessStatus::NotStarted => false,
+            ProcessStatus::WaitingForUpstream => false,
+            ProcessStatus::Running => false,
+            ProcessStatus::Success => false,
+            ProcessStatus::Ready => false,
+            ProcessStatus::Errors { .. } => true,
+            ProcessStatus::Exited { exit_code, unexpected } => *exit_code != 0 || *unexpected,
+            ProcessStatus::StartFailed => true,
+        }
+    }
+
+    pub(crate) fn is_success(&self) -> bool {
+        match self {
+            ProcessStatus::NotStarted => false,
+            ProcessStatus::WaitingForUpstream => false,
+            ProcessStatus::Running => false,
+            ProcessStatus::Success => true,
+            ProcessStatus::Ready => true,
+            ProcessStatus::Errors { .. } => false,
+            ProcessStatus::Exited { exit_code, unexpected } => *exit_code == 0 && !*unexpected,
+            ProcessStatus::StartFailed => false,
+        }
+    }
+
+    /// The name of the status, as used by the control socket and hooks.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ProcessStatus::NotStarted => "not-started",
+            ProcessStatus::WaitingForUpstream => "waiting",
+            ProcessStatus::Running => "running",
+            ProcessStatus::Success => "success",
+            ProcessStatus::Ready => "ready",
+            ProcessStatus::Errors { .. } => "errors",
+            ProcessStatus::Exited { .. } => "exited",
+            ProcessStatus::StartFailed => "start-failed",
+        }
+    }
+
+    pub(crate) fn description(&self) -> String {
+        match self {
+            ProcessStatus::NotStarted => "is not started".to_owned(),
+            ProcessStatus::WaitingForUpstream => "is waiting".to_owned(),
+            ProcessStatus::Running => "is running".to_owned(),
+            ProcessStatus::Success => "succeeded".to_owned(),
+            ProcessStatus::Ready => "is ready".to_owned(),
+            ProcessStatus::Errors { error_count: Some(error_count) } => format!("failed with {error_count} errors"),
+            ProcessStatus::Errors { error_count: None } => "failed".to_owned(),
+            ProcessStatus::Exited { exit_code, unexpected: false } => format!("exited with code {exit_code}"),
+            ProcessStatus::Exited { exit_code, unexpected: true } => format!("exited unexpectedly with code {exit_code}"),
+            ProcessStatus::StartFailed => "failed to start".to_owned(),
         }
     }
 }
@@ -221,6 +1480,10 @@ enum ProcessInstanceState {
     /// This process has not yet been triggered.
     NotStarted,
 
+    /// This process was stopped on request, and won't be started again until
+    /// requested, even if its upstream process succeeds.
+    Stopped,
+
     /// This process will be triggered once an upstream process reaches a
     /// success state.
     WaitingForUpstream,
@@ -228,6 +1491,17 @@ enum ProcessInstanceState {
     /// This process should be restarted.
     PendingRestart,
 
+    /// This process is being spawned on a worker thread.
+    Starting {
+        instance_rx: std::sync::mpsc::Receiver<Result<ProcessInstance, ProcessError>>,
+        status_rx: std::sync::mpsc::Receiver<ProcessStatus>,
+    },
+
+    /// This process couldn't be started.
+    StartFailed {
+        message: String,
+    },
+
     /// This process has a running instance.
     Running {
         instance: ProcessInstance,
@@ -243,7 +1517,48 @@ pub(crate) struct Process {
     pty_system: SharedPtySystem,
     pty_size: PtySize,
     instance_state: ProcessInstanceState,
-    on_change: TerminalWaker,
+    on_change: ChangeNotifier,
+    output_sinks: OutputSinks,
+    /// The output of the previous instance, kept when the process is
+    /// restarted without clearing the output.
+    previous_output: Option<String>,
+    startup_timing: StartupTiming,
+    /// When the status of the process last changed.
+    status_changed_at: Option<Instant>,
+    /// When the current instance was started.
+    instance_started_at: Option<Instant>,
+    restart_count: usize,
+    highlighter: Highlighter,
+    /// How many rows the output of the process is scrolled back from the
+    /// bottom.
+    scroll_offset: usize,
+    /// Whether input from the user, such as quick keys, is refused.
+    readonly: bool,
+    /// Watches the files matching `watch`, if any.
+    file_watcher: Option<FileWatcher>,
+    /// When the process was recently restarted because a watched file changed.
+    watch_restart_times: VecDeque<Instant>,
+    /// When the restart scheduled by `restart_policy` is due.
+    restart_at: Option<Instant>,
+    /// How many times in a row the process has exited soon after starting.
+    rapid_exit_count: u32,
+}
+
+/// How long a process has been running, and how many times it's been
+/// restarted.
+#[derive(Clone, Copy)]
+pub(crate) struct ProcessStats {
+    /// How long the current instance has been running, if it's still running.
+    pub(crate) uptime: Option<Duration>,
+    pub(crate) restart_count: usize,
+}
+
+/// When a process first started and first reached a success state during this
+/// session of Mintaka.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct StartupTiming {
+    pub(crate) started_at: Option<Instant>,
+    pub(crate) ready_at: Option<Instant>,
 }
 
 impl Process {
@@ -251,10 +1566,13 @@ impl Process {
         process_config: ProcessConfig,
         pty_system: SharedPtySystem,
         pty_size: PtySize,
-        on_change: TerminalWaker,
+        on_change: ChangeNotifier,
+        output_sinks: OutputSinks,
     ) -> Self {
-        let name = process_config.name.clone()
-            .unwrap_or_else(|| process_config.command.join(" "));
+        let name = process_config.name();
+
+        // The highlights are checked when the config is validated.
+        let highlighter = Highlighter::new(&process_config.highlights).unwrap_or_default();
 
         let instance_state = if process_config.autostart() {
             ProcessInstanceState::PendingRestart
@@ -262,6 +1580,14 @@ impl Process {
             ProcessInstanceState::NotStarted
         };
 
+        let readonly = process_config.readonly;
+        let file_watcher = if process_config.watch.is_empty() {
+            None
+        } else {
+            let base_directory = process_config.working_directory.clone().unwrap_or_else(|| PathBuf::from("."));
+            Some(FileWatcher::spawn(&process_config.watch, &base_directory, on_change.clone()))
+        };
+
         Self {
             name,
             process_config,
@@ -269,102 +1595,704 @@ impl Process {
             pty_size,
             instance_state,
             on_change,
+            output_sinks,
+            previous_output: None,
+            startup_timing: StartupTiming::default(),
+            status_changed_at: None,
+            instance_started_at: None,
+            restart_count: 0,
+            highlighter,
+            scroll_offset: 0,
+            readonly,
+            file_watcher,
+            watch_restart_times: VecDeque::new(),
+            restart_at: None,
+            rapid_exit_count: 0,
+        }
+    }
+
+    fn start(
+        &mut self,
+    ) -> Result<(), ProcessError> {
+        let pty_pair = self.pty_system.openpty(self.pty_size).unwrap();
+
+        let (status_tx, status_rx) = std::sync::mpsc::channel();
+        let status_sender = StatusSender::new(status_tx);
+        let (instance_tx, instance_rx) = std::sync::mpsc::channel();
+
+        // Spawning a process can be slow, so spawn on a worker thread to avoid
+        // delaying other processes.
+        let name = self.name.clone();
+        let process_config = self.process_config.clone();
+        let on_change = self.on_change.clone();
+        let output_sinks = self.output_sinks.clone();
+        let previous_output = self.previous_output.take();
+        std::thread::spawn(move || {
+            let instance = ProcessInstance::start(
+                &name,
+                &process_config,
+                pty_pair,
+                on_change.clone(),
+                status_sender,
+                output_sinks,
+                previous_output,
+            );
+
+            // If the process was stopped while it was being spawned, then
+            // nothing will receive the instance, so stop it here instead.
+            if let Err(std::sync::mpsc::SendError(Ok(mut instance))) = instance_tx.send(instance) {
+                instance.kill();
+            }
+
+            on_change.wake();
+        });
+
+        self.instance_state = ProcessInstanceState::Starting {
+            instance_rx,
+            status_rx,
+        };
+        self.scroll_offset = 0;
+
+        if self.instance_started_at.is_some() {
+            self.restart_count += 1;
+        }
+        self.instance_started_at = Some(Instant::now());
+
+        // Wake once the grace period is over, so that autofocus and the process
+        // list notice any errors that were ignored during it.
+        let startup_grace = self.process_config.startup_grace();
+        if !startup_grace.is_zero() {
+            let on_change = self.on_change.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(startup_grace);
+                on_change.wake();
+            });
+        }
+
+        if self.startup_timing.started_at.is_none() {
+            self.startup_timing.started_at = Some(Instant::now());
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn config_source(&self) -> Option<&ConfigSource> {
+        self.process_config.source.as_ref()
+    }
+
+    pub(crate) fn background_color(&self) -> Option<RgbColor> {
+        self.process_config.background_color()
+    }
+
+    pub(crate) fn accent_color(&self) -> Option<RgbColor> {
+        self.process_config.accent_color()
+    }
+
+    pub(crate) fn group(&self) -> Option<&str> {
+        self.process_config.group.as_deref()
+    }
+
+    fn cloned_config(
+        &self,
+        name: String,
+        extra_arguments: Vec<String>,
+        extra_environment_variables: Vec<(String, String)>,
+    ) -> ProcessConfig {
+        self.process_config.cloned(name, extra_arguments, extra_environment_variables)
+    }
+
+    pub(crate) fn notifies(&self, event: NotificationEvent) -> bool {
+        self.process_config.notifies(event)
+    }
+
+    pub(crate) fn after(&self) -> Option<&str> {
+        self.process_config.after.as_deref()
+    }
+
+    fn on_ready(&self) -> Option<&[String]> {
+        self.process_config.on_ready.as_deref()
+    }
+
+    /// Run the `on_error`, `on_success` or `on_exit` command of the process
+    /// for a change of status, describing the result for the event log.
+    /// `on_error` and `on_success` are only run when the process starts
+    /// failing or succeeding, rather than for every new error count.
+    fn run_status_hook(&self, old_status: ProcessStatus, new_status: ProcessStatus) -> Option<String> {
+        let (hook_name, hook) = match new_status {
+            ProcessStatus::Errors { .. } if !matches!(old_status, ProcessStatus::Errors { .. }) => {
+                ("on_error", self.process_config.on_error.as_deref())
+            },
+            ProcessStatus::Success | ProcessStatus::Ready if !old_status.is_success() => {
+                ("on_success", self.process_config.on_success.as_deref())
+            },
+            ProcessStatus::Exited { .. } => ("on_exit", self.process_config.on_exit.as_deref()),
+            _ => return None,
+        };
+        let hook = hook?;
+
+        let mut environment_variables = vec![
+            ("MINTAKA_PROCESS", self.name().to_owned()),
+            ("MINTAKA_STATUS", new_status.name().to_owned()),
+        ];
+        if let ProcessStatus::Errors { error_count: Some(error_count) } = new_status {
+            environment_variables.push(("MINTAKA_ERROR_COUNT", error_count.to_string()));
+        }
+        if let ProcessStatus::Exited { exit_code, .. } = new_status {
+            environment_variables.push(("MINTAKA_EXIT_CODE", exit_code.to_string()));
+        }
+
+        match spawn_hook(hook, &environment_variables) {
+            Ok(()) => Some(format!("ran {hook_name} for {}: {}", self.name(), hook.join(" "))),
+            Err(error) => Some(format!("failed to run {hook_name} for {}: {error}", self.name())),
+        }
+    }
+
+    /// The URL of the process, either from the config or captured from its
+    /// output.
+    pub(crate) fn url(&self) -> Option<String> {
+        if let Some(url) = &self.process_config.url {
+            return Some(url.clone());
+        }
+
+        match &self.instance_state {
+            ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped
+            | ProcessInstanceState::WaitingForUpstream
+            | ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. }
+            | ProcessInstanceState::StartFailed { .. } => None,
+            ProcessInstanceState::Running { instance, .. } => instance.published_url.lock().unwrap().clone(),
+        }
+    }
+
+    fn set_after(&mut self, after: Option<String>) {
+        self.process_config.after = after;
+    }
+
+    /// Whether the process is in the middle of some work that would be
+    /// interrupted by stopping it, such as restarting or compiling.
+    fn is_busy(&self) -> bool {
+        match &self.instance_state {
+            ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped
+            | ProcessInstanceState::WaitingForUpstream
+            | ProcessInstanceState::StartFailed { .. } => false,
+            ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. } => true,
+            ProcessInstanceState::Running { status, .. } => {
+                matches!(status, ProcessStatus::Running) && self.process_config.has_status_analysis()
+            },
+        }
+    }
+
+    /// How long the current instance has been running, if it's running.
+    pub(crate) fn uptime(&self) -> Option<Duration> {
+        match self.instance_state {
+            ProcessInstanceState::Running { .. } => self.instance_started_at.map(|started_at| started_at.elapsed()),
+            _ => None,
+        }
+    }
+
+    /// How many times the process has been started after first being started.
+    pub(crate) fn restart_count(&self) -> usize {
+        self.restart_count
+    }
+
+    pub(crate) fn stats(&self) -> ProcessStats {
+        let has_exited = matches!(self.status(), ProcessStatus::Exited { .. });
+        ProcessStats {
+            uptime: self.uptime().filter(|_| !has_exited),
+            restart_count: self.restart_count,
+        }
+    }
+
+    pub(crate) fn startup_timing(&self) -> StartupTiming {
+        self.startup_timing
+    }
+
+    fn restart(&mut self) {
+        self.rapid_exit_count = 0;
+        self.kill(ProcessInstanceState::PendingRestart);
+    }
+
+    fn is_stopped(&self) -> bool {
+        matches!(self.instance_state, ProcessInstanceState::Stopped)
+    }
+
+    fn mark_waiting_for_upstream(&mut self) {
+        self.kill(ProcessInstanceState::WaitingForUpstream);
+    }
+
+    /// Stop the process before quitting, returning a flag that is set once
+    /// the process has exited, if the process was running.
+    fn stop_for_shutdown(&mut self) -> Option<Arc<AtomicBool>> {
+        let exited = match &self.instance_state {
+            ProcessInstanceState::Running { instance, .. } => Some(Arc::clone(&instance.exited)),
+            _ => None,
+        };
+        self.kill(ProcessInstanceState::Stopped);
+        exited
+    }
+
+    fn kill(&mut self, new_process_instance_state: ProcessInstanceState) {
+        self.restart_at = None;
+        let previous_instance_state = std::mem::replace(
+            &mut self.instance_state,
+            new_process_instance_state,
+        );
+        if let ProcessInstanceState::Running { mut instance, status, .. } = previous_instance_state {
+            instance.kill();
+
+            let is_restarting = matches!(
+                self.instance_state,
+                ProcessInstanceState::PendingRestart | ProcessInstanceState::WaitingForUpstream
+            );
+            if is_restarting && !self.process_config.clear_on_restart() {
+                self.previous_output = Some(instance.output_with_run_header(&self.run_header(status)));
+            }
+        }
+    }
+
+    /// Describe the current run, such as `run #12 · exit 0 · 3.4s`, given its
+    /// final status.
+    fn run_header(&self, status: ProcessStatus) -> String {
+        let (outcome, ended_at) = match status {
+            ProcessStatus::Exited { exit_code, .. } => (format!("exit {exit_code}"), self.status_changed_at),
+            _ => ("stopped".to_owned(), None),
+        };
+        let ended_at = ended_at.unwrap_or_else(Instant::now);
+        let duration = self.instance_started_at
+            .map(|started_at| ended_at.saturating_duration_since(started_at))
+            .unwrap_or_default();
+        format!("run #{} · {outcome} · {:.1}s", self.restart_count + 1, duration.as_secs_f64())
+    }
+
+    /// Schedule a restart after the process has exited, as set by
+    /// `restart_policy`, returning a description of the decision for the event
+    /// log. The output of the process is kept until the restart, which is
+    /// delayed for longer each time the process exits soon after starting, and
+    /// is abandoned after `max_restarts` such exits in a row.
+    fn apply_restart_policy(&mut self, exit_code: u32, is_failure: bool) -> Option<String> {
+        if !self.process_config.restart_policy().restarts_after(is_failure) {
+            return None;
+        }
+
+        let exited_rapidly = self.instance_started_at
+            .is_some_and(|started_at| started_at.elapsed() < RAPID_EXIT_DURATION);
+        self.rapid_exit_count = if exited_rapidly { self.rapid_exit_count + 1 } else { 0 };
+
+        if self.rapid_exit_count > self.process_config.max_restarts() {
+            self.kill(ProcessInstanceState::StartFailed {
+                message: format!("exited {} times in a row soon after starting", self.rapid_exit_count),
+            });
+            self.status_changed_at = Some(Instant::now());
+            return Some(format!(
+                "stopped restarting {} after it exited {} times in a row soon after starting",
+                self.name,
+                self.rapid_exit_count,
+            ));
+        }
+
+        let restart_delay = restart_delay(self.rapid_exit_count);
+        let restart_at = Instant::now() + restart_delay;
+        self.restart_at = Some(restart_at);
+        let on_change = self.on_change.clone();
+        std::thread::spawn(move || {
+            // The UI is woken every second so that the countdown to the
+            // restart in the process list is kept up to date.
+            while let Some(time_until_restart) = restart_at.checked_duration_since(Instant::now()).filter(|duration| !duration.is_zero()) {
+                std::thread::sleep(time_until_restart.min(Duration::from_secs(1)));
+                on_change.wake();
+            }
+        });
+
+        Some(format!(
+            "restarting {} in {:.0}s because it exited with code {exit_code}",
+            self.name,
+            restart_delay.as_secs_f64(),
+        ))
+    }
+
+    /// Whether a file matching `watch` has changed since the last call, and
+    /// the process should be restarted. Processes that haven't been started,
+    /// are stopped or are waiting for their upstream process aren't restarted.
+    fn take_watched_file_change(&mut self) -> bool {
+        let changed = self.file_watcher.as_ref().is_some_and(|file_watcher| file_watcher.take_changed());
+        changed && !matches!(self.status(), ProcessStatus::NotStarted | ProcessStatus::WaitingForUpstream)
+    }
+
+    /// Record a restart caused by a watched file, returning whether the
+    /// process has been restarted so often that it's a restart storm.
+    /// Restarts are counted for each process, since saving one file restarts
+    /// every process that watches it.
+    fn record_watch_restart(&mut self) -> bool {
+        let now = Instant::now();
+        while self.watch_restart_times.front().is_some_and(|restart_time| now - *restart_time > RESTART_STORM_WINDOW) {
+            self.watch_restart_times.pop_front();
+        }
+        self.watch_restart_times.push_back(now);
+        self.watch_restart_times.len() > RESTART_STORM_LIMIT
+    }
+
+    fn send_ctrl_d(&mut self) {
+        if let ProcessInstanceState::Running { instance, .. } = &mut self.instance_state {
+            instance.send_ctrl_d();
+        }
+    }
+
+    fn send_quick_key(&mut self, key: char) {
+        let Some(input) = self.process_config.quick_key_input(key) else {
+            return;
+        };
+
+        if let ProcessInstanceState::Running { instance, .. } = &mut self.instance_state {
+            instance.send_input(input.as_bytes());
+        }
+    }
+
+    fn handle_status_updates(&mut self, exits_are_failures: bool) -> Option<ProcessStatus> {
+        match &mut self.instance_state {
+            ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped
+            | ProcessInstanceState::WaitingForUpstream
+            | ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. }
+            | ProcessInstanceState::StartFailed { .. } => None,
+            ProcessInstanceState::Running { status, status_rx, .. } => {
+                // Watchers are expected to keep running, so exiting is
+                // unexpected even when the exit code is zero.
+                let new_status = status_rx.try_iter().last().map(|new_status| match new_status {
+                    ProcessStatus::Exited { exit_code, .. } => ProcessStatus::Exited {
+                        exit_code,
+                        unexpected: exits_are_failures,
+                    },
+                    new_status => new_status,
+                });
+
+                if let Some(new_status) = new_status {
+                    *status = new_status;
+                    self.status_changed_at = Some(Instant::now());
+
+                    if new_status.is_success() && self.startup_timing.ready_at.is_none() {
+                        self.startup_timing.ready_at = Some(Instant::now());
+                    }
+                }
+
+                new_status
+            },
+        }
+    }
+
+    fn do_work(&mut self) -> Result<(), ProcessError> {
+        if self.restart_at.is_some_and(|restart_at| Instant::now() >= restart_at) {
+            self.kill(ProcessInstanceState::PendingRestart);
+        }
+
+        if matches!(self.instance_state, ProcessInstanceState::PendingRestart) {
+            self.start()?;
+        }
+
+        if let ProcessInstanceState::Starting { instance_rx, .. } = &self.instance_state {
+            let instance = match instance_rx.try_recv() {
+                Ok(instance) => instance,
+                Err(std::sync::mpsc::TryRecvError::Empty) => return Ok(()),
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => Err(ProcessError::SpawnThreadFailed),
+            };
+
+            let previous_instance_state = std::mem::replace(
+                &mut self.instance_state,
+                ProcessInstanceState::NotStarted,
+            );
+            if let ProcessInstanceState::Starting { status_rx, .. } = previous_instance_state {
+                match instance {
+                    Ok(mut instance) => {
+                        instance.resize(self.pty_size);
+                        self.instance_state = ProcessInstanceState::Running {
+                            instance,
+                            status: ProcessStatus::Running,
+                            status_rx,
+                        };
+                    },
+                    Err(error) => {
+                        self.instance_state = ProcessInstanceState::StartFailed {
+                            message: error.to_string(),
+                        };
+                        self.status_changed_at = Some(Instant::now());
+                        return Err(error);
+                    },
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The current working directory of the process, if it's running and the
+    /// working directory can be found.
+    pub(crate) fn working_directory(&self) -> Option<PathBuf> {
+        working_directories::working_directory_of(self.process_id()?)
+    }
+
+    /// How long until the process is restarted after exiting, if a restart is
+    /// pending.
+    pub(crate) fn time_until_restart(&self) -> Option<Duration> {
+        self.restart_at
+            .and_then(|restart_at| restart_at.checked_duration_since(Instant::now()))
+            .filter(|time_until_restart| !time_until_restart.is_zero())
+    }
+
+    /// Describe why the process couldn't be started, if it couldn't.
+    pub(crate) fn start_error(&self) -> Option<&str> {
+        match &self.instance_state {
+            ProcessInstanceState::StartFailed { message } => Some(message),
+            _ => None,
+        }
+    }
+
+    fn resize(&mut self, pty_size: PtySize) {
+        if self.pty_size == pty_size {
+            return;
+        }
+        self.pty_size = pty_size;
+        if let ProcessInstanceState::Running { instance, .. } = &mut self.instance_state {
+            instance.resize(pty_size);
+        }
+    }
+
+    pub(crate) fn status(&self) -> ProcessStatus {
+        match &self.instance_state {
+            ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped => ProcessStatus::NotStarted,
+            ProcessInstanceState::WaitingForUpstream => ProcessStatus::WaitingForUpstream,
+            ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. } => ProcessStatus::Running,
+            ProcessInstanceState::StartFailed { .. } => ProcessStatus::StartFailed,
+            ProcessInstanceState::Running { status, .. } => *status,
+        }
+
+    }
+
+    /// The status used for autofocus, the status user var and notifications,
+    /// where errors during the startup grace period are treated as still
+    /// running.
+    pub(crate) fn alerting_status(&self) -> ProcessStatus {
+        match self.status() {
+            ProcessStatus::Errors { .. } if self.is_in_startup_grace_period() => ProcessStatus::Running,
+            status => status,
+        }
+    }
+
+    fn is_in_startup_grace_period(&self) -> bool {
+        self.instance_started_at
+            .is_some_and(|started_at| started_at.elapsed() < self.process_config.startup_grace())
+    }
+
+    pub(crate) fn highlighter(&self) -> &Highlighter {
+        &self.highlighter
+    }
+
+    pub(crate) fn scroll_offset(&self) -> usize {
+        self.scroll_offset
+    }
+
+    /// How far through the output the process is scrolled, as a percentage
+    /// where the end of the output is 100%.
+    pub(crate) fn scroll_percentage(&self) -> usize {
+        let max_scroll_offset = self.max_scroll_offset();
+        if max_scroll_offset == 0 {
+            100
+        } else {
+            100 * (max_scroll_offset - self.scroll_offset.min(max_scroll_offset)) / max_scroll_offset
         }
     }
 
-    fn start(
-        &mut self,
-    ) -> Result<(), ProcessError> {
-        let pty_pair = self.pty_system.openpty(self.pty_size).unwrap();
+    fn max_scroll_offset(&self) -> usize {
+        match &self.instance_state {
+            ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped
+            | ProcessInstanceState::WaitingForUpstream
+            | ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. }
+            | ProcessInstanceState::StartFailed { .. } => 0,
+            ProcessInstanceState::Running { instance, .. } => instance.max_scroll_offset(),
+        }
+    }
 
-        let (status_tx, status_rx) = std::sync::mpsc::channel();
+    fn scroll_back(&mut self, rows: usize) {
+        self.scroll_offset = (self.scroll_offset + rows).min(self.max_scroll_offset());
+    }
 
-        let instance = ProcessInstance::start(
-            &self.process_config,
-            pty_pair,
-            self.on_change.clone(),
-            status_tx,
-        )?;
+    fn scroll_forward(&mut self, rows: usize) {
+        self.scroll_offset = self.scroll_offset.saturating_sub(rows);
+    }
 
-        self.instance_state = ProcessInstanceState::Running {
-            instance,
-            status: ProcessStatus::Running,
-            status_rx,
+    /// Find the nearest row that matches the query, starting from the given
+    /// row or, if there is no row, from the end of the output.
+    fn find_row(&self, query: &str, start_row: Option<usize>, backwards: bool) -> Option<usize> {
+        let ProcessInstanceState::Running { instance, .. } = &self.instance_state else {
+            return None;
         };
+        let row_texts = instance.row_texts();
 
-        Ok(())
+        if backwards {
+            let end_row = start_row.unwrap_or(row_texts.len()).min(row_texts.len());
+            row_texts[..end_row].iter().rposition(|text| search::is_match(text, query))
+        } else {
+            let start_row = start_row? + 1;
+            row_texts.get(start_row..)?
+                .iter()
+                .position(|text| search::is_match(text, query))
+                .map(|row_offset| start_row + row_offset)
+        }
     }
 
-    pub(crate) fn name(&self) -> &str {
-        &self.name
+    /// Scroll to the header of the previous or next run, or to the end of the
+    /// output when there is no next run, returning whether there was anywhere
+    /// to scroll to.
+    fn scroll_to_run(&mut self, backwards: bool) -> bool {
+        let ProcessInstanceState::Running { instance, .. } = &self.instance_state else {
+            return false;
+        };
+        let row_texts = instance.row_texts();
+        let screen_rows = usize::from(self.pty_size.rows);
+        let top_row = row_texts.len().saturating_sub(self.scroll_offset + screen_rows);
+        let is_run_header = |row_text: &String| row_text.starts_with(RUN_HEADER_PREFIX);
+
+        let header_row = if backwards {
+            row_texts[..top_row].iter().rposition(is_run_header)
+        } else {
+            row_texts.iter()
+                .enumerate()
+                .skip(top_row + 1)
+                .find(|(_, row_text)| is_run_header(row_text))
+                .map(|(row, _)| row)
+        };
+
+        match header_row {
+            Some(header_row) => {
+                self.scroll_offset = row_texts.len()
+                    .saturating_sub(header_row + screen_rows)
+                    .min(instance.max_scroll_offset());
+                true
+            },
+            None if !backwards && self.scroll_offset > 0 => {
+                self.scroll_offset = 0;
+                true
+            },
+            None => false,
+        }
     }
 
-    fn restart(&mut self) {
-        self.kill(ProcessInstanceState::PendingRestart);
+    /// Scroll so that the given row is in the middle of the screen, as far as
+    /// possible.
+    fn scroll_to_row(&mut self, row: usize) {
+        let ProcessInstanceState::Running { instance, .. } = &self.instance_state else {
+            return;
+        };
+        let row_count = instance.row_count();
+        let end_row = row + 1 + usize::from(self.pty_size.rows) / 2;
+        self.scroll_offset = row_count.saturating_sub(end_row).min(instance.max_scroll_offset());
     }
 
-    fn mark_waiting_for_upstream(&mut self) {
-        self.kill(ProcessInstanceState::WaitingForUpstream);
+    pub(crate) fn process_id(&self) -> Option<u32> {
+        match &self.instance_state {
+            ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped
+            | ProcessInstanceState::WaitingForUpstream
+            | ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. }
+            | ProcessInstanceState::StartFailed { .. } => None,
+            ProcessInstanceState::Running { instance, .. } => instance.process_id,
+        }
     }
 
-    fn kill(&mut self, new_process_instance_state: ProcessInstanceState) {
-        let previous_instance_state = std::mem::replace(
-            &mut self.instance_state,
-            new_process_instance_state,
-        );
-        if let ProcessInstanceState::Running { mut instance, .. } = previous_instance_state {
-            instance.kill();
+    /// A line from the output of the last failed run that describes the
+    /// failure, if the process is currently failing: the first line that
+    /// mentions an error, or else the last line.
+    pub(crate) fn failure_summary(&self) -> Option<String> {
+        if !self.status().is_failure() {
+            return None;
         }
+        let output = self.last_failure_output()?;
+        let lines: Vec<&str> = output.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        lines.iter()
+            .find(|line| line.to_ascii_lowercase().contains("error"))
+            .or(lines.last())
+            .map(|line| line.to_string())
     }
 
-    fn handle_status_updates(&mut self) -> Option<ProcessStatus> {
-        match &mut self.instance_state {
+    pub(crate) fn last_failure_output(&self) -> Option<String> {
+        match &self.instance_state {
             ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped
             | ProcessInstanceState::WaitingForUpstream
-            | ProcessInstanceState::PendingRestart => None,
-            ProcessInstanceState::Running { status, status_rx, .. } => {
-                let new_status = status_rx.try_iter().last();
-
-                if let Some(new_status) = new_status {
-                    *status = new_status;
-                }
-
-                new_status
-            },
+            | ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. }
+            | ProcessInstanceState::StartFailed { .. } => None,
+            ProcessInstanceState::Running { instance, .. } => instance.last_failure_output.lock().unwrap().clone(),
         }
     }
 
-    fn do_work(&mut self) -> Result<(), ProcessError> {
-        if matches!(self.instance_state, ProcessInstanceState::PendingRestart) {
-            self.start()?;
+    /// Whether the process is running inside a sandbox.
+    pub(crate) fn is_sandboxed(&self) -> bool {
+        match &self.instance_state {
+            ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped
+            | ProcessInstanceState::WaitingForUpstream
+            | ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. }
+            | ProcessInstanceState::StartFailed { .. } => false,
+            ProcessInstanceState::Running { instance, .. } => instance.sandboxed,
         }
-
-        Ok(())
     }
 
-    fn resize(&mut self, pty_size: PtySize) {
-        self.pty_size = pty_size;
-        if let ProcessInstanceState::Running { instance, .. } = &mut self.instance_state {
-            instance.resize(pty_size);
+    /// The text of each line of the screen, without scrolling back, and
+    /// without trailing blank lines.
+    pub(crate) fn screen_text_lines(&self) -> Vec<String> {
+        let lines = match &self.instance_state {
+            ProcessInstanceState::Running { instance, .. } => instance.lines(0),
+            _ => Vec::new(),
+        };
+        let mut text_lines: Vec<String> = lines.iter()
+            .map(|line| line.as_str().trim_end().to_owned())
+            .collect();
+
+        while text_lines.last().is_some_and(|line| line.is_empty()) {
+            text_lines.pop();
         }
+
+        text_lines
     }
 
-    pub(crate) fn status(&self) -> ProcessStatus {
+    pub(crate) fn lines(&self) -> Vec<wezterm_term::Line> {
         match &self.instance_state {
-            ProcessInstanceState::NotStarted => ProcessStatus::NotStarted,
-            ProcessInstanceState::WaitingForUpstream => ProcessStatus::WaitingForUpstream,
-            ProcessInstanceState::PendingRestart => ProcessStatus::Running,
-            ProcessInstanceState::Running { status, .. } => *status,
+            ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped
+            | ProcessInstanceState::WaitingForUpstream
+            | ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. }
+            | ProcessInstanceState::StartFailed { .. } => Vec::new(),
+            ProcessInstanceState::Running { instance, .. } => instance.lines(self.scroll_offset),
         }
-
     }
 
-    fn lines(&self) -> Vec<wezterm_term::Line> {
+    /// Every line of output, including the scrollback.
+    pub(crate) fn all_lines(&self) -> Vec<wezterm_term::Line> {
         match &self.instance_state {
             ProcessInstanceState::NotStarted
+            | ProcessInstanceState::Stopped
             | ProcessInstanceState::WaitingForUpstream
-            | ProcessInstanceState::PendingRestart => Vec::new(),
-            ProcessInstanceState::Running { instance, .. } => instance.lines(),
+            | ProcessInstanceState::PendingRestart
+            | ProcessInstanceState::Starting { .. }
+            | ProcessInstanceState::StartFailed { .. } => Vec::new(),
+            ProcessInstanceState::Running { instance, .. } => instance.all_lines(),
         }
     }
 }
@@ -372,64 +2300,179 @@ impl Process {
 pub(crate) struct ProcessInstance {
     terminal: Arc<Mutex<wezterm_term::Terminal>>,
     pty_master: Box<dyn portable_pty::MasterPty>,
+    pty_writer: SharedWriter,
     child_process_killer: Box<dyn ChildKiller + Send + Sync>,
+    process_id: Option<u32>,
+    sandboxed: bool,
+
+    /// The output of the last run of the process that finished with errors.
+    last_failure_output: Arc<Mutex<Option<String>>>,
+
+    /// The URL captured from the output of the process using `publish_regex`.
+    published_url: Arc<Mutex<Option<String>>>,
+
+    stop_signal: Option<String>,
+
+    stop_timeout: Option<Duration>,
+
+    /// Set once the output has ended, which happens when the process exits.
+    exited: Arc<AtomicBool>,
+
+    /// The first row of the output of this run, after any output kept from
+    /// earlier runs.
+    run_start_row: StableRowIndex,
+}
+
+/// The state used by the thread that reads the output of a process, other
+/// than the terminal that the output is written to.
+struct OutputWatchers {
+    name: String,
+    process_status_analyzer: ProcessStatusAnalyzer,
+    failure_output_recorder: FailureOutputRecorder,
+    url_publisher: Option<UrlPublisher>,
+    carriage_return_collapser: Option<CarriageReturnCollapser>,
+    line_splitter: LineSplitter,
+    output_sinks: OutputSinks,
+    process_id: Option<u32>,
+
+    /// Set once the output has ended, so that ready checks stop.
+    exited: Arc<AtomicBool>,
 }
 
 impl ProcessInstance {
     fn start(
+        name: &str,
         process_config: &ProcessConfig,
         pty_pair: PtyPair,
-        on_change: TerminalWaker,
-        status_tx: std::sync::mpsc::Sender<ProcessStatus>,
+        on_change: ChangeNotifier,
+        status_sender: StatusSender,
+        output_sinks: OutputSinks,
+        previous_output: Option<String>,
     ) -> Result<Self, ProcessError> {
-        let pty_command = Self::process_config_to_pty_command(&process_config)?;
+        let (pty_command, sandboxed) = Self::process_config_to_pty_command(&process_config)?;
 
-        let child_process = pty_pair.slave.spawn_command(pty_command).unwrap();
+        let child_process = pty_pair.slave.spawn_command(pty_command)
+            .map_err(|error| ProcessError::SpawnFailed(error.to_string()))?;
         let child_process_killer = child_process.clone_killer();
+        let process_id = child_process.process_id();
         std::mem::drop(pty_pair.slave);
 
         let pty_size = pty_pair.master.get_size().unwrap();
-        let child_process_writer = pty_pair.master.take_writer().unwrap();
+        let pty_writer = SharedWriter(Arc::new(Mutex::new(pty_pair.master.take_writer().unwrap())));
         let terminal = Arc::new(Mutex::new(Self::create_process_terminal(
-            child_process_writer,
+            Box::new(pty_writer.clone()),
             pty_size,
+            process_config.scrollback_lines(),
         )));
+        if let Some(previous_output) = previous_output {
+            let mut actions = Vec::new();
+            Parser::new().parse(previous_output.as_bytes(), |action| actions.push(action));
+            terminal.lock().unwrap().perform_actions(actions);
+        }
+        let run_start_row = {
+            let terminal = terminal.lock().unwrap();
+            terminal.screen().visible_row_to_stable_row(terminal.cursor_pos().y)
+        };
 
         let child_process_reader = pty_pair.master.try_clone_reader().unwrap();
+        let last_failure_output = Arc::new(Mutex::new(None));
+        let published_url = Arc::new(Mutex::new(None));
+        let exited = Arc::new(AtomicBool::new(false));
+        if let Some(ready_check) = &process_config.ready_check {
+            ready_checks::spawn_prober(ready_check.clone(), Arc::clone(&exited), status_sender.clone(), on_change.clone());
+        }
+        let output_watchers = OutputWatchers {
+            name: name.to_owned(),
+            process_status_analyzer: process_config.process_status_analyzer(),
+            failure_output_recorder: FailureOutputRecorder::new(Arc::clone(&last_failure_output)),
+            url_publisher: process_config.publish_regex()
+                .map(|regex| UrlPublisher::new(regex, Arc::clone(&published_url))),
+            carriage_return_collapser: process_config.collapse_carriage_returns.then(CarriageReturnCollapser::new),
+            line_splitter: LineSplitter::new(),
+            output_sinks,
+            process_id,
+            exited: Arc::clone(&exited),
+        };
         Self::spawn_process_reader(
-            process_config.process_status_analyzer(),
+            output_watchers,
             child_process,
             child_process_reader,
             Arc::clone(&terminal),
             on_change,
-            status_tx,
+            status_sender,
         );
 
         Ok(Self {
             terminal,
             pty_master: pty_pair.master,
+            pty_writer,
             child_process_killer,
+            process_id,
+            sandboxed,
+            last_failure_output,
+            published_url,
+            stop_signal: process_config.stop_signal.clone(),
+            stop_timeout: process_config.stop_timeout(),
+            exited,
+            run_start_row,
         })
     }
 
-    fn process_config_to_pty_command(process_config: &ProcessConfig) -> Result<portable_pty::CommandBuilder, ProcessError> {
-        let executable = process_config.command.first()
-            .ok_or(ProcessError::ProcessConfigMissingCommand)?;
-        let mut pty_command = portable_pty::CommandBuilder::new(executable);
-
-        pty_command.args(process_config.command.iter().skip(1));
+    fn process_config_to_pty_command(
+        process_config: &ProcessConfig,
+    ) -> Result<(portable_pty::CommandBuilder, bool), ProcessError> {
+        if process_config.command.is_empty() {
+            return Err(ProcessError::ProcessConfigMissingCommand);
+        }
 
         let current_dir = std::env::current_dir().map_err(ProcessError::GetCurrentDirFailed)?;
         let working_directory = match &process_config.working_directory {
             Some(relative_working_directory) => current_dir.join(relative_working_directory),
             None => current_dir,
         };
+
+        // When the environment is loaded before running the command, the
+        // executable may only be found using the loaded environment, so can't
+        // be checked here.
+        if process_config.environment.is_none() {
+            executables::find_executable(&process_config.command[0], &working_directory)
+                .map_err(ProcessError::ExecutableUnavailable)?;
+        }
+
+        let command = match process_config.environment {
+            None => process_config.command.clone(),
+            Some(environment_config) => shell_environment::wrap_command(
+                environment_config,
+                &process_config.command,
+                &working_directory,
+            ),
+        };
+
+        let (command, sandboxed) = match &process_config.sandbox {
+            None => {
+                let command = command.iter().map(OsString::from).collect();
+                (command, false)
+            },
+            Some(sandbox_config) => {
+                let command = sandbox::sandbox_command(
+                    sandbox_config,
+                    &command,
+                    &working_directory,
+                ).map_err(ProcessError::SandboxUnavailable)?;
+                (command, true)
+            },
+        };
+
+        let mut pty_command = portable_pty::CommandBuilder::from_argv(command);
         pty_command.cwd(working_directory);
+        for (key, value) in &process_config.extra_environment_variables {
+            pty_command.env(key, value);
+        }
 
-        Ok(pty_command)
+        Ok((pty_command, sandboxed))
     }
 
-    fn create_process_terminal(writer: Box<dyn std::io::Write + Send>, size: PtySize) -> wezterm_term::Terminal {
+    fn create_process_terminal(writer: Box<dyn std::io::Write + Send>, size: PtySize, scrollback_lines: usize) -> wezterm_term::Terminal {
         let terminal_size = wezterm_term::TerminalSize {
             rows: size.rows.into(),
             cols: size.cols.into(),
@@ -437,7 +2480,7 @@ impl ProcessInstance {
             pixel_height: size.pixel_height.into(),
             ..Default::default()
         };
-        let terminal_config = Arc::new(ProcessTerminal);
+        let terminal_config = Arc::new(ProcessTerminal { scrollback_lines });
         wezterm_term::Terminal::new(
             terminal_size,
             terminal_config,
@@ -448,13 +2491,25 @@ impl ProcessInstance {
     }
 
     fn spawn_process_reader(
-        process_status_analyzer: ProcessStatusAnalyzer,
+        output_watchers: OutputWatchers,
         mut child_process: Box<dyn portable_pty::Child>,
         mut reader: Box<dyn std::io::Read + Send>,
         terminal: Arc<Mutex<wezterm_term::Terminal>>,
-        on_change: TerminalWaker,
-        status_tx: std::sync::mpsc::Sender<ProcessStatus>,
+        on_change: ChangeNotifier,
+        status_sender: StatusSender,
     ) {
+        let OutputWatchers {
+            name,
+            mut process_status_analyzer,
+            mut failure_output_recorder,
+            url_publisher,
+            mut carriage_return_collapser,
+            mut line_splitter,
+            output_sinks,
+            process_id,
+            exited,
+        } = output_watchers;
+
         std::thread::spawn(move || {
             let mut bytes = vec![0; 256];
             let mut parser = Parser::new();
@@ -469,19 +2524,38 @@ impl ProcessInstance {
                     // TODO: handle failure to get exit code properly
                     let exit_code = child_process.wait().unwrap_or(ExitStatus::with_exit_code(1));
 
-                    let new_status = ProcessStatus::Exited { exit_code: exit_code.exit_code() };
+                    let new_status = ProcessStatus::Exited { exit_code: exit_code.exit_code(), unexpected: false };
+
+                    exited.store(true, Ordering::SeqCst);
+
+                    if let Some(line) = line_splitter.finish() {
+                        output_sinks.write(&name, process_id, &[line]);
+                    }
 
-                    let _ = status_tx.send(new_status);
+                    status_sender.send(new_status);
 
-                    on_change.wake().unwrap();
+                    on_change.wake();
 
                     break;
                 }
 
+                trace::event("output_received", &[("process", &name), ("bytes", &bytes_read)]);
+
+                let parse_span = trace::Span::start();
                 let mut actions = Vec::new();
 
                 parser.parse(&bytes[..bytes_read], |action| actions.push(action));
 
+                if let Some(carriage_return_collapser) = &mut carriage_return_collapser {
+                    let columns = terminal.lock().unwrap().get_size().cols;
+                    actions = carriage_return_collapser.normalize(actions, columns);
+                }
+
+                let lines = line_splitter.split(&actions);
+                if !lines.is_empty() {
+                    output_sinks.write(&name, process_id, &lines);
+                }
+
                 for action in &actions {
                     // TODO: handle other control codes?
                     match action {
@@ -492,20 +2566,43 @@ impl ProcessInstance {
                             termwiz::escape::ControlCode::CarriageReturn
                         ) |
                         termwiz::escape::Action::Esc(Esc::Code(EscCode::FullReset)) => {
-                            if let Some(new_status) = process_status_analyzer.analyze_line(&last_line) {
-                                let _ = status_tx.send(new_status);
+                            let new_status = process_status_analyzer.analyze_line(&last_line);
+                            if let Some(new_status) = new_status {
+                                status_sender.send(new_status);
+                            }
+
+                            let is_line_feed = !matches!(
+                                action,
+                                termwiz::escape::Action::Control(termwiz::escape::ControlCode::CarriageReturn)
+                            );
+                            failure_output_recorder.record_line(&last_line, is_line_feed, new_status);
+
+                            if let Some(url_publisher) = &url_publisher {
+                                url_publisher.analyze_line(&last_line);
                             }
 
                             last_line.clear();
                         },
+                        termwiz::escape::Action::CSI(CSI::Edit(Edit::EraseInDisplay(EraseInDisplay::EraseDisplay))) => {
+                            if let Some(new_status) = process_status_analyzer.analyze_screen_clear() {
+                                status_sender.send(new_status);
+                                failure_output_recorder.record_status(new_status);
+                            }
+                        },
                         _ => {},
                     }
                 }
+                parse_span.end("output_parsed", &[("process", &name)]);
 
+                let lock_span = trace::Span::start();
                 let mut terminal_locked = terminal.lock().unwrap();
+                lock_span.end("terminal_lock_wait", &[("process", &name)]);
+
+                let perform_span = trace::Span::start();
                 terminal_locked.perform_actions(actions);
+                perform_span.end("output_performed", &[("process", &name)]);
 
-                on_change.wake().unwrap();
+                on_change.wake();
             }
         });
     }
@@ -517,7 +2614,35 @@ impl ProcessInstance {
         //
         // We could check the error we get back, but since the kind is
         // `Uncategorized`, we'd need to check the message which feels fragile.
-        let _ = self.child_process_killer.kill();
+        //
+        // The signal is sent to the processes started by the process too, so
+        // that they aren't left running.
+        let stop_signal = self.stop_signal.as_deref().unwrap_or(signals::DEFAULT_STOP_SIGNAL);
+        let signal_sent = match self.process_id {
+            Some(process_id) => signals::send_signal(process_id, stop_signal),
+            None => false,
+        };
+        if !signal_sent {
+            let _ = self.child_process_killer.kill();
+        }
+
+        if let (Some(process_id), Some(stop_timeout)) = (self.process_id, self.stop_timeout) {
+            signals::kill_after_timeout(process_id, stop_timeout, Arc::clone(&self.exited));
+        }
+    }
+
+    fn send_ctrl_d(&mut self) {
+        // Ctrl+D is only interpreted as the end of input when the child has
+        // left the terminal in canonical mode and nothing has been typed on
+        // the current line. Otherwise, the child receives it as input.
+        self.send_input(&[CTRL_D]);
+    }
+
+    fn send_input(&mut self, input: &[u8]) {
+        // Failures to write are (hopefully) because the process has already
+        // stopped, in which case there's nothing to receive the input.
+        let _ = self.pty_writer.write_all(input);
+        let _ = self.pty_writer.flush();
     }
 
     fn resize(&mut self, pty_size: PtySize) {
@@ -533,9 +2658,294 @@ impl ProcessInstance {
         });
     }
 
-    fn lines(&self) -> Vec<wezterm_term::Line> {
+    /// The lines on the screen, scrolled back by the given number of rows.
+    fn lines(&self, scroll_offset: usize) -> Vec<wezterm_term::Line> {
+        let terminal = self.terminal.lock().unwrap();
+        let screen = terminal.screen();
+        if scroll_offset == 0 {
+            return screen.lines_in_phys_range(screen.phys_range(&(0..VisibleRowIndex::MAX)));
+        }
+
+        let end = screen.scrollback_rows() - scroll_offset.min(Self::max_scroll_offset_of(screen));
+        screen.lines_in_phys_range(end.saturating_sub(screen.physical_rows)..end)
+    }
+
+    fn row_count(&self) -> usize {
+        self.terminal.lock().unwrap().screen().scrollback_rows()
+    }
+
+    /// The text of the output, without styles, with a header above the output
+    /// of this run, for keeping the output when the process is restarted.
+    fn output_with_run_header(&self, run_header: &str) -> String {
+        let mut row_texts = self.row_texts();
+        while row_texts.last().is_some_and(|row_text| row_text.trim().is_empty()) {
+            row_texts.pop();
+        }
+
+        // The start of the run may have since been dropped from the
+        // scrollback, in which case the header goes above what's left.
+        let run_start_row = self.terminal.lock().unwrap().screen()
+            .stable_row_to_phys(self.run_start_row)
+            .unwrap_or(0)
+            .min(row_texts.len());
+        // The header is dimmed so that it stands out from the output.
+        row_texts.insert(run_start_row, format!("\x1b[2m{RUN_HEADER_PREFIX}{run_header} ────\x1b[0m"));
+
+        let mut output = String::new();
+        for row_text in row_texts {
+            output.push_str(row_text.trim_end());
+            output.push_str("\r\n");
+        }
+        output
+    }
+
+    fn all_lines(&self) -> Vec<wezterm_term::Line> {
         let terminal = self.terminal.lock().unwrap();
-        terminal.screen().lines_in_phys_range(terminal.screen().phys_range(&(0..VisibleRowIndex::MAX)))
+        let screen = terminal.screen();
+        screen.lines_in_phys_range(0..screen.scrollback_rows())
+    }
+
+    /// The text of every row, including the scrollback.
+    fn row_texts(&self) -> Vec<String> {
+        self.all_lines()
+            .iter()
+            .map(|line| line.as_str().into_owned())
+            .collect()
+    }
+
+    fn max_scroll_offset(&self) -> usize {
+        Self::max_scroll_offset_of(self.terminal.lock().unwrap().screen())
+    }
+
+    fn max_scroll_offset_of(screen: &wezterm_term::Screen) -> usize {
+        screen.scrollback_rows().saturating_sub(screen.physical_rows)
+    }
+}
+
+/// An entry in the process list.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) enum ProcessListEntry {
+    /// The output of every process together.
+    CombinedLog,
+
+    /// The heading of a group of processes.
+    Group(String),
+
+    Process(usize),
+}
+
+/// A second process shown alongside the focused process.
+pub(crate) struct Split {
+    pub(crate) process_index: usize,
+
+    pub(crate) direction: SplitDirection,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum SplitDirection {
+    SideBySide,
+    Stacked,
+}
+
+/// The processes being stopped before quitting.
+pub(crate) struct Shutdown {
+    /// The indexes of the processes to stop in each of the remaining phases.
+    phases: VecDeque<Vec<usize>>,
+
+    /// The names of the processes stopped in the current phase, along with
+    /// whether each has exited.
+    stopping: Vec<(String, Arc<AtomicBool>)>,
+
+    phase_started_at: Instant,
+
+    /// How long to wait for the processes in the current phase to exit.
+    phase_timeout: Duration,
+}
+
+impl Shutdown {
+    /// The names of the processes that have been stopped but haven't yet
+    /// exited.
+    pub(crate) fn stopping_process_names(&self) -> Vec<&str> {
+        self.stopping.iter()
+            .filter(|(_, exited)| !exited.load(Ordering::SeqCst))
+            .map(|(process_name, _)| process_name.as_str())
+            .collect()
+    }
+
+    fn is_phase_finished(&self) -> bool {
+        self.phase_started_at.elapsed() >= self.phase_timeout
+            || self.stopping.iter().all(|(_, exited)| exited.load(Ordering::SeqCst))
+    }
+}
+
+/// The start of the header above the output of each earlier run, which is
+/// kept when `clear_on_restart` is `false`.
+const RUN_HEADER_PREFIX: &str = "──── run #";
+
+/// How long to wait for a process without a stop timeout to exit when
+/// stopping before moving on to the next phase anyway.
+const SHUTDOWN_PHASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait after the stop timeout of a process for it to be killed
+/// and exit.
+const SHUTDOWN_KILL_MARGIN: Duration = Duration::from_secs(1);
+
+/// More restarts of a process than this within the window caused by watched
+/// files are treated as a restart storm.
+const RESTART_STORM_LIMIT: usize = 10;
+
+const RESTART_STORM_WINDOW: Duration = Duration::from_secs(10);
+
+/// Exiting within this long of starting counts towards `max_restarts`.
+const RAPID_EXIT_DURATION: Duration = Duration::from_secs(10);
+
+const INITIAL_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+/// The delay doubles with each exit soon after starting.
+fn restart_delay(rapid_exit_count: u32) -> Duration {
+    let doublings = rapid_exit_count.saturating_sub(1).min(16);
+    (INITIAL_RESTART_DELAY * 2u32.pow(doublings)).min(MAX_RESTART_DELAY)
+}
+
+/// Start a command, such as opening a browser, without waiting for it to
+/// finish or showing its output.
+fn spawn_hook(command: &[String], environment_variables: &[(&str, String)]) -> std::io::Result<()> {
+    let Some((executable, arguments)) = command.split_first() else {
+        return Ok(());
+    };
+
+    let mut child = std::process::Command::new(executable)
+        .args(arguments)
+        .envs(environment_variables.iter().map(|(name, value)| (*name, value)))
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+
+    // Wait for the command in the background so that it doesn't linger as a
+    // zombie process.
+    std::thread::spawn(move || child.wait());
+
+    Ok(())
+}
+
+/// Split a word such as `PORT=3001` into the name and value of an environment
+/// variable, or return `None` if the word isn't an assignment.
+fn parse_environment_variable(word: &str) -> Option<(String, String)> {
+    let (name, value) = word.split_once('=')?;
+    let is_valid_name = name.chars().next().is_some_and(|char| char.is_ascii_alphabetic() || char == '_')
+        && name.chars().all(|char| char.is_ascii_alphanumeric() || char == '_');
+    is_valid_name.then(|| (name.to_owned(), value.to_owned()))
+}
+
+/// Ctrl+D, the default end-of-file character for terminals.
+const CTRL_D: u8 = 0x04;
+
+/// Where lines of output are copied to, in addition to the terminal of the
+/// process.
+#[derive(Clone)]
+struct OutputSinks {
+    /// How to print the output to stdout, as when running without the UI.
+    echo: Option<EchoFormat>,
+
+    combined_log: CombinedLog,
+
+    secret_scanner: Option<SecretScanner>,
+}
+
+impl OutputSinks {
+    fn write(&self, process_name: &str, process_id: Option<u32>, lines: &[OutputLine]) {
+        if let Some(echo_format) = &self.echo {
+            output_lines::echo_lines(echo_format, process_name, process_id, lines);
+        }
+        self.combined_log.push(process_name, lines);
+        if let Some(secret_scanner) = &self.secret_scanner {
+            secret_scanner.scan(process_name, lines);
+        }
+    }
+}
+
+/// Wakes the main loop when something changes, such as a process writing
+/// output or changing status.
+#[derive(Clone)]
+pub(crate) enum ChangeNotifier {
+    /// Wakes the main loop of the UI from waiting for input.
+    Terminal(TerminalWaker),
+
+    /// Wakes the main loop when running without the UI.
+    Channel(std::sync::mpsc::Sender<()>),
+}
+
+impl ChangeNotifier {
+    pub(crate) fn wake(&self) {
+        // Failing to wake means that the main loop has already exited.
+        match self {
+            ChangeNotifier::Terminal(terminal_waker) => {
+                let _ = terminal_waker.wake();
+            },
+            ChangeNotifier::Channel(change_tx) => {
+                let _ = change_tx.send(());
+            },
+        }
+    }
+}
+
+/// Sends the status of a running process to the main loop. A running status
+/// that follows another running status is skipped, such as the status for each
+/// unremarkable line of output. Other statuses are always sent, even when
+/// repeated: watchers such as Vite and webpack report each rebuild as another
+/// success without reporting that they're running in between, and each
+/// rebuild must still restart downstream processes and reload browsers.
+#[derive(Clone)]
+pub(crate) struct StatusSender {
+    status_tx: std::sync::mpsc::Sender<ProcessStatus>,
+
+    /// Shared between the output reader and the ready check, since both send
+    /// statuses for the same process.
+    last_sent_status: Arc<Mutex<ProcessStatus>>,
+}
+
+impl StatusSender {
+    fn new(status_tx: std::sync::mpsc::Sender<ProcessStatus>) -> Self {
+        Self {
+            status_tx,
+            // Processes are running until a status is received.
+            last_sent_status: Arc::new(Mutex::new(ProcessStatus::Running)),
+        }
+    }
+
+    /// Send the status unless it's a repeated running status, returning
+    /// whether it was sent.
+    pub(crate) fn send(&self, status: ProcessStatus) -> bool {
+        let mut last_sent_status = self.last_sent_status.lock().unwrap();
+        if status == ProcessStatus::Running && *last_sent_status == ProcessStatus::Running {
+            return false;
+        }
+
+        // Failing to send means that the process has been stopped, so nothing
+        // needs the status.
+        if self.status_tx.send(status).is_err() {
+            return false;
+        }
+        *last_sent_status = status;
+        true
+    }
+}
+
+/// A writer to the PTY that is shared between the process terminal, which
+/// writes responses to escape sequences, and Mintaka itself.
+#[derive(Clone)]
+struct SharedWriter(Arc<Mutex<Box<dyn std::io::Write + Send>>>);
+
+impl std::io::Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.lock().unwrap().flush()
     }
 }
 
@@ -544,15 +2954,148 @@ impl ProcessInstance {
 pub(crate) enum ProcessError {
     ProcessConfigMissingCommand,
 
+    SpawnFailed(String),
+
+    SpawnThreadFailed,
+
     GetCurrentDirFailed(std::io::Error),
+
+    ExecutableUnavailable(ExecutableError),
+
+    SandboxUnavailable(SandboxError),
+}
+
+impl std::fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessError::ProcessConfigMissingCommand => write!(f, "command is empty"),
+            ProcessError::SpawnFailed(error) => write!(f, "failed to spawn process: {error}"),
+            ProcessError::SpawnThreadFailed => write!(f, "failed to spawn process: worker thread stopped"),
+            ProcessError::GetCurrentDirFailed(error) => write!(f, "failed to get current directory: {error}"),
+            ProcessError::ExecutableUnavailable(error) => write!(f, "{error}"),
+            ProcessError::SandboxUnavailable(error) => write!(f, "{error}"),
+        }
+    }
 }
 
 
 #[derive(Debug)]
-struct ProcessTerminal;
+struct ProcessTerminal {
+    scrollback_lines: usize,
+}
 
 impl wezterm_term::TerminalConfiguration for ProcessTerminal {
+    fn scrollback_size(&self) -> usize {
+        self.scrollback_lines
+    }
+
     fn color_palette(&self) -> wezterm_term::color::ColorPalette {
         wezterm_term::color::ColorPalette::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::config::MintakaConfig;
+
+    use super::*;
+
+    /// Processes that aren't started automatically, so that no commands are
+    /// run.
+    fn processes(config_str: &str) -> Processes {
+        let config: MintakaConfig = toml::from_str(config_str).unwrap();
+        let (change_tx, _) = std::sync::mpsc::channel();
+        let mut processes = Processes::new(ChangeNotifier::Channel(change_tx));
+        for process_config in config.processes {
+            processes.start_process(process_config).unwrap();
+        }
+        processes
+    }
+
+    #[test]
+    fn shutdown_phases_stop_downstream_processes_first() {
+        let processes = processes(r#"
+            [[processes]]
+            name = "db"
+            command = ["true"]
+            autostart = false
+
+            [[processes]]
+            name = "migrate"
+            command = ["true"]
+            autostart = false
+            after = "db"
+
+            [[processes]]
+            name = "server"
+            command = ["true"]
+            autostart = false
+            after = "migrate"
+
+            [[processes]]
+            name = "worker"
+            command = ["true"]
+            autostart = false
+            after = "db"
+        "#);
+
+        assert_eq!(processes.shutdown_phases(), VecDeque::from([vec![2, 3], vec![1], vec![0]]));
+    }
+
+    #[test]
+    fn shutdown_phases_stop_processes_in_cycle_together() {
+        let processes = processes(r#"
+            [[processes]]
+            name = "a"
+            command = ["true"]
+            autostart = false
+            after = "b"
+
+            [[processes]]
+            name = "b"
+            command = ["true"]
+            autostart = false
+            after = "a"
+
+            [[processes]]
+            name = "c"
+            command = ["true"]
+            autostart = false
+            after = "a"
+        "#);
+
+        assert_eq!(processes.shutdown_phases(), VecDeque::from([vec![2], vec![0, 1]]));
+    }
+
+    #[test]
+    fn restart_delay_starts_at_initial_delay() {
+        assert_eq!(restart_delay(0), INITIAL_RESTART_DELAY);
+        assert_eq!(restart_delay(1), INITIAL_RESTART_DELAY);
+    }
+
+    #[test]
+    fn restart_delay_doubles_with_each_rapid_exit() {
+        assert_eq!(restart_delay(2), Duration::from_secs(2));
+        assert_eq!(restart_delay(3), Duration::from_secs(4));
+        assert_eq!(restart_delay(6), Duration::from_secs(32));
+    }
+
+    #[test]
+    fn restart_delay_is_capped() {
+        assert_eq!(restart_delay(7), MAX_RESTART_DELAY);
+        assert_eq!(restart_delay(u32::MAX), MAX_RESTART_DELAY);
+    }
+
+    #[test]
+    fn parse_environment_variable_splits_assignments() {
+        assert_eq!(parse_environment_variable("PORT=3001"), Some(("PORT".to_owned(), "3001".to_owned())));
+        assert_eq!(parse_environment_variable("_FLAGS=a=b"), Some(("_FLAGS".to_owned(), "a=b".to_owned())));
+    }
+
+    #[test]
+    fn parse_environment_variable_rejects_other_words() {
+        assert_eq!(parse_environment_variable("--port"), None);
+        assert_eq!(parse_environment_variable("1PORT=3001"), None);
+        assert_eq!(parse_environment_variable("MY-PORT=3001"), None);
+    }
+}
@@ -0,0 +1,156 @@
+use std::{io::{Read, Write}, net::{TcpStream, ToSocketAddrs}, sync::{atomic::{AtomicBool, Ordering}, Arc}, time::Duration};
+
+use crate::{config::ReadyCheckConfig, processes::{ChangeNotifier, ProcessStatus, StatusSender}};
+
+const PROBE_INTERVAL: Duration = Duration::from_millis(500);
+
+pub(crate) const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Repeatedly check whether a process is ready on a background thread, marking
+/// the process as "Ready" once the check passes. Probing stops once the
+/// process is ready or has exited.
+pub(crate) fn spawn_prober(
+    ready_check: ReadyCheckConfig,
+    exited: Arc<AtomicBool>,
+    status_sender: StatusSender,
+    on_change: ChangeNotifier,
+) {
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(PROBE_INTERVAL);
+
+            if exited.load(Ordering::SeqCst) {
+                return;
+            }
+
+            if is_ready(&ready_check) {
+                if status_sender.send(ProcessStatus::Ready) {
+                    on_change.wake();
+                }
+                return;
+            }
+        }
+    });
+}
+
+fn is_ready(ready_check: &ReadyCheckConfig) -> bool {
+    match ready_check {
+        ReadyCheckConfig::Tcp(address) => connect(address).is_some(),
+        ReadyCheckConfig::Http(url) => is_http_ready(url),
+    }
+}
+
+pub(crate) fn connect(address: &str) -> Option<TcpStream> {
+    address.to_socket_addrs().ok()?
+        .find_map(|socket_address| TcpStream::connect_timeout(&socket_address, PROBE_TIMEOUT).ok())
+}
+
+/// Whether a GET request to the URL receives a successful (2xx) or redirect
+/// (3xx) response.
+fn is_http_ready(url: &str) -> bool {
+    let Ok(HttpUrl { address, host, path }) = parse_http_url(url) else {
+        return false;
+    };
+    let Some(mut stream) = connect(&address) else {
+        return false;
+    };
+    let _ = stream.set_read_timeout(Some(PROBE_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(PROBE_TIMEOUT));
+
+    let request = format!("GET {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n");
+    if stream.write_all(request.as_bytes()).is_err() {
+        return false;
+    }
+
+    // Only the status line is needed.
+    let mut response = [0; 12];
+    if stream.read_exact(&mut response).is_err() {
+        return false;
+    }
+    let response = String::from_utf8_lossy(&response);
+
+    response.starts_with("HTTP/") && matches!(response.get(9..10), Some("2" | "3"))
+}
+
+pub(crate) struct HttpUrl {
+    pub(crate) address: String,
+    pub(crate) host: String,
+    pub(crate) path: String,
+}
+
+/// Parse a plain HTTP URL. HTTPS isn't supported.
+pub(crate) fn parse_http_url(url: &str) -> Result<HttpUrl, String> {
+    let Some(rest) = url.strip_prefix("http://") else {
+        return Err(format!("URL {url} must start with http://"));
+    };
+
+    let (host, path) = match rest.find('/') {
+        Some(path_index) => (&rest[..path_index], &rest[path_index..]),
+        None => (rest, "/"),
+    };
+    if host.is_empty() {
+        return Err(format!("URL {url} has no host"));
+    }
+
+    let address = if host.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) {
+        host.to_owned()
+    } else {
+        format!("{host}:80")
+    };
+
+    Ok(HttpUrl {
+        address,
+        host: host.to_owned(),
+        path: path.to_owned(),
+    })
+}
+
+/// Describe the problem with a ready check, if any.
+pub(crate) fn ready_check_problem(ready_check: &ReadyCheckConfig) -> Option<String> {
+    match ready_check {
+        ReadyCheckConfig::Tcp(address) => {
+            if address.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) {
+                None
+            } else {
+                Some(format!("ready check address {address} must include a port"))
+            }
+        },
+        ReadyCheckConfig::Http(url) => parse_http_url(url)
+            .err()
+            .map(|error| format!("ready check {error}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_http_url_reads_host_port_and_path() {
+        let url = parse_http_url("http://localhost:3000/health?full=1").unwrap();
+
+        assert_eq!(url.address, "localhost:3000");
+        assert_eq!(url.host, "localhost:3000");
+        assert_eq!(url.path, "/health?full=1");
+    }
+
+    #[test]
+    fn parse_http_url_defaults_to_port_80_and_root_path() {
+        let url = parse_http_url("http://example.test").unwrap();
+
+        assert_eq!(url.address, "example.test:80");
+        assert_eq!(url.host, "example.test");
+        assert_eq!(url.path, "/");
+    }
+
+    #[test]
+    fn parse_http_url_rejects_other_schemes() {
+        assert_eq!(parse_http_url("https://localhost:3000/").err(), Some("URL https://localhost:3000/ must start with http://".to_owned()));
+        assert_eq!(parse_http_url("localhost:3000").err(), Some("URL localhost:3000 must start with http://".to_owned()));
+    }
+
+    #[test]
+    fn parse_http_url_rejects_missing_host() {
+        assert_eq!(parse_http_url("http:///health").err(), Some("URL http:///health has no host".to_owned()));
+    }
+}
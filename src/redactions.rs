@@ -0,0 +1,160 @@
+use regex::Regex;
+use termwiz::cell::Cell;
+
+/// Regexes for values that are hidden in privacy mode even without any
+/// redactions in the config: JSON web tokens, email addresses and IPv4
+/// addresses.
+const DEFAULT_REDACTIONS: [&str; 3] = [
+    r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*",
+    r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+    r"\b(\d{1,3}\.){3}\d{1,3}\b",
+];
+
+/// Masks the parts of lines of process output that match regexes, so that
+/// sensitive values aren't shown when sharing the screen. Only the rendered
+/// lines are changed: the output of the process is kept as it is.
+pub(crate) struct Redactor {
+    regexes: Vec<Regex>,
+}
+
+impl Redactor {
+    /// Build the redactor from the default redactions and the redactions in
+    /// the config, or describe the problems with the redactions in the config.
+    pub(crate) fn new(redactions: &[String]) -> Result<Self, Vec<String>> {
+        let mut problems = Vec::new();
+        let mut regexes: Vec<Regex> = DEFAULT_REDACTIONS.iter()
+            .map(|regex_str| Regex::new(regex_str).unwrap())
+            .collect();
+
+        for regex_str in redactions {
+            match Regex::new(regex_str) {
+                Ok(regex) => regexes.push(regex),
+                Err(error) => problems.push(format!("redaction regex {regex_str:?} is invalid: {error}")),
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(Self { regexes })
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Replace each character of the text that matches a redaction with an
+    /// asterisk.
+    pub(crate) fn redact(&self, text: &str) -> String {
+        let matched_ranges: Vec<(usize, usize)> = self.regexes.iter()
+            .flat_map(|regex| regex.find_iter(text))
+            .map(|matched| (matched.start(), matched.end()))
+            .collect();
+
+        text.char_indices()
+            .map(|(byte_offset, char)| {
+                let is_matched = matched_ranges.iter()
+                    .any(|(start, end)| byte_offset >= *start && byte_offset < *end);
+                if is_matched { '*' } else { char }
+            })
+            .collect()
+    }
+
+    /// Replace each character that matches a redaction with an asterisk,
+    /// keeping the original style.
+    pub(crate) fn apply(&self, lines: &mut [wezterm_term::Line]) {
+        for line in lines {
+            let mut text = String::new();
+            let mut cell_byte_offsets = Vec::new();
+            for cell in line.visible_cells() {
+                cell_byte_offsets.push((text.len(), cell.cell_index(), cell.width()));
+                text.push_str(cell.str());
+            }
+
+            let matched_cells: Vec<(usize, usize)> = self.regexes.iter()
+                .flat_map(|regex| regex.find_iter(&text))
+                .flat_map(|matched| {
+                    cell_byte_offsets.iter()
+                        .filter(move |(byte_offset, _, _)| *byte_offset >= matched.start() && *byte_offset < matched.end())
+                        .map(|(_, cell_index, width)| (*cell_index, *width))
+                })
+                .collect();
+
+            let seqno = line.current_seqno();
+            for (cell_index, width) in matched_cells {
+                let Some(mut attributes) = line.get_cell(cell_index).map(|cell| cell.attrs().clone()) else {
+                    continue;
+                };
+                // Hyperlinks are removed too, since the target may include
+                // the redacted value.
+                attributes.set_hyperlink(None);
+                // Wide characters are replaced with one asterisk per column
+                // so that the rest of the line stays in place.
+                for column_index in cell_index..cell_index + width {
+                    line.set_cell(column_index, Cell::new('*', attributes.clone()), seqno);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use termwiz::{cell::{CellAttributes, Hyperlink}, color::AnsiColor, surface::SEQ_ZERO};
+
+    use super::*;
+
+    fn line(text: &str, attributes: &CellAttributes) -> wezterm_term::Line {
+        wezterm_term::Line::from_text(text, attributes, SEQ_ZERO, None)
+    }
+
+    #[test]
+    fn invalid_redactions_are_reported() {
+        let problems = Redactor::new(&["(".to_owned()]).err().unwrap();
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("redaction regex \"(\" is invalid"));
+    }
+
+    #[test]
+    fn redact_masks_default_redactions() {
+        let redactor = Redactor::new(&[]).ok().unwrap();
+
+        assert_eq!(redactor.redact("from bob@example.com at 10.0.0.1"), "from *************** at ********");
+    }
+
+    #[test]
+    fn apply_masks_matching_cells_and_keeps_style() {
+        let redactor = Redactor::new(&["secret-[0-9]+".to_owned()]).ok().unwrap();
+        let mut attributes = CellAttributes::default();
+        attributes.set_foreground(AnsiColor::Red);
+        let mut lines = [line("key=secret-42;", &attributes)];
+
+        redactor.apply(&mut lines);
+
+        assert_eq!(lines[0].as_str(), "key=*********;");
+        assert_eq!(lines[0].get_cell(4).unwrap().attrs().foreground(), attributes.foreground());
+    }
+
+    #[test]
+    fn apply_removes_hyperlinks_from_masked_cells() {
+        let redactor = Redactor::new(&[]).ok().unwrap();
+        let mut attributes = CellAttributes::default();
+        attributes.set_hyperlink(Some(Arc::new(Hyperlink::new("mailto:bob@example.com"))));
+        let mut lines = [line("bob@example.com", &attributes)];
+
+        redactor.apply(&mut lines);
+
+        assert_eq!(lines[0].as_str(), "***************");
+        assert!(lines[0].get_cell(0).unwrap().attrs().hyperlink().is_none());
+    }
+
+    #[test]
+    fn apply_masks_each_column_of_wide_characters() {
+        let redactor = Redactor::new(&["秘密".to_owned()]).ok().unwrap();
+        let mut lines = [line("a秘密b", &CellAttributes::default())];
+
+        redactor.apply(&mut lines);
+
+        assert_eq!(lines[0].as_str(), "a****b");
+    }
+}
@@ -0,0 +1,200 @@
+use std::{collections::HashSet, sync::{Arc, Mutex}};
+
+use crate::{config::ProcessConfig, config_validation::validate_process_config, processes::{Process, ProcessStatus, Processes}};
+
+/// A command sent to a running instance of Mintaka from outside of the UI.
+pub(crate) enum Command {
+    /// Describe the status of every process.
+    Status,
+
+    /// Restart the process with the given name.
+    Restart {
+        name: String,
+    },
+
+    /// Stop the process with the given name.
+    Stop {
+        name: String,
+    },
+
+    /// Get the current screen of the process with the given name as text.
+    Tail {
+        name: String,
+    },
+
+    /// Add and start a new process.
+    Add {
+        process_config: ProcessConfig,
+    },
+}
+
+pub(crate) enum CommandError {
+    UnknownProcess(String),
+
+    InvalidProcessConfig(Vec<String>),
+
+    StartFailed(String),
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::UnknownProcess(name) => write!(f, "unknown process: {name}"),
+            CommandError::InvalidProcessConfig(problems) => write!(f, "invalid process config: {}", problems.join("; ")),
+            CommandError::StartFailed(error) => write!(f, "failed to start process: {error}"),
+        }
+    }
+}
+
+pub(crate) enum CommandOutput {
+    Json(String),
+
+    Text(String),
+}
+
+pub(crate) fn dispatch(processes: &Arc<Mutex<Processes>>, command: Command) -> Result<CommandOutput, CommandError> {
+    let mut processes = processes.lock().unwrap();
+
+    let output = match command {
+        Command::Status => CommandOutput::Json(status_json(&processes)),
+        Command::Restart { name } => {
+            let process_index = processes.process_index(&name)
+                .ok_or(CommandError::UnknownProcess(name))?;
+            processes.restart(process_index);
+            CommandOutput::Text("OK\n".to_owned())
+        },
+        Command::Stop { name } => {
+            let process_index = processes.process_index(&name)
+                .ok_or(CommandError::UnknownProcess(name))?;
+            processes.stop(process_index);
+            CommandOutput::Text("OK\n".to_owned())
+        },
+        Command::Tail { name } => {
+            let process_index = processes.process_index(&name)
+                .ok_or(CommandError::UnknownProcess(name))?;
+            CommandOutput::Text(screen_text(&processes.processes()[process_index]))
+        },
+        Command::Add { process_config } => {
+            let process_names: HashSet<String> = processes.processes()
+                .iter()
+                .map(|process| process.name().to_owned())
+                .collect();
+            let problems = validate_process_config(&process_config, &process_names);
+            if !problems.is_empty() {
+                return Err(CommandError::InvalidProcessConfig(problems));
+            }
+            processes.start_process(process_config)
+                .map_err(|error| CommandError::StartFailed(error.to_string()))?;
+            CommandOutput::Text("OK\n".to_owned())
+        },
+    };
+
+    processes.wake();
+
+    Ok(output)
+}
+
+fn status_json(processes: &Processes) -> String {
+    let process_jsons: Vec<String> = processes.processes()
+        .iter()
+        .map(|process| {
+            let mut fields = vec![
+                format!("\"name\":{}", json_string(process.name())),
+                format!("\"restart_count\":{}", process.restart_count()),
+            ];
+            if let Some(uptime) = process.uptime() {
+                fields.push(format!("\"uptime_seconds\":{}", uptime.as_secs()));
+            }
+            fields.extend(status_fields(process));
+            format!("{{{}}}", fields.join(","))
+        })
+        .collect();
+
+    format!("{{\"processes\":[{}]}}\n", process_jsons.join(","))
+}
+
+/// The JSON fields describing the status of a process.
+pub(crate) fn status_fields(process: &Process) -> Vec<String> {
+    let status = process.status();
+    let mut fields = vec![format!("\"status\":{}", json_string(status.name()))];
+    match status {
+        ProcessStatus::NotStarted
+        | ProcessStatus::WaitingForUpstream
+        | ProcessStatus::Running
+        | ProcessStatus::Success
+        | ProcessStatus::Ready => {},
+        ProcessStatus::Errors { error_count } => {
+            if let Some(error_count) = error_count {
+                fields.push(format!("\"error_count\":{error_count}"));
+            }
+        },
+        ProcessStatus::Exited { exit_code, unexpected } => {
+            fields.push(format!("\"exit_code\":{exit_code}"));
+            fields.push(format!("\"unexpected\":{unexpected}"));
+        },
+        ProcessStatus::StartFailed => {
+            if let Some(start_error) = process.start_error() {
+                fields.push(format!("\"error\":{}", json_string(start_error)));
+            }
+        },
+    }
+    fields
+}
+
+fn screen_text(process: &Process) -> String {
+    let mut lines: Vec<String> = process.lines()
+        .iter()
+        .map(|line| line.as_str().trim_end().to_owned())
+        .collect();
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            char if char.is_control() => json.push_str(&format!("\\u{:04x}", char as u32)),
+            char => json.push(char),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_string_quotes_plain_text() {
+        assert_eq!(json_string("web server"), "\"web server\"");
+    }
+
+    #[test]
+    fn json_string_escapes_quotes_and_backslashes() {
+        assert_eq!(json_string(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+    }
+
+    #[test]
+    fn json_string_escapes_control_characters() {
+        assert_eq!(json_string("a\nb\r\tc\u{1b}"), r#""a\nb\r\tc\u001b""#);
+    }
+
+    #[test]
+    fn json_string_keeps_non_ascii_characters() {
+        assert_eq!(json_string("café"), "\"café\"");
+    }
+}
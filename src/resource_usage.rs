@@ -0,0 +1,144 @@
+use std::{collections::{HashMap, HashSet}, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use crate::processes::{ChangeNotifier, Process, Processes};
+
+/// How much memory and CPU a process is using, including any processes that
+/// it started.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct ResourceUsage {
+    pub(crate) memory_bytes: u64,
+    pub(crate) cpu_percent: f64,
+}
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The CPU time used by each process, by process ID, when the processes were
+/// last sampled. CPU usage is the CPU time used between samples, since the
+/// CPU usage reported by `ps` is averaged over the lifetime of each process.
+struct CpuTimes {
+    sampled_at: Instant,
+    cpu_times: HashMap<u32, Duration>,
+}
+
+/// Sample the resource usage of the running processes on a background thread,
+/// since sampling may be slow when there are many processes on the machine.
+pub(crate) fn spawn(processes: Arc<Mutex<Processes>>, on_change: ChangeNotifier) {
+    std::thread::spawn(move || {
+        let mut last_cpu_times = None;
+        loop {
+            std::thread::sleep(SAMPLE_INTERVAL);
+
+            let process_ids: HashSet<u32> = processes.lock().unwrap()
+                .processes()
+                .iter()
+                .filter_map(Process::process_id)
+                .collect();
+            let Some(resource_usages) = sample(&process_ids, &mut last_cpu_times) else {
+                continue;
+            };
+            processes.lock().unwrap().set_resource_usages(resource_usages);
+            on_change.wake();
+        }
+    });
+}
+
+/// The resource usage of each of the given processes, by process ID.
+/// Processes are started in their own session, so their process ID is also
+/// their process group ID, and summing over the group includes grandchildren
+/// such as the server started by `npm run`. CPU usage is only known from the
+/// second sample onwards.
+#[cfg(unix)]
+fn sample(process_ids: &HashSet<u32>, last_cpu_times: &mut Option<CpuTimes>) -> Option<HashMap<u32, ResourceUsage>> {
+    if process_ids.is_empty() {
+        return Some(HashMap::new());
+    }
+
+    let output = std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=,pgid=,rss=,time="])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let sampled_at = Instant::now();
+    let mut cpu_times = HashMap::new();
+    let mut resource_usages: HashMap<u32, ResourceUsage> = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(process_id), Some(process_group_id), Some(resident_kibibytes), Some(cpu_time)) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        let (Ok(process_id), Ok(process_group_id), Ok(resident_kibibytes), Some(cpu_time)) = (
+            process_id.parse::<u32>(),
+            process_group_id.parse::<u32>(),
+            resident_kibibytes.parse::<u64>(),
+            parse_cpu_time(cpu_time),
+        ) else {
+            continue;
+        };
+        if !process_ids.contains(&process_group_id) {
+            continue;
+        }
+        cpu_times.insert(process_id, cpu_time);
+
+        let resource_usage = resource_usages.entry(process_group_id).or_default();
+        resource_usage.memory_bytes += resident_kibibytes * 1024;
+        if let Some(last_cpu_times) = last_cpu_times {
+            // A process that wasn't running at the last sample has used all of
+            // its CPU time since then.
+            let last_cpu_time = last_cpu_times.cpu_times.get(&process_id).copied().unwrap_or(Duration::ZERO);
+            let elapsed = sampled_at - last_cpu_times.sampled_at;
+            resource_usage.cpu_percent += cpu_time.saturating_sub(last_cpu_time).as_secs_f64() / elapsed.as_secs_f64() * 100.0;
+        }
+    }
+
+    *last_cpu_times = Some(CpuTimes { sampled_at, cpu_times });
+    Some(resource_usages)
+}
+
+#[cfg(not(unix))]
+fn sample(_process_ids: &HashSet<u32>, _last_cpu_times: &mut Option<CpuTimes>) -> Option<HashMap<u32, ResourceUsage>> {
+    None
+}
+
+/// Parse the CPU time of a process as printed by `ps`, such as `01:02:03` or
+/// `2-01:02:03` on Linux, and `1:02.50` on macOS.
+#[cfg(unix)]
+fn parse_cpu_time(cpu_time: &str) -> Option<Duration> {
+    let (days, time) = match cpu_time.split_once('-') {
+        Some((days, time)) => (days.parse::<u64>().ok()?, time),
+        None => (0, cpu_time),
+    };
+    let seconds = time.split(':')
+        .try_fold(0.0, |seconds: f64, part| part.parse::<f64>().ok().map(|part| seconds * 60.0 + part))?;
+    Duration::try_from_secs_f64(days as f64 * 24.0 * 60.0 * 60.0 + seconds).ok()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cpu_time_reads_hours_minutes_and_seconds() {
+        assert_eq!(parse_cpu_time("00:00:07"), Some(Duration::from_secs(7)));
+        assert_eq!(parse_cpu_time("01:02:03"), Some(Duration::from_secs(3723)));
+    }
+
+    #[test]
+    fn parse_cpu_time_reads_days() {
+        assert_eq!(parse_cpu_time("2-00:00:01"), Some(Duration::from_secs(2 * 24 * 60 * 60 + 1)));
+    }
+
+    #[test]
+    fn parse_cpu_time_reads_fractional_seconds() {
+        assert_eq!(parse_cpu_time("1:02.50"), Some(Duration::from_millis(62_500)));
+    }
+
+    #[test]
+    fn parse_cpu_time_rejects_other_text() {
+        assert_eq!(parse_cpu_time("TIME"), None);
+        assert_eq!(parse_cpu_time("-1"), None);
+    }
+}
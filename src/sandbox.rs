@@ -0,0 +1,80 @@
+use std::{ffi::OsString, path::{Path, PathBuf}};
+
+use crate::{config::SandboxConfig, executables::executable_exists};
+
+/// Why a process can't be run inside its sandbox. Such a process isn't run at
+/// all, rather than being run without the restrictions it asked for.
+#[derive(Debug)]
+pub(crate) enum SandboxError {
+    Unsupported,
+
+    BubblewrapMissing,
+
+    ReadOnlyPathMissing(PathBuf),
+}
+
+impl std::fmt::Display for SandboxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SandboxError::Unsupported => write!(f, "sandbox is only supported on Linux"),
+            SandboxError::BubblewrapMissing => write!(f, "sandbox requires bubblewrap (bwrap), which could not be found"),
+            SandboxError::ReadOnlyPathMissing(path) => write!(f, "sandbox read-only path {} does not exist", path.display()),
+        }
+    }
+}
+
+/// Check that a process can be run inside its sandbox.
+///
+/// Sandboxing is implemented using bubblewrap (`bwrap`), so is only supported
+/// on Linux when bubblewrap is installed. Bubblewrap also fails to start the
+/// process when a path to bind read-only doesn't exist.
+pub(crate) fn check_sandbox(sandbox_config: &SandboxConfig, working_directory: &Path) -> Result<(), SandboxError> {
+    if !cfg!(target_os = "linux") {
+        return Err(SandboxError::Unsupported);
+    }
+    if !executable_exists("bwrap", working_directory) {
+        return Err(SandboxError::BubblewrapMissing);
+    }
+    for read_only_path in &sandbox_config.read_only_paths {
+        let read_only_path = working_directory.join(read_only_path);
+        if !read_only_path.exists() {
+            return Err(SandboxError::ReadOnlyPathMissing(read_only_path));
+        }
+    }
+    Ok(())
+}
+
+/// Wrap a command so that it runs inside a sandbox.
+pub(crate) fn sandbox_command(
+    sandbox_config: &SandboxConfig,
+    command: &[String],
+    working_directory: &Path,
+) -> Result<Vec<OsString>, SandboxError> {
+    check_sandbox(sandbox_config, working_directory)?;
+
+    let mut sandboxed_command: Vec<OsString> = vec![
+        "bwrap".into(),
+        "--dev-bind".into(),
+        "/".into(),
+        "/".into(),
+    ];
+
+    for read_only_path in &sandbox_config.read_only_paths {
+        let read_only_path = working_directory.join(read_only_path);
+        sandboxed_command.push("--ro-bind".into());
+        sandboxed_command.push(read_only_path.clone().into());
+        sandboxed_command.push(read_only_path.into());
+    }
+
+    if !sandbox_config.network() {
+        sandboxed_command.push("--unshare-net".into());
+    }
+
+    sandboxed_command.push("--die-with-parent".into());
+    sandboxed_command.push("--chdir".into());
+    sandboxed_command.push(working_directory.into());
+    sandboxed_command.push("--".into());
+    sandboxed_command.extend(command.iter().map(OsString::from));
+
+    Ok(sandboxed_command)
+}
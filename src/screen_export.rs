@@ -0,0 +1,189 @@
+use std::{fmt::Write as _, path::Path};
+
+use termwiz::{cell::{CellAttributes, Intensity, Underline}, color::{ColorAttribute, ColorSpec}, escape::{csi::Sgr, CSI}};
+use wezterm_term::color::ColorPalette;
+
+/// Write the lines of a process screen, including colors and styles, to an
+/// HTML file.
+pub(crate) fn export_html(title: &str, lines: &[wezterm_term::Line], path: &Path) -> std::io::Result<()> {
+    let palette = ColorPalette::default();
+
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>");
+    let _ = writeln!(html, "<html>");
+    let _ = writeln!(html, "<head>");
+    let _ = writeln!(html, "<meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>{}</title>", escape_html(title));
+    let _ = writeln!(html, "</head>");
+    let _ = writeln!(html, "<body style=\"margin: 0;\">");
+    let _ = writeln!(
+        html,
+        "<pre style=\"margin: 0; padding: 1em; font-family: monospace; color: {}; background-color: {};\">",
+        palette.foreground.to_rgb_string(),
+        palette.background.to_rgb_string(),
+    );
+
+    for line in without_trailing_empty_lines(lines) {
+        let mut run_attributes: Option<CellAttributes> = None;
+        let mut run_text = String::new();
+
+        for cell in line.visible_cells() {
+            if run_attributes.as_ref() != Some(cell.attrs()) {
+                if let Some(run_attributes) = &run_attributes {
+                    write_run(&mut html, run_attributes, &run_text, &palette);
+                }
+                run_attributes = Some(cell.attrs().clone());
+                run_text.clear();
+            }
+            run_text.push_str(cell.str());
+        }
+
+        if let Some(run_attributes) = &run_attributes {
+            write_run(&mut html, run_attributes, run_text.trim_end(), &palette);
+        }
+        html.push('\n');
+    }
+
+    let _ = writeln!(html, "</pre>");
+    let _ = writeln!(html, "</body>");
+    let _ = writeln!(html, "</html>");
+
+    std::fs::write(path, html)
+}
+
+/// Write lines of output to a text file, either as plain text or with colors
+/// and styles as ANSI escape sequences, as when viewed using `less -R`.
+pub(crate) fn export_text(lines: &[wezterm_term::Line], path: &Path, with_colors: bool) -> std::io::Result<()> {
+    let mut text = String::new();
+
+    for line in without_trailing_empty_lines(lines) {
+        if with_colors {
+            write_ansi_line(&mut text, line);
+        } else {
+            text.push_str(line.as_str().trim_end());
+        }
+        text.push('\n');
+    }
+
+    std::fs::write(path, text)
+}
+
+fn without_trailing_empty_lines(lines: &[wezterm_term::Line]) -> &[wezterm_term::Line] {
+    let last_non_empty_line_index = lines.iter()
+        .rposition(|line| line.visible_cells().any(|cell| !cell.str().trim().is_empty()));
+    match last_non_empty_line_index {
+        Some(last_non_empty_line_index) => &lines[..=last_non_empty_line_index],
+        None => &[],
+    }
+}
+
+fn write_ansi_line(text: &mut String, line: &wezterm_term::Line) {
+    let mut runs: Vec<(CellAttributes, String)> = Vec::new();
+    for cell in line.visible_cells() {
+        match runs.last_mut() {
+            Some((run_attributes, run_text)) if run_attributes == cell.attrs() => run_text.push_str(cell.str()),
+            _ => runs.push((cell.attrs().clone(), cell.str().to_owned())),
+        }
+    }
+    if let Some((_, run_text)) = runs.last_mut() {
+        run_text.truncate(run_text.trim_end().len());
+    }
+
+    for (run_attributes, run_text) in runs {
+        if run_text.is_empty() {
+            continue;
+        }
+        let sgrs = attributes_sgrs(&run_attributes);
+        let is_styled = !sgrs.is_empty();
+        for sgr in sgrs {
+            let _ = write!(text, "{}", CSI::Sgr(sgr));
+        }
+        text.push_str(&run_text);
+        if is_styled {
+            let _ = write!(text, "{}", CSI::Sgr(Sgr::Reset));
+        }
+    }
+}
+
+/// The escape sequences that set the attributes, starting from the default
+/// attributes.
+fn attributes_sgrs(attributes: &CellAttributes) -> Vec<Sgr> {
+    let mut sgrs = Vec::new();
+    if attributes.intensity() != Intensity::Normal {
+        sgrs.push(Sgr::Intensity(attributes.intensity()));
+    }
+    if attributes.underline() != Underline::None {
+        sgrs.push(Sgr::Underline(attributes.underline()));
+    }
+    if attributes.italic() {
+        sgrs.push(Sgr::Italic(true));
+    }
+    if attributes.reverse() {
+        sgrs.push(Sgr::Inverse(true));
+    }
+    if attributes.strikethrough() {
+        sgrs.push(Sgr::StrikeThrough(true));
+    }
+    if attributes.foreground() != ColorAttribute::Default {
+        sgrs.push(Sgr::Foreground(color_spec(attributes.foreground())));
+    }
+    if attributes.background() != ColorAttribute::Default {
+        sgrs.push(Sgr::Background(color_spec(attributes.background())));
+    }
+    sgrs
+}
+
+fn color_spec(color: ColorAttribute) -> ColorSpec {
+    match color {
+        ColorAttribute::TrueColorWithPaletteFallback(color, _)
+        | ColorAttribute::TrueColorWithDefaultFallback(color) => ColorSpec::TrueColor(color),
+        ColorAttribute::PaletteIndex(index) => ColorSpec::PaletteIndex(index),
+        ColorAttribute::Default => ColorSpec::Default,
+    }
+}
+
+fn write_run(html: &mut String, attributes: &CellAttributes, text: &str, palette: &ColorPalette) {
+    if text.is_empty() {
+        return;
+    }
+
+    let (foreground, background) = if attributes.reverse() {
+        (palette.resolve_bg(attributes.background()), palette.resolve_fg(attributes.foreground()))
+    } else {
+        (palette.resolve_fg(attributes.foreground()), palette.resolve_bg(attributes.background()))
+    };
+
+    let mut style = format!("color: {};", foreground.to_rgb_string());
+    if background != palette.background || attributes.reverse() {
+        let _ = write!(style, " background-color: {};", background.to_rgb_string());
+    }
+    match attributes.intensity() {
+        Intensity::Bold => style.push_str(" font-weight: bold;"),
+        Intensity::Half => style.push_str(" opacity: 0.6;"),
+        Intensity::Normal => {},
+    }
+    if attributes.italic() {
+        style.push_str(" font-style: italic;");
+    }
+    if attributes.underline() != Underline::None {
+        style.push_str(" text-decoration: underline;");
+    } else if attributes.strikethrough() {
+        style.push_str(" text-decoration: line-through;");
+    }
+
+    let _ = write!(html, "<span style=\"{style}\">{}</span>", escape_html(text));
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,60 @@
+/// A search through the output of the focused process.
+pub(crate) struct Search {
+    pub(crate) query: String,
+
+    /// Whether the query is still being typed.
+    pub(crate) editing: bool,
+
+    /// The process and row of the match that was last jumped to.
+    pub(crate) current_match: Option<(usize, usize)>,
+}
+
+impl Search {
+    pub(crate) fn new() -> Self {
+        Self {
+            query: String::new(),
+            editing: true,
+            current_match: None,
+        }
+    }
+}
+
+pub(crate) fn is_match(text: &str, query: &str) -> bool {
+    !query.is_empty() && text.to_ascii_lowercase().contains(&query.to_ascii_lowercase())
+}
+
+/// Show the parts of the lines that match the query in reverse video.
+pub(crate) fn highlight_matches(lines: &mut [wezterm_term::Line], query: &str) {
+    if query.is_empty() {
+        return;
+    }
+    let query = query.to_ascii_lowercase();
+
+    for line in lines {
+        // Lowercasing only ASCII characters keeps the byte offsets the same,
+        // so the offset of each match can be mapped back to a cell.
+        let mut text = String::new();
+        let mut cell_byte_offsets = Vec::new();
+        for cell in line.visible_cells() {
+            cell_byte_offsets.push((text.len(), cell.cell_index()));
+            text.push_str(&cell.str().to_ascii_lowercase());
+        }
+
+        let matched_cell_indexes: Vec<usize> = text.match_indices(&query)
+            .flat_map(|(match_start, matched)| {
+                let match_end = match_start + matched.len();
+                cell_byte_offsets.iter()
+                    .filter(move |(byte_offset, _)| *byte_offset >= match_start && *byte_offset < match_end)
+                    .map(|(_, cell_index)| *cell_index)
+            })
+            .collect();
+
+        let cells = line.cells_mut_for_attr_changes_only();
+        for cell_index in matched_cell_indexes {
+            if let Some(cell) = cells.get_mut(cell_index) {
+                let attributes = cell.attrs_mut();
+                attributes.set_reverse(!attributes.reverse());
+            }
+        }
+    }
+}
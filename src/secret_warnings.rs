@@ -0,0 +1,69 @@
+use std::{collections::HashSet, sync::{Arc, Mutex}};
+
+use regex::Regex;
+
+use crate::output_lines::OutputLine;
+
+lazy_static::lazy_static! {
+    /// Common credentials, along with a description of each.
+    static ref SECRET_PATTERNS: Vec<(&'static str, Regex)> = [
+        ("an AWS access key ID", r"\b(AKIA|ASIA)[0-9A-Z]{16}\b"),
+        ("an AWS secret access key", r"(?i)aws_secret_access_key\s*[=:]\s*[A-Za-z0-9/+=]{40}"),
+        ("a private key", r"-----BEGIN ([A-Z]+ )?PRIVATE KEY-----"),
+        ("a GitHub token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+        ("a Slack token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+    ]
+        .into_iter()
+        .map(|(description, regex_str)| (description, Regex::new(regex_str).unwrap()))
+        .collect();
+}
+
+/// A process printed something that looks like a credential.
+pub(crate) struct SecretWarning {
+    pub(crate) process_name: String,
+    pub(crate) secret_description: &'static str,
+}
+
+/// Scans the output of processes for credentials that were logged by
+/// accident. Each kind of credential is only reported once per process, so
+/// that a process that keeps logging a credential doesn't hide other
+/// messages.
+#[derive(Clone)]
+pub(crate) struct SecretScanner {
+    state: Arc<Mutex<SecretScannerState>>,
+}
+
+#[derive(Default)]
+struct SecretScannerState {
+    reported: HashSet<(String, &'static str)>,
+    warnings: Vec<SecretWarning>,
+}
+
+impl SecretScanner {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SecretScannerState::default())),
+        }
+    }
+
+    pub(crate) fn scan(&self, process_name: &str, lines: &[OutputLine]) {
+        for (secret_description, regex) in SECRET_PATTERNS.iter() {
+            if !lines.iter().any(|line| regex.is_match(&line.text)) {
+                continue;
+            }
+
+            let mut state = self.state.lock().unwrap();
+            if state.reported.insert((process_name.to_owned(), secret_description)) {
+                state.warnings.push(SecretWarning {
+                    process_name: process_name.to_owned(),
+                    secret_description,
+                });
+            }
+        }
+    }
+
+    /// The warnings found since the last call.
+    pub(crate) fn take_warnings(&self) -> Vec<SecretWarning> {
+        std::mem::take(&mut self.state.lock().unwrap().warnings)
+    }
+}
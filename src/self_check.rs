@@ -0,0 +1,123 @@
+use termwiz::caps::{Capabilities, ColorLevel};
+
+use crate::host_terminal::HostTerminalFeatures;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Print the version of Mintaka, and which features are supported by the
+/// current terminal.
+pub(crate) fn run(check_for_updates: bool) {
+    println!("Mintaka {VERSION}");
+
+    if check_for_updates {
+        match latest_version() {
+            Ok(latest_version) if is_newer(&latest_version, VERSION) => {
+                println!("A newer version is available: {latest_version}");
+            },
+            Ok(_) => println!("Mintaka is up to date"),
+            Err(error) => println!("Couldn't check for updates: {error}"),
+        }
+    }
+
+    println!();
+    println!("Terminal:");
+    for (feature, support) in terminal_features() {
+        println!("  {feature}: {support}");
+    }
+}
+
+fn terminal_features() -> Vec<(&'static str, String)> {
+    let host_terminal_features = HostTerminalFeatures::detect();
+    let capabilities = Capabilities::new_from_env().ok();
+    let in_tmux = std::env::var_os("TMUX").is_some();
+
+    let truecolor = match capabilities.as_ref().map(|capabilities| capabilities.color_level()) {
+        Some(ColorLevel::TrueColor) => "yes".to_owned(),
+        Some(_) => "no, so colors set by `background` and `highlights` are approximated".to_owned(),
+        None => "unknown".to_owned(),
+    };
+
+    let graphics = match &capabilities {
+        Some(capabilities) if capabilities.sixel() || capabilities.iterm2_image() => "yes",
+        Some(_) => "no",
+        None => "unknown",
+    };
+
+    let clipboard = if in_tmux {
+        if tmux_clipboard_enabled() {
+            "yes, using tmux".to_owned()
+        } else {
+            "no, since the tmux option `set-clipboard` is off".to_owned()
+        }
+    } else if host_terminal_features.hyperlinks {
+        "yes".to_owned()
+    } else {
+        "unknown, depending on whether the terminal supports OSC 52".to_owned()
+    };
+
+    let status_user_var = if in_tmux {
+        "yes, as the tmux pane option `@mintaka_status`"
+    } else if host_terminal_features.hyperlinks {
+        "yes, as the user var `mintaka_status`"
+    } else {
+        "unknown"
+    };
+
+    vec![
+        ("Truecolor", truecolor),
+        ("Hyperlinks", yes_or_no(host_terminal_features.hyperlinks)),
+        ("Styled underlines", yes_or_no(host_terminal_features.styled_underlines)),
+        ("Copying to the clipboard (OSC 52)", clipboard),
+        ("Publishing the status", status_user_var.to_owned()),
+        ("Graphics (sixel or iTerm2 images)", graphics.to_owned()),
+    ]
+}
+
+fn yes_or_no(supported: bool) -> String {
+    let support = if supported { "yes" } else { "no" };
+    support.to_owned()
+}
+
+fn tmux_clipboard_enabled() -> bool {
+    std::process::Command::new("tmux")
+        .arg("show-options")
+        .arg("-gv")
+        .arg("set-clipboard")
+        .output()
+        .is_ok_and(|output| {
+            output.status.success() && String::from_utf8_lossy(&output.stdout).trim() != "off"
+        })
+}
+
+/// Find the latest released version of Mintaka using `cargo search`, since
+/// Mintaka is installed using Cargo.
+fn latest_version() -> Result<String, String> {
+    let output = std::process::Command::new("cargo")
+        .arg("search")
+        .arg("mintaka")
+        .arg("--limit")
+        .arg("1")
+        .output()
+        .map_err(|error| format!("failed to run cargo: {error}"))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+
+    // The output has lines such as `mintaka = "0.2.0"    # Description`.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines()
+        .find_map(|line| line.strip_prefix("mintaka = \""))
+        .and_then(|rest| rest.split('"').next())
+        .map(str::to_owned)
+        .ok_or_else(|| "mintaka wasn't found on crates.io".to_owned())
+}
+
+fn is_newer(version: &str, current_version: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version.split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(version) > parse(current_version)
+}
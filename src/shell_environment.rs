@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use crate::config::EnvironmentConfig;
+
+/// Wrap a command so that it runs with the environment that the user would get
+/// in their own shell, including any changes made by tools such as nvm, pyenv
+/// or direnv.
+pub(crate) fn wrap_command(
+    environment_config: EnvironmentConfig,
+    command: &[String],
+    working_directory: &Path,
+) -> Vec<String> {
+    match environment_config {
+        EnvironmentConfig::LoginShell => shell_command("-l", command),
+        EnvironmentConfig::InteractiveShell => shell_command("-i", command),
+        EnvironmentConfig::Direnv => {
+            let mut wrapped_command = vec![
+                "direnv".to_owned(),
+                "exec".to_owned(),
+                working_directory.display().to_string(),
+            ];
+            wrapped_command.extend(command.iter().cloned());
+            wrapped_command
+        },
+    }
+}
+
+/// The executable that loads the environment.
+pub(crate) fn loader_executable(environment_config: EnvironmentConfig) -> String {
+    match environment_config {
+        EnvironmentConfig::LoginShell
+        | EnvironmentConfig::InteractiveShell => user_shell(),
+        EnvironmentConfig::Direnv => "direnv".to_owned(),
+    }
+}
+
+fn shell_command(shell_flag: &str, command: &[String]) -> Vec<String> {
+    let shell = user_shell();
+
+    // The command is passed as arguments to the shell, rather than being
+    // interpolated into the script, to avoid any quoting problems.
+    let is_fish = Path::new(&shell).file_name().is_some_and(|file_name| file_name == "fish");
+    let mut wrapped_command = if is_fish {
+        vec![shell, shell_flag.to_owned(), "-c".to_owned(), "exec $argv".to_owned()]
+    } else {
+        vec![shell, shell_flag.to_owned(), "-c".to_owned(), "exec \"$@\"".to_owned(), "mintaka".to_owned()]
+    };
+    wrapped_command.extend(command.iter().cloned());
+    wrapped_command
+}
+
+fn user_shell() -> String {
+    std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_owned())
+}
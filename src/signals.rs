@@ -0,0 +1,87 @@
+use std::{sync::{atomic::AtomicBool, Arc}, time::Duration};
+
+/// The signals that can be used to stop a process, without the `SIG` prefix.
+const STOP_SIGNALS: [&str; 7] = ["HUP", "INT", "QUIT", "TERM", "KILL", "USR1", "USR2"];
+
+/// Normalize the name of a signal such as `"SIGINT"` or `"INT"`, or return
+/// `None` if the signal isn't supported for stopping processes.
+pub(crate) fn stop_signal_name(signal: &str) -> Option<&'static str> {
+    let signal = signal.strip_prefix("SIG").unwrap_or(signal);
+    STOP_SIGNALS.into_iter().find(|stop_signal| *stop_signal == signal)
+}
+
+/// The signal used to stop a process when the config doesn't set one, which
+/// is the same signal sent when a terminal is closed.
+pub(crate) const DEFAULT_STOP_SIGNAL: &str = "HUP";
+
+/// Send a signal to a process along with any processes it started, returning
+/// whether the signal was sent. Processes are started in their own session,
+/// so their process ID is also their process group ID, and signalling the
+/// group reaches grandchildren such as the server started by `npm run`.
+#[cfg(unix)]
+pub(crate) fn send_signal(pid: u32, signal: &str) -> bool {
+    let Some(signal) = signal_number(signal) else {
+        return false;
+    };
+    signal_process_group(pid, signal)
+}
+
+/// Signals aren't supported on other platforms, so the process and any
+/// processes it started are forcibly killed instead.
+#[cfg(not(unix))]
+pub(crate) fn send_signal(pid: u32, _signal: &str) -> bool {
+    std::process::Command::new("taskkill")
+        .arg("/PID")
+        .arg(pid.to_string())
+        .arg("/T")
+        .arg("/F")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Forcibly kill a process, along with any processes it started, if any of
+/// them are still running after the timeout. Once the process has exited, its
+/// process group ID may be reused, so the group is only killed if the process
+/// hasn't exited.
+#[cfg(unix)]
+pub(crate) fn kill_after_timeout(pid: u32, timeout: Duration, exited: Arc<AtomicBool>) {
+    std::thread::spawn(move || {
+        std::thread::sleep(timeout);
+
+        if !exited.load(std::sync::atomic::Ordering::SeqCst) {
+            signal_process_group(pid, libc::SIGKILL);
+        }
+    });
+}
+
+/// On other platforms, processes are always forcibly killed, so there's no
+/// need to escalate.
+#[cfg(not(unix))]
+pub(crate) fn kill_after_timeout(_pid: u32, _timeout: Duration, _exited: Arc<AtomicBool>) {
+}
+
+#[cfg(unix)]
+fn signal_number(signal: &str) -> Option<libc::c_int> {
+    match stop_signal_name(signal)? {
+        "HUP" => Some(libc::SIGHUP),
+        "INT" => Some(libc::SIGINT),
+        "QUIT" => Some(libc::SIGQUIT),
+        "TERM" => Some(libc::SIGTERM),
+        "KILL" => Some(libc::SIGKILL),
+        "USR1" => Some(libc::SIGUSR1),
+        "USR2" => Some(libc::SIGUSR2),
+        _ => None,
+    }
+}
+
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: libc::c_int) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // A process group ID of 0 or 1 would signal Mintaka's own group or every
+    // process, so is never valid for a child.
+    pid > 1 && unsafe { libc::killpg(pid, signal) } == 0
+}
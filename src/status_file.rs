@@ -0,0 +1,96 @@
+use std::{path::{Path, PathBuf}, sync::mpsc::{Receiver, RecvTimeoutError, Sender}, thread::JoinHandle, time::Duration};
+
+use crate::{processes::{ProcessStatus, Processes}, remote_control::{json_string, status_fields}};
+
+/// How long the statuses must stay the same before they're written, so that a
+/// burst of changes, such as when every process restarts, is written once.
+const DEBOUNCE_DURATION: Duration = Duration::from_millis(200);
+
+/// Writes the status of every process to a JSON file whenever it changes, so
+/// that tools such as the status line of an editor can show the statuses
+/// without using the control socket.
+pub(crate) struct StatusFile {
+    path: PathBuf,
+    last_statuses: Option<Vec<(String, ProcessStatus)>>,
+    json_tx: Sender<String>,
+    writer: JoinHandle<()>,
+}
+
+impl StatusFile {
+    pub(crate) fn spawn(path: PathBuf) -> Self {
+        let (json_tx, json_rx) = std::sync::mpsc::channel();
+        let writer = std::thread::spawn({
+            let path = path.clone();
+            move || write_status_files(&path, json_rx)
+        });
+
+        Self {
+            path,
+            last_statuses: None,
+            json_tx,
+            writer,
+        }
+    }
+
+    pub(crate) fn update(&mut self, processes: &Processes) {
+        // Building the JSON includes summarising the output of failed runs, so
+        // the statuses are compared first.
+        let statuses: Vec<(String, ProcessStatus)> = processes.processes()
+            .iter()
+            .map(|process| (process.name().to_owned(), process.status()))
+            .collect();
+        if self.last_statuses.as_ref() == Some(&statuses) {
+            return;
+        }
+        let _ = self.json_tx.send(status_file_json(processes));
+        self.last_statuses = Some(statuses);
+    }
+
+    /// Remove the file so that tools don't show the statuses of processes that
+    /// are no longer running.
+    pub(crate) fn remove(self) {
+        drop(self.json_tx);
+        let _ = self.writer.join();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn write_status_files(path: &Path, json_rx: Receiver<String>) {
+    while let Ok(mut json) = json_rx.recv() {
+        loop {
+            match json_rx.recv_timeout(DEBOUNCE_DURATION) {
+                Ok(newer_json) => json = newer_json,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        // Write to a temporary file first so that readers never see a
+        // partially written file.
+        let mut temporary_path = path.as_os_str().to_owned();
+        temporary_path.push(".tmp");
+        if std::fs::write(&temporary_path, &json).is_ok() {
+            let _ = std::fs::rename(&temporary_path, path);
+        }
+    }
+}
+
+fn status_file_json(processes: &Processes) -> String {
+    let process_jsons: Vec<String> = processes.processes()
+        .iter()
+        .map(|process| {
+            let mut fields = vec![format!("\"name\":{}", json_string(process.name()))];
+            fields.extend(status_fields(process));
+            if let Some(failure_summary) = process.failure_summary() {
+                fields.push(format!("\"last_error\":{}", json_string(&failure_summary)));
+            }
+            format!("{{{}}}", fields.join(","))
+        })
+        .collect();
+
+    format!(
+        "{{\"failure_count\":{},\"processes\":[{}]}}\n",
+        processes.failure_count(),
+        process_jsons.join(","),
+    )
+}
@@ -0,0 +1,92 @@
+use std::{collections::HashMap, str::FromStr};
+
+use ratatui::style::Color;
+
+/// The colors used by the UI, built from the defaults and the `theme` table of
+/// the config.
+#[derive(Clone, Copy)]
+pub(crate) struct Theme {
+    pub(crate) success: Color,
+    pub(crate) failure: Color,
+    pub(crate) muted: Color,
+    pub(crate) process_list: Color,
+    pub(crate) process_list_text: Color,
+    pub(crate) highlight: Color,
+    pub(crate) highlight_text: Color,
+    pub(crate) status_bar: Color,
+    pub(crate) mode_main: Color,
+    pub(crate) mode_history: Color,
+    pub(crate) mode_typing: Color,
+    pub(crate) mode_menu: Color,
+    pub(crate) mode_text: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            success: Color::Green,
+            failure: Color::Red,
+            muted: Color::DarkGray,
+            process_list: Color::White,
+            process_list_text: Color::Black,
+            highlight: Color::Black,
+            highlight_text: Color::White,
+            status_bar: Color::Reset,
+            mode_main: Color::Gray,
+            mode_history: Color::Yellow,
+            mode_typing: Color::Cyan,
+            mode_menu: Color::Magenta,
+            mode_text: Color::Black,
+        }
+    }
+}
+
+impl Theme {
+    /// Build the theme, or describe the problems with the `theme` table.
+    pub(crate) fn new(theme_config: &HashMap<String, String>) -> Result<Self, Vec<String>> {
+        let mut theme = Self::default();
+        let mut problems = Vec::new();
+
+        let mut configured_color_names: Vec<&String> = theme_config.keys().collect();
+        configured_color_names.sort();
+
+        for color_name in configured_color_names {
+            let color_str = &theme_config[color_name];
+
+            let Some(color) = theme.color_mut(color_name) else {
+                problems.push(format!("unknown color {color_name}"));
+                continue;
+            };
+
+            match Color::from_str(color_str) {
+                Ok(configured_color) => *color = configured_color,
+                Err(_) => problems.push(format!("{color_name} has invalid color {color_str:?}")),
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(theme)
+        } else {
+            Err(problems)
+        }
+    }
+
+    fn color_mut(&mut self, color_name: &str) -> Option<&mut Color> {
+        match color_name {
+            "success" => Some(&mut self.success),
+            "failure" => Some(&mut self.failure),
+            "muted" => Some(&mut self.muted),
+            "process_list" => Some(&mut self.process_list),
+            "process_list_text" => Some(&mut self.process_list_text),
+            "highlight" => Some(&mut self.highlight),
+            "highlight_text" => Some(&mut self.highlight_text),
+            "status_bar" => Some(&mut self.status_bar),
+            "mode_main" => Some(&mut self.mode_main),
+            "mode_history" => Some(&mut self.mode_history),
+            "mode_typing" => Some(&mut self.mode_typing),
+            "mode_menu" => Some(&mut self.mode_menu),
+            "mode_text" => Some(&mut self.mode_text),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::{fmt::Debug, fs::File, io::{BufWriter, Write}, path::Path, sync::{Mutex, OnceLock}, time::Instant};
+
+/// Trace events are written as lines in logfmt, such as:
+///
+/// ```text
+/// t_us=1523 event=output_parsed process="tsc" duration_us=12
+/// ```
+///
+/// where `t_us` is the number of microseconds since tracing started.
+struct Tracer {
+    start: Instant,
+    writer: Mutex<BufWriter<File>>,
+}
+
+static TRACER: OnceLock<Tracer> = OnceLock::new();
+
+pub(crate) fn start(path: &Path) -> std::io::Result<()> {
+    let file = File::create(path)?;
+    let _ = TRACER.set(Tracer {
+        start: Instant::now(),
+        writer: Mutex::new(BufWriter::new(file)),
+    });
+    Ok(())
+}
+
+pub(crate) fn enabled() -> bool {
+    TRACER.get().is_some()
+}
+
+pub(crate) fn event(name: &str, fields: &[(&str, &dyn Debug)]) {
+    let Some(tracer) = TRACER.get() else {
+        return;
+    };
+
+    let mut line = format!("t_us={} event={name}", tracer.start.elapsed().as_micros());
+    for (key, value) in fields {
+        line.push_str(&format!(" {key}={value:?}"));
+    }
+    line.push('\n');
+
+    let mut writer = tracer.writer.lock().unwrap();
+    // Tracing is a diagnostic aid, so failing to write a trace shouldn't
+    // interrupt the processes being run.
+    let _ = writer.write_all(line.as_bytes());
+}
+
+pub(crate) fn flush() {
+    if let Some(tracer) = TRACER.get() {
+        let _ = tracer.writer.lock().unwrap().flush();
+    }
+}
+
+/// Measures the duration of an operation when tracing is enabled. When
+/// tracing is disabled, no time is measured.
+pub(crate) struct Span {
+    start: Option<Instant>,
+}
+
+impl Span {
+    pub(crate) fn start() -> Self {
+        Self {
+            start: enabled().then(Instant::now),
+        }
+    }
+
+    pub(crate) fn end(self, name: &str, fields: &[(&str, &dyn Debug)]) {
+        if let Some(start) = self.start {
+            let duration_us = start.elapsed().as_micros();
+            let mut fields = fields.to_vec();
+            fields.push(("duration_us", &duration_us));
+            event(name, &fields);
+        }
+    }
+}
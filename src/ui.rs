@@ -1,24 +1,93 @@
-use std::sync::{Arc, Mutex};
+use std::{path::Path, sync::{Arc, Mutex}, time::{Duration, Instant}};
 
-use ratatui::{backend::TermwizBackend, buffer::Buffer, layout::{Constraint, Layout, Rect}, style::{Color, Style, Stylize}, text::{Line, Text}, widgets::{Block, List, ListItem, ListState, Widget}, Frame};
-use termwiz::surface::{Change, Surface};
+use ratatui::{backend::TermwizBackend, buffer::Buffer, layout::{Constraint, Layout, Margin, Rect}, style::{Color, Style, Stylize}, symbols, text::{Line, Span, Text}, widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Widget}, Frame};
+use termwiz::{color::RgbColor, surface::{Change, Surface}};
 use wezterm_term::CellAttributes;
 
-use crate::processes::{ProcessStatus, Processes};
+use crate::{checkpoints::{self, DiffLine}, host_terminal::HostTerminalFeatures, messages::{Locale, Message}, pane_background::PaneBackground, processes::{Process, ProcessListEntry, ProcessStatus, Processes, Shutdown, SplitDirection}, redactions::Redactor, resource_usage::ResourceUsage, search, theme::Theme};
 
-pub(crate) fn render_ui(processes: &Arc<Mutex<Processes>>, terminal: &mut ratatui::Terminal<TermwizBackend>) {
+pub(crate) fn render_ui(
+    processes: &Arc<Mutex<Processes>>,
+    config_path: &Path,
+    max_process_list_width: usize,
+    host_terminal_features: &HostTerminalFeatures,
+    redactor: &Redactor,
+    locale: Locale,
+    theme: &Theme,
+    process_list_scroll: &mut ProcessListScroll,
+    terminal: &mut ratatui::Terminal<TermwizBackend>,
+) -> termwiz::Result<UiLayout> {
     let mut processes = processes.lock().unwrap();
     let mut process_pane = ProcessPane::new();
-    terminal.draw(|frame| {
-        render_main(&processes, &mut process_pane, frame);
-    }).unwrap();
+    let mut split_pane = ProcessPane::new();
+    let mut ui_layout = UiLayout::default();
+    draw_without_flushing(terminal, |frame| {
+        render_main(&processes, config_path, max_process_list_width, redactor, locale, theme, process_list_scroll, &mut process_pane, &mut split_pane, &mut ui_layout, frame);
+    })?;
+    ui_layout.process_pane = process_pane.area;
 
     let buffered_terminal = terminal.backend_mut().buffered_terminal_mut();
-    processes.resize((process_pane.area.width.into(), process_pane.area.height.into()));
+    let split_size = processes.split().map(|_| (split_pane.area.width.into(), split_pane.area.height.into()));
+    processes.resize((process_pane.area.width.into(), process_pane.area.height.into()), split_size);
 
-    let lines = processes.lines();
-    let mut process_surface = Surface::new(process_pane.area.width.into(), process_pane.area.height.into());
-    process_surface.add_change(Change::ClearScreen(Default::default()));
+    if process_pane_hidden(&processes) {
+        buffered_terminal.flush()?;
+        return Ok(ui_layout);
+    }
+
+    let search_query = processes.search().map(|search| search.query.as_str());
+    let process_surface = process_output_surface(processes.focused_process(), search_query, process_pane.area, processes.privacy_mode(), host_terminal_features, redactor, locale);
+    buffered_terminal.draw_from_screen(
+        &process_surface,
+        process_pane.area.x.into(),
+        process_pane.area.y.into(),
+    );
+
+    if let Some(split_process) = processes.split_process() {
+        let split_surface = process_output_surface(split_process, None, split_pane.area, processes.privacy_mode(), host_terminal_features, redactor, locale);
+        buffered_terminal.draw_from_screen(
+            &split_surface,
+            split_pane.area.x.into(),
+            split_pane.area.y.into(),
+        );
+    }
+
+    buffered_terminal.flush()?;
+
+    Ok(ui_layout)
+}
+
+/// Draw the visible output of a process to a surface the size of its pane,
+/// highlighting any matches of the search query.
+fn process_output_surface(
+    process: &Process,
+    search_query: Option<&str>,
+    area: Rect,
+    privacy_mode: bool,
+    host_terminal_features: &HostTerminalFeatures,
+    redactor: &Redactor,
+    locale: Locale,
+) -> Surface {
+    let mut lines = process.lines();
+    if privacy_mode {
+        redactor.apply(&mut lines);
+    }
+    process.highlighter().apply(&mut lines);
+    if let Some(search_query) = search_query {
+        search::highlight_matches(&mut lines, search_query);
+    }
+    let pane_background = process.background_color().map(PaneBackground::new);
+    let default_attributes = match &pane_background {
+        Some(pane_background) => pane_background.default_attributes(),
+        None => CellAttributes::blank(),
+    };
+    let mut process_surface = Surface::new(area.width.into(), area.height.into());
+    process_surface.add_change(Change::AllAttributes(default_attributes.clone()));
+    process_surface.add_change(Change::ClearScreen(default_attributes.background()));
+
+    if let Some(start_error) = process.start_error() {
+        process_surface.add_change(Change::Text(Message::FailedToStart { error: start_error }.text(locale)));
+    }
 
     for (line_index, line) in lines.iter().enumerate() {
         if line_index != 0 {
@@ -26,143 +95,999 @@ pub(crate) fn render_ui(processes: &Arc<Mutex<Processes>>, terminal: &mut ratatu
                 termwiz::surface::Change::Text("\r\n".to_owned()),
             );
         }
-        let changes = line.changes(&CellAttributes::blank());
+        let mut changes = host_terminal_features.degrade_changes(line.changes(&CellAttributes::blank()));
+        if let Some(pane_background) = &pane_background {
+            changes = pane_background.apply(changes);
+        }
         process_surface.add_changes(changes);
         process_surface.add_change(
-            termwiz::surface::Change::AllAttributes(CellAttributes::blank()),
+            termwiz::surface::Change::AllAttributes(default_attributes.clone()),
         );
     }
 
-    buffered_terminal.draw_from_screen(
-        &process_surface,
-        process_pane.area.x.into(),
-        process_pane.area.y.into(),
-    );
-    buffered_terminal.flush().unwrap();
+    process_surface
 }
 
-fn render_main(processes: &Processes, process_pane: &mut ProcessPane, frame: &mut Frame) {
-    let layout = Layout::horizontal([
-        Constraint::Length(process_list_width(processes) as u16),
-        Constraint::Min(30),
-    ]).split(frame.size());
+/// Draw a frame using ratatui, as `ratatui::Terminal::draw` does, but without
+/// flushing it to the host terminal. The process pane is drawn on top of the
+/// frame afterwards, and the whole frame is then flushed at once: flushing the
+/// frame first would briefly show the new frame around the old process pane,
+/// which tears when the pane is slow to draw.
+fn draw_without_flushing(
+    terminal: &mut ratatui::Terminal<TermwizBackend>,
+    render: impl FnOnce(&mut Frame),
+) -> std::io::Result<()> {
+    terminal.autoresize()?;
+    let mut frame = terminal.get_frame();
+    render(&mut frame);
+    terminal.flush()?;
+    terminal.hide_cursor()?;
+    terminal.swap_buffers();
+    Ok(())
+}
+
+/// Where parts of the UI were drawn, for handling mouse events.
+#[derive(Default)]
+pub(crate) struct UiLayout {
+    process_list: Rect,
+    process_list_offset: usize,
+    process_list_label_width: usize,
+    locale: Locale,
+    process_pane: Rect,
+}
+
+impl UiLayout {
+    /// The entry shown in the process list at the given position.
+    pub(crate) fn process_list_entry_at(&self, processes: &Processes, x: u16, y: u16) -> Option<ProcessListEntry> {
+        if !rect_contains(self.process_list, x, y) {
+            return None;
+        }
+
+        let mut label_y = self.process_list.y;
+        for entry in processes.process_list_entries().into_iter().skip(self.process_list_offset) {
+            label_y += process_list_entry_height(processes, &entry) as u16;
+            if y < label_y {
+                return Some(entry);
+            }
+        }
 
-    let left_layout = Layout::vertical([
+        None
+    }
+
+    pub(crate) fn process_list_label_width(&self) -> usize {
+        self.process_list_label_width
+    }
+
+    pub(crate) fn is_in_process_list(&self, x: u16, y: u16) -> bool {
+        rect_contains(self.process_list, x, y)
+    }
+
+    pub(crate) fn is_in_process_pane(&self, x: u16, y: u16) -> bool {
+        rect_contains(self.process_pane, x, y)
+    }
+}
+
+fn rect_contains(rect: Rect, x: u16, y: u16) -> bool {
+    x >= rect.left() && x < rect.right() && y >= rect.top() && y < rect.bottom()
+}
+
+fn render_main(
+    processes: &Processes,
+    config_path: &Path,
+    max_process_list_width: usize,
+    redactor: &Redactor,
+    locale: Locale,
+    theme: &Theme,
+    process_list_scroll: &mut ProcessListScroll,
+    process_pane: &mut ProcessPane,
+    split_pane: &mut ProcessPane,
+    ui_layout: &mut UiLayout,
+    frame: &mut Frame,
+) {
+    let main_layout = Layout::vertical([
         Constraint::Fill(1),
         Constraint::Length(1),
-    ]).split(layout[0]);
+    ]).split(frame.size());
+
+    // TODO: Is there a way to calculate this programatically from the block?
+    let border_width = 1;
+    let max_label_width = max_process_list_width.saturating_sub(border_width * 2);
+    let label_width = match processes.process_list_width() {
+        // The process pane is kept at least as wide as its minimum width.
+        Some(process_list_width) => process_list_width
+            .min((frame.size().width as usize).saturating_sub(PROCESS_PANE_MIN_WIDTH as usize + border_width * 2))
+            .max(PROCESS_LIST_MIN_WIDTH),
+        None => process_list_label_width(processes, max_label_width, locale),
+    };
+
+    // When zoomed, the process list is hidden, and the status bar shows the
+    // name of the focused process instead.
+    let (pane_area, shown_label_width) = if processes.zoomed() {
+        ui_layout.process_list_label_width = label_width;
+        (main_layout[0], 0)
+    } else {
+        let layout = Layout::horizontal([
+            Constraint::Length((label_width + border_width * 2) as u16),
+            Constraint::Min(PROCESS_PANE_MIN_WIDTH),
+        ]).split(main_layout[0]);
+        render_process_list(processes, layout[0], label_width, locale, theme, process_list_scroll, ui_layout, frame);
+        (layout[1], label_width)
+    };
+
+    render_status_bar(processes, config_path, shown_label_width, locale, theme, main_layout[1], frame);
 
-    render_process_list(processes, left_layout[0], frame);
+    // When split, the second process is shown to the right of or below the
+    // focused process.
+    let focused_pane_area = match processes.split() {
+        None => pane_area,
+        Some(split) => {
+            let split_layout = match split.direction {
+                SplitDirection::SideBySide => Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]),
+                SplitDirection::Stacked => Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]),
+            }.split(pane_area);
+            render_split_pane(processes, split.direction, split_pane, split_layout[1], frame);
+            split_layout[0]
+        },
+    };
 
-    render_focus(processes, left_layout[1], frame);
+    // When any process has an accent color, a column is kept for the accent
+    // of the focused process, so that the process pane doesn't change size as
+    // the focus changes.
+    let process_pane_area = if processes.processes().iter().any(|process| process.accent_color().is_some()) {
+        let pane_layout = Layout::horizontal([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ]).split(focused_pane_area);
+        render_pane_accent(processes, pane_layout[0], frame);
+        pane_layout[1]
+    } else {
+        focused_pane_area
+    };
+
+    render_process_pane(process_pane, process_pane_area, frame);
+
+    if let Some(shutdown) = processes.shutdown() {
+        render_shutdown(shutdown, locale, pane_area, frame);
+    } else if let Some(busy_process_names) = processes.quit_confirmation() {
+        render_quit_confirmation(busy_process_names, locale, pane_area, frame);
+    } else if processes.upstream_picker().is_some() {
+        render_upstream_picker(processes, locale, theme, pane_area, frame);
+    } else if processes.checkpoint_browser().is_some() {
+        render_checkpoint_browser(processes, locale, theme, pane_area, frame);
+    } else if processes.waterfall_visible() {
+        render_waterfall(processes, locale, theme, pane_area, frame);
+    } else if processes.event_log_visible() {
+        render_event_log(processes, locale, theme, pane_area, frame);
+    } else if processes.config_source_visible() {
+        render_config_source(processes, locale, theme, pane_area, frame);
+    } else if processes.combined_log_focused() {
+        render_combined_log(processes, redactor, locale, theme, pane_area, frame);
+    }
+}
 
-    render_process_pane(process_pane, layout[1], frame);
+/// Whether something else is being shown in place of the output of the
+/// focused process.
+fn process_pane_hidden(processes: &Processes) -> bool {
+    processes.shutdown().is_some() || processes.quit_confirmation().is_some() || processes.upstream_picker().is_some() || processes.checkpoint_browser().is_some() || processes.waterfall_visible() || processes.event_log_visible() || processes.config_source_visible() || processes.combined_log_focused()
 }
 
-fn process_list_width(processes: &Processes) -> usize {
-    let process_labels = process_list_labels(processes);
+const PROCESS_PANE_MIN_WIDTH: u16 = 30;
+
+/// The narrowest that the labels in the process list can be made, which is
+/// enough to show the number of each process.
+const PROCESS_LIST_MIN_WIDTH: usize = 5;
+
+fn process_list_label_width(processes: &Processes, max_label_width: usize, locale: Locale) -> usize {
+    // The labels are measured after being translated, since translated
+    // statuses may be wider.
     let min_label_width = 15;
-    let label_width = process_labels
-        .map(|label| label.width())
+    processes.process_list_entries()
+        .iter()
+        .map(|entry| process_list_entry_width(processes, entry, locale))
         .max()
         .unwrap_or(min_label_width)
-        .max(min_label_width);
-    // TODO: Is there a way to calculate this programatically from the block?
-    let border_width = 1;
-    label_width + border_width * 2
+        .max(min_label_width)
+        .min(max_label_width)
+}
+
+/// The position of the process list, which is kept between frames so that
+/// the list only scrolls to the focused process when the focus changes.
+#[derive(Default)]
+pub(crate) struct ProcessListScroll {
+    /// The first entry shown, where the combined log is the first entry.
+    offset: usize,
+
+    /// The entry that was focused when the list was last drawn.
+    focused_entry_index: Option<usize>,
+}
+
+impl ProcessListScroll {
+    /// Scroll the list by the given number of entries, such as when using
+    /// the mouse wheel.
+    pub(crate) fn scroll_by(&mut self, entries: isize) {
+        self.offset = self.offset.saturating_add_signed(entries);
+    }
+
+    /// Choose the first entry to show, given the height of each entry and
+    /// the height of the list.
+    fn update(&mut self, focused_entry_index: usize, entry_heights: &[usize], height: usize) -> usize {
+        let mut offset = self.offset.min(entry_heights.len().saturating_sub(1));
+
+        if self.focused_entry_index != Some(focused_entry_index) {
+            offset = offset.min(focused_entry_index);
+            while offset < focused_entry_index && entry_heights[offset..=focused_entry_index].iter().sum::<usize>() > height {
+                offset += 1;
+            }
+        }
+
+        // Avoid leaving space below the last entry when scrolled to the end.
+        while offset > 0 && entry_heights[offset - 1..].iter().sum::<usize>() <= height {
+            offset -= 1;
+        }
+
+        self.offset = offset;
+        self.focused_entry_index = Some(focused_entry_index);
+        offset
+    }
 }
 
-fn render_process_list(processes: &Processes, area: Rect, frame: &mut Frame) {
-    let process_labels = process_list_labels(processes);
+fn render_process_list(processes: &Processes, area: Rect, label_width: usize, locale: Locale, theme: &Theme, process_list_scroll: &mut ProcessListScroll, ui_layout: &mut UiLayout, frame: &mut Frame) {
+    let block = Block::bordered();
+    let inner_area = block.inner(area);
+    ui_layout.process_list = inner_area;
+    ui_layout.process_list_label_width = label_width;
+    ui_layout.locale = locale;
+
+    let entries = processes.process_list_entries();
+    let focused_entry = processes.focused_process_list_entry();
+    let focused_entry_index = entries.iter()
+        .position(|entry| *entry == focused_entry)
+        .unwrap_or(0);
+    let entry_heights: Vec<usize> = entries.iter()
+        .map(|entry| process_list_entry_height(processes, entry))
+        .collect();
+    let height = usize::from(inner_area.height);
+    let offset = process_list_scroll.update(focused_entry_index, &entry_heights, height);
+    ui_layout.process_list_offset = offset;
+
+    // Only the labels that are visible are built, since there may be many
+    // processes.
+    let mut visible_height = 0;
+    let visible_entry_count = entry_heights[offset..].iter()
+        .take_while(|entry_height| {
+            let is_visible = visible_height < height;
+            visible_height += **entry_height;
+            is_visible
+        })
+        .count();
+    let process_labels = entries[offset..offset + visible_entry_count].iter()
+        .map(|entry| process_list_label(processes, entry, *entry == focused_entry, label_width, locale, theme));
+
     let process_list = List::new(process_labels)
-        .block(Block::bordered());
-    // TODO: maintain list state
-    let mut process_list_state = ListState::default().with_selected(Some(processes.focused_process_index));
+        .block(block);
+    let mut process_list_state = ListState::default()
+        .with_selected(focused_entry_index.checked_sub(offset));
     frame.render_stateful_widget(&process_list, area, &mut process_list_state);
+
+    let max_offset = entry_heights.len() - visible_entry_count;
+    if offset > 0 || max_offset > 0 {
+        let mut scrollbar_state = ScrollbarState::new(max_offset + 1)
+            .position(offset)
+            .viewport_content_length(visible_entry_count);
+        // The track matches the border that the scrollbar is drawn over.
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .track_symbol(Some(symbols::line::VERTICAL))
+            .begin_symbol(None)
+            .end_symbol(None);
+        frame.render_stateful_widget(scrollbar, area.inner(&Margin { vertical: 1, horizontal: 0 }), &mut scrollbar_state);
+    }
 }
 
-const STATUS_COLOR_SUCCESS: Color = Color::Green;
-const STATUS_COLOR_OTHER: Color = Color::DarkGray;
-const STATUS_COLOR_FAILED: Color = Color::Red;
+/// Which color of the theme a status is shown in.
+#[derive(Clone, Copy)]
+enum StatusColor {
+    Success,
+    Failure,
+    Muted,
+}
 
-fn process_list_labels(processes: & Processes) -> impl Iterator<Item=ListItem> {
-    let normal_style = Style::default().fg(Color::Black).bg(Color::White);
-    let focused_style = Style::default().fg(Color::White).bg(Color::Black);
+impl StatusColor {
+    fn color(self, theme: &Theme) -> Color {
+        match self {
+            StatusColor::Success => theme.success,
+            StatusColor::Failure => theme.failure,
+            StatusColor::Muted => theme.muted,
+        }
+    }
+}
+
+/// The height of an entry in the process list, which matches the number of
+/// lines in its label.
+fn process_list_entry_height(processes: &Processes, entry: &ProcessListEntry) -> usize {
+    match entry {
+        ProcessListEntry::CombinedLog | ProcessListEntry::Group(_) => 1,
+        ProcessListEntry::Process(process_index) => {
+            let process = &processes.processes()[*process_index];
+            2 + usize::from(process.is_sandboxed())
+                + usize::from(processes.resource_usage(process).is_some())
+                + usize::from(process_list_git_branch(processes, process).is_some())
+        },
+    }
+}
+
+/// The width that the label of an entry in the process list would have if
+/// it weren't truncated. This is measured without building the label, since
+/// every entry is measured on every frame.
+fn process_list_entry_width(processes: &Processes, entry: &ProcessListEntry, locale: Locale) -> usize {
+    let process_index = match entry {
+        ProcessListEntry::CombinedLog => {
+            return Span::raw(Message::AllProcessesLabel.text(locale)).width() + 2;
+        },
+        ProcessListEntry::Group(group) => {
+            return Span::raw(group_heading(processes, group)).width() + 2;
+        },
+        ProcessListEntry::Process(process_index) => *process_index,
+    };
+    let process = &processes.processes()[process_index];
+
+    let name_width = Span::raw(format!(" {}. {} ", process_index + 1, process.name())).width();
+    let (status_str, _) = process_list_status_label(process, locale);
+    let sandbox_width = if process.is_sandboxed() {
+        Span::raw(Message::Sandboxed.text(locale)).width() + 4
+    } else {
+        0
+    };
 
-    processes.processes()
-        .into_iter()
-        .enumerate()
-        .map(move |(process_index, process)| {
-            let mut text = Text::default();
-            let style = if processes.focused_process_index == process_index {
-                focused_style
+    let resource_usage_width = processes.resource_usage(process)
+        .map(|resource_usage| Span::raw(resource_usage_label(resource_usage)).width() + 4)
+        .unwrap_or(0);
+
+    let git_branch_width = process_list_git_branch(processes, process)
+        .map(|git_branch| Span::raw(git_branch_label(git_branch)).width() + 4)
+        .unwrap_or(0);
+
+    name_width
+        .max(Span::raw(status_str).width() + 4)
+        .max(sandbox_width)
+        .max(resource_usage_width)
+        .max(git_branch_width)
+}
+
+/// The heading of a group, such as `▸ backend (3)`, where the arrow shows
+/// whether the group is collapsed.
+fn group_heading(processes: &Processes, group: &str) -> String {
+    let process_count = processes.processes()
+        .iter()
+        .filter(|process| process.group() == Some(group))
+        .count();
+    let arrow = if processes.is_group_collapsed(group) { '▸' } else { '▾' };
+    format!("{arrow} {group} ({process_count})")
+}
+
+fn process_list_label(processes: &Processes, entry: &ProcessListEntry, is_focused: bool, max_label_width: usize, locale: Locale, theme: &Theme) -> ListItem<'static> {
+    let normal_style = Style::default().fg(theme.process_list_text).bg(theme.process_list);
+    let focused_style = Style::default().fg(theme.highlight_text).bg(theme.highlight);
+    let style = if is_focused {
+        focused_style
+    } else {
+        normal_style
+    };
+
+    let process_index = match entry {
+        ProcessListEntry::CombinedLog => {
+            return ListItem::new(Line::styled(format!(" {} ", Message::AllProcessesLabel.text(locale)), style));
+        },
+        ProcessListEntry::Group(group) => {
+            // A collapsed group is shown as failing when any of its processes
+            // are failing, since they can't be seen.
+            let is_failing = processes.is_group_collapsed(group) && processes.processes()
+                .iter()
+                .any(|process| process.group() == Some(group.as_str()) && process.alerting_status().is_failure());
+            let group_style = if is_failing {
+                style.fg(theme.failure)
             } else {
-                normal_style
+                style
             };
+            let heading = truncate_with_ellipsis(&group_heading(processes, group), max_label_width.saturating_sub(2));
+            return ListItem::new(Line::styled(format!(" {heading} "), group_style.bold()));
+        },
+        ProcessListEntry::Process(process_index) => *process_index,
+    };
+    let process = &processes.processes()[process_index];
 
-            text.push_line(Line::styled(
-                format!(" {}. {} ", process_index + 1, process.name()),
-                style
-            ));
+    let mut text = Text::default();
 
-            let (status_str, status_color) = match process.status() {
-                ProcessStatus::NotStarted => {
-                    ("INACTIVE".to_owned(), STATUS_COLOR_OTHER)
-                },
-                ProcessStatus::WaitingForUpstream => {
-                    ("WAITING".to_owned(), STATUS_COLOR_OTHER)
-                },
-                ProcessStatus::Running => {
-                    ("RUNNING".to_owned(), STATUS_COLOR_OTHER)
-                },
-                ProcessStatus::Success => {
-                    ("SUCCESS".to_owned(), STATUS_COLOR_SUCCESS)
-                }
-                ProcessStatus::Errors { error_count } => {
-                    let mut status_str = "ERR".to_owned();
-
-                    if let Some(error_count) = error_count {
-                        let error_count_str = if error_count >= 100 {
-                            "99+".to_owned()
-                        } else {
-                            format!("{error_count}")
-                        };
-                        status_str.push_str(&format!(" ({error_count_str})"));
-                    }
-
-                    (status_str, STATUS_COLOR_FAILED)
-                },
-                ProcessStatus::Exited { exit_code } => {
-                    let status_color = if exit_code == 0 {
-                        STATUS_COLOR_SUCCESS
-                    } else {
-                        STATUS_COLOR_FAILED
-                    };
-                    (format!("EXIT {exit_code}"), status_color)
-                }
+    let name_prefix = format!(" {}. ", process_index + 1);
+    let max_name_width = max_label_width.saturating_sub(name_prefix.chars().count() + 1);
+    let name_style = match process.accent_color() {
+        Some(accent_color) => style.fg(ratatui_color(accent_color)).bold(),
+        None => style,
+    };
+    text.push_line(Line::from(vec![
+        Span::styled(name_prefix, style),
+        Span::styled(format!("{} ", truncate_with_ellipsis(process.name(), max_name_width)), name_style),
+    ]));
+
+    let (status_str, status_color) = process_list_status_label(process, locale);
+    let status_style = Style::default()
+        .fg(status_color.color(theme))
+        .bg(style.bg.unwrap())
+        .bold();
+
+    text.push_line(Line::styled(format!("    {status_str}"), status_style));
+
+    if process.is_sandboxed() {
+        let sandbox_style = Style::default()
+            .fg(StatusColor::Muted.color(theme))
+            .bg(style.bg.unwrap());
+        text.push_line(Line::styled(format!("    {}", Message::Sandboxed.text(locale)), sandbox_style));
+    }
+
+    if let Some(resource_usage) = processes.resource_usage(process) {
+        let resource_usage_style = Style::default()
+            .fg(theme.muted)
+            .bg(style.bg.unwrap());
+        text.push_line(Line::styled(format!("    {}", resource_usage_label(resource_usage)), resource_usage_style));
+    }
+
+    if let Some(git_branch) = process_list_git_branch(processes, process) {
+        let git_branch_style = Style::default()
+            .fg(theme.muted)
+            .bg(style.bg.unwrap());
+        text.push_line(Line::styled(format!("    {}", git_branch_label(git_branch)), git_branch_style));
+    }
+
+    ListItem::new(text)
+}
+
+/// The git branch of a process if it's shown in the process list.
+fn process_list_git_branch<'a>(processes: &'a Processes, process: &Process) -> Option<&'a str> {
+    if processes.git_branches_in_process_list() {
+        processes.git_branch(process)
+    } else {
+        None
+    }
+}
+
+fn git_branch_label(git_branch: &str) -> String {
+    format!("⎇ {git_branch}")
+}
+
+/// The memory and CPU usage of a process, such as `1.2G 4%`.
+fn resource_usage_label(resource_usage: ResourceUsage) -> String {
+    const KIBIBYTE: f64 = 1024.0;
+    const MEBIBYTE: f64 = 1024.0 * KIBIBYTE;
+    const GIBIBYTE: f64 = 1024.0 * MEBIBYTE;
+
+    let memory_bytes = resource_usage.memory_bytes as f64;
+    let memory_str = if memory_bytes >= GIBIBYTE {
+        format!("{:.1}G", memory_bytes / GIBIBYTE)
+    } else if memory_bytes >= MEBIBYTE {
+        format!("{:.0}M", memory_bytes / MEBIBYTE)
+    } else {
+        format!("{:.0}K", memory_bytes / KIBIBYTE)
+    };
+    format!("{memory_str} {:.0}%", resource_usage.cpu_percent)
+}
+
+/// The status shown in the process list, along with the uptime and restart
+/// count of the process, or the countdown to the restart of the process when
+/// it's waiting to be restarted.
+fn process_list_status_label(process: &Process, locale: Locale) -> (String, StatusColor) {
+    let (mut status_str, status_color) = process_status_label(process.alerting_status(), locale);
+    if let Some(time_until_restart) = process.time_until_restart() {
+        let seconds = time_until_restart.as_secs_f64().ceil() as u64;
+        return (Message::StatusStartsIn { seconds }.text(locale), status_color);
+    }
+
+    let stats = process.stats();
+    if let Some(uptime) = stats.uptime {
+        status_str.push_str(&format!(" {}", format_uptime(uptime)));
+    }
+    if stats.restart_count > 0 {
+        status_str.push_str(&format!(" ×{}", stats.restart_count));
+    }
+    (status_str, status_color)
+}
+
+/// Format an uptime in its largest whole unit, such as `12m`.
+fn format_uptime(uptime: Duration) -> String {
+    let minutes = uptime.as_secs() / 60;
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else if minutes < 24 * 60 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}d", minutes / (24 * 60))
+    }
+}
+
+fn process_status_label(status: ProcessStatus, locale: Locale) -> (String, StatusColor) {
+    match status {
+        ProcessStatus::NotStarted => {
+            (Message::StatusInactive.text(locale), StatusColor::Muted)
+        },
+        ProcessStatus::WaitingForUpstream => {
+            (Message::StatusWaiting.text(locale), StatusColor::Muted)
+        },
+        ProcessStatus::Running => {
+            (Message::StatusRunning.text(locale), StatusColor::Muted)
+        },
+        ProcessStatus::Success => {
+            (Message::StatusSuccess.text(locale), StatusColor::Success)
+        }
+        ProcessStatus::Ready => {
+            (Message::StatusReady.text(locale), StatusColor::Success)
+        }
+        ProcessStatus::Errors { error_count } => {
+            let mut status_str = Message::StatusErrors.text(locale);
+
+            if let Some(error_count) = error_count {
+                let error_count_str = if error_count >= 100 {
+                    "99+".to_owned()
+                } else {
+                    format!("{error_count}")
+                };
+                status_str.push_str(&format!(" ({error_count_str})"));
+            }
+
+            (status_str, StatusColor::Failure)
+        },
+        ProcessStatus::Exited { exit_code, unexpected: true } => {
+            (Message::StatusExitedUnexpectedly { exit_code }.text(locale), StatusColor::Failure)
+        }
+        ProcessStatus::Exited { exit_code, unexpected: false } => {
+            let status_color = if exit_code == 0 {
+                StatusColor::Success
+            } else {
+                StatusColor::Failure
             };
-            let status_style = Style::default()
-                .fg(status_color)
-                .bg(style.bg.unwrap())
-                .bold();
+            (Message::StatusExited { exit_code }.text(locale), status_color)
+        }
+        ProcessStatus::StartFailed => {
+            (Message::StatusStartFailed.text(locale), StatusColor::Failure)
+        }
+    }
+}
 
-            text.push_line(Line::styled(format!("    {status_str}"), status_style));
+fn truncate_with_ellipsis(text: &str, max_width: usize) -> String {
+    if text.chars().count() <= max_width {
+        return text.to_owned();
+    }
 
-            ListItem::new(text)
-        })
+    let mut truncated: String = text.chars().take(max_width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
 }
 
-fn render_focus(processes: &Processes, area: Rect, frame: &mut Frame) {
+/// The mode that keys are handled in, which is always shown at the start of
+/// the status bar, since keys do different things in each mode.
+fn mode_indicator(processes: &Processes, locale: Locale, theme: &Theme) -> Span<'static> {
+    let is_typing = processes.search().is_some_and(|search| search.editing)
+        || processes.clone_changes().is_some()
+        || processes.checkpoint_name().is_some();
+    let is_in_menu = processes.upstream_picker().is_some() || processes.checkpoint_browser().is_some();
+    let scroll_offset = if processes.combined_log_focused() {
+        0
+    } else {
+        processes.focused_process().scroll_offset()
+    };
+
+    let (mode, background) = if is_typing {
+        (Message::ModeTyping, theme.mode_typing)
+    } else if is_in_menu {
+        (Message::ModeMenu, theme.mode_menu)
+    } else if scroll_offset > 0 {
+        (Message::ModeHistory { percentage: processes.focused_process().scroll_percentage() }, theme.mode_history)
+    } else {
+        (Message::ModeMain, theme.mode_main)
+    };
+    Span::styled(format!(" {} ", mode.text(locale)), Style::default().fg(theme.mode_text).bg(background).bold())
+}
+
+fn render_status_bar(processes: &Processes, config_path: &Path, process_list_label_width: usize, locale: Locale, theme: &Theme, area: Rect, frame: &mut Frame) {
     let focus_str = if processes.autofocus() {
-        "Auto"
+        Message::FocusAuto.text(locale)
     } else {
-        "Manual"
+        Message::FocusManual.text(locale)
     };
 
-    frame.render_widget(
-        Line::raw(format!("  Focus: {focus_str}")),
-        area,
-    );
+    let mut status_line = Line::from(vec![
+        mode_indicator(processes, locale, theme),
+        Span::raw(format!("  {}", Message::FocusLabel { focus: &focus_str }.text(locale))),
+    ]);
+    let mut extra_spans = Vec::new();
+
+    // When the name of the focused process is truncated in the process list,
+    // the full name is shown here instead.
+    let focused_process_name = processes.focused_process().name();
+    let focused_label_width = format!(" {}. {focused_process_name} ", processes.focused_process_index + 1).chars().count();
+    if focused_label_width > process_list_label_width && !processes.combined_log_focused() {
+        extra_spans.push(Span::raw(format!("    {}", Message::ProcessLabel { process_name: focused_process_name }.text(locale))));
+    }
+
+    if processes.privacy_mode() {
+        extra_spans.push(Span::styled(format!("    {}", Message::PrivacyMode.text(locale)), Style::default().bold()));
+    }
+
+    if processes.zoomed() {
+        extra_spans.push(Span::styled(format!("    {}", Message::Zoomed.text(locale)), Style::default().bold()));
+    }
+
+    if !processes.combined_log_focused() {
+        let stats = processes.focused_process().stats();
+        if let Some(uptime) = stats.uptime {
+            let uptime = format_uptime(uptime);
+            extra_spans.push(Span::raw(format!("    {}", Message::ProcessStats { uptime: &uptime, restart_count: stats.restart_count }.text(locale))));
+        }
+    }
+
+    let scroll_offset = processes.focused_process().scroll_offset();
+    if scroll_offset > 0 {
+        extra_spans.push(Span::raw(format!("    {}", Message::ScrolledBack { line_count: scroll_offset }.text(locale))));
+    }
+
+    if let Some(checkpoint_name) = processes.checkpoint_name() {
+        extra_spans.push(Span::styled(format!("    {}█", Message::CheckpointNamePrompt { name: checkpoint_name }.text(locale)), Style::default().bold()));
+    }
+
+    if let Some(clone_changes) = processes.clone_changes() {
+        let process_name = processes.focused_process().name();
+        extra_spans.push(Span::styled(format!("    {}█", Message::ClonePrompt { process_name, changes: clone_changes }.text(locale)), Style::default().bold()));
+    }
+
+    if let Some(search) = processes.search() {
+        if search.editing {
+            extra_spans.push(Span::styled(format!("    {}█", Message::SearchPrompt { query: &search.query }.text(locale)), Style::default().bold()));
+        } else {
+            extra_spans.push(Span::raw(format!("    {}", Message::SearchHint { query: &search.query }.text(locale))));
+        }
+    }
+
+    if let Some(status_message) = processes.status_message() {
+        extra_spans.push(Span::styled(format!("    {status_message}"), Style::default().bold()));
+    }
+
+    // The path of the config is the least important part of the status bar,
+    // so it's left out when there isn't room for everything, such as when the
+    // translated text is longer.
+    let config_span = Span::raw(format!("    {}", Message::ConfigLabel { path: &config_path.display().to_string() }.text(locale)));
+    let extra_width: usize = extra_spans.iter().map(Span::width).sum();
+    if status_line.width() + config_span.width() + extra_width <= area.width as usize {
+        status_line.push_span(config_span);
+    }
+    for extra_span in extra_spans {
+        status_line.push_span(extra_span);
+    }
+
+    frame.render_widget(Block::new().style(Style::default().bg(theme.status_bar)), area);
+    frame.render_widget(status_line, area);
+}
+
+fn render_quit_confirmation(busy_process_names: &[String], locale: Locale, area: Rect, frame: &mut Frame) {
+    let mut text = Text::default();
+    text.push_line(Line::styled(Message::QuitWillInterrupt.text(locale), Style::default().bold()));
+    text.push_line(Line::default());
+    for busy_process_name in busy_process_names {
+        text.push_line(Line::raw(format!("  {busy_process_name}")));
+    }
+    text.push_line(Line::default());
+    text.push_line(Line::raw(Message::QuitHint.text(locale)));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+}
+
+fn render_shutdown(shutdown: &Shutdown, locale: Locale, area: Rect, frame: &mut Frame) {
+    let mut text = Text::default();
+    text.push_line(Line::styled(Message::Stopping.text(locale), Style::default().bold()));
+    text.push_line(Line::default());
+    for stopping_process_name in shutdown.stopping_process_names() {
+        text.push_line(Line::raw(format!("  {stopping_process_name}")));
+    }
+    text.push_line(Line::default());
+    text.push_line(Line::raw(Message::StoppingHint.text(locale)));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+}
+
+fn render_waterfall(processes: &Processes, locale: Locale, theme: &Theme, area: Rect, frame: &mut Frame) {
+    let now = Instant::now();
+    let seconds_since_start = |instant: Instant| {
+        instant.saturating_duration_since(processes.started_at()).as_secs_f64()
+    };
+
+    let total_seconds = processes.processes()
+        .iter()
+        .filter_map(|process| {
+            let startup_timing = process.startup_timing();
+            startup_timing.started_at?;
+            Some(seconds_since_start(startup_timing.ready_at.unwrap_or(now)))
+        })
+        .fold(0.0, f64::max)
+        .max(1.0);
+
+    let name_width = processes.processes()
+        .iter()
+        .map(|process| process.name().chars().count())
+        .max()
+        .unwrap_or(0);
+    let description_width = 32;
+    let bar_width = (area.width as usize)
+        .saturating_sub(name_width + description_width + 4)
+        .max(10);
+    let bar_offset = |seconds: f64| ((seconds / total_seconds) * bar_width as f64).round() as usize;
+
+    let mut text = Text::default();
+    text.push_line(Line::styled(Message::StartupWaterfall.text(locale), Style::default().bold()));
+    text.push_line(Line::default());
+
+    for process in processes.processes() {
+        let startup_timing = process.startup_timing();
+        let mut line = Line::raw(format!("{:name_width$}  ", process.name()));
+
+        match startup_timing.started_at {
+            None => {
+                line.push_span(Span::styled(" ".repeat(bar_width), Style::default()));
+                line.push_span(Span::styled(format!("  {}", Message::NotStarted.text(locale)), Style::default().fg(theme.muted)));
+            },
+            Some(started_at) => {
+                let started_seconds = seconds_since_start(started_at);
+                let ready_seconds = startup_timing.ready_at.map(seconds_since_start);
+                let end_seconds = ready_seconds.unwrap_or_else(|| seconds_since_start(now));
+
+                let start_offset = bar_offset(started_seconds).min(bar_width - 1);
+                let end_offset = bar_offset(end_seconds).clamp(start_offset + 1, bar_width);
+                let bar_color = if ready_seconds.is_some() {
+                    theme.success
+                } else {
+                    theme.muted
+                };
+
+                line.push_span(Span::raw(" ".repeat(start_offset)));
+                line.push_span(Span::styled("█".repeat(end_offset - start_offset), Style::default().fg(bar_color)));
+                line.push_span(Span::raw(" ".repeat(bar_width - end_offset)));
+
+                let mut description = match ready_seconds {
+                    Some(ready_seconds) => format!(
+                        "  {}",
+                        Message::ReadyIn { started_seconds, ready_seconds: ready_seconds - started_seconds }.text(locale),
+                    ),
+                    None => format!("  {}", Message::NotReady { started_seconds }.text(locale)),
+                };
+                if let Some(after) = process.after() {
+                    description.push_str(&format!(" {}", Message::After { upstream: after }.text(locale)));
+                }
+                line.push_span(Span::raw(description));
+            },
+        }
+
+        text.push_line(line);
+    }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+}
+
+fn render_upstream_picker(processes: &Processes, locale: Locale, theme: &Theme, area: Rect, frame: &mut Frame) {
+    let Some(upstream_picker) = processes.upstream_picker() else {
+        return;
+    };
+    let process_name = processes.processes()[upstream_picker.process_index].name();
+
+    let mut text = Text::default();
+    text.push_line(Line::styled(Message::RunAfter { process_name }.text(locale), Style::default().bold()));
+    text.push_line(Line::default());
+
+    for (choice_index, choice) in upstream_picker.choices.iter().enumerate() {
+        let choice_str = match choice {
+            None => Message::NoUpstream.text(locale),
+            Some(upstream) => upstream.clone(),
+        };
+        if choice_index == upstream_picker.selected_index {
+            text.push_line(Line::styled(format!("> {choice_str}"), Style::default().reversed()));
+        } else {
+            text.push_line(Line::raw(format!("  {choice_str}")));
+        }
+    }
+
+    text.push_line(Line::default());
+    text.push_line(Line::styled(Message::ChooseOrCancel.text(locale), Style::default().fg(theme.muted)));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+}
+
+fn render_checkpoint_browser(processes: &Processes, locale: Locale, theme: &Theme, area: Rect, frame: &mut Frame) {
+    let Some(checkpoint_browser) = processes.checkpoint_browser() else {
+        return;
+    };
+    let checkpoint = &processes.checkpoints()[checkpoint_browser.selected_index];
+
+    let mut text = Text::default();
+
+    if checkpoint_browser.showing_diff {
+        let process = processes.focused_process();
+        text.push_line(Line::styled(
+            Message::ChangesSince { process_name: process.name(), checkpoint_name: &checkpoint.name }.text(locale),
+            Style::default().bold(),
+        ));
+        text.push_line(Line::default());
+
+        match checkpoint.screen(process.name()) {
+            None => text.push_line(Line::raw(Message::ProcessMissingFromCheckpoint.text(locale))),
+            Some(checkpoint_screen) => {
+                let current_screen = process.screen_text_lines();
+                for diff_line in checkpoints::diff_lines(checkpoint_screen, &current_screen) {
+                    text.push_line(match diff_line {
+                        DiffLine::Unchanged(line) => Line::raw(format!("  {line}")),
+                        DiffLine::Removed(line) => Line::styled(format!("- {line}"), Style::default().fg(theme.failure)),
+                        DiffLine::Added(line) => Line::styled(format!("+ {line}"), Style::default().fg(theme.success)),
+                    });
+                }
+            },
+        }
+
+        text.push_line(Line::default());
+        text.push_line(Line::styled(Message::Back.text(locale), Style::default().fg(theme.muted)));
+    } else {
+        text.push_line(Line::styled(Message::Checkpoints.text(locale), Style::default().bold()));
+        text.push_line(Line::default());
+
+        for (checkpoint_index, checkpoint) in processes.checkpoints().iter().enumerate() {
+            let seconds = checkpoint.created_at.saturating_duration_since(processes.started_at()).as_secs_f64();
+            let checkpoint_str = format!("{} (+{seconds:.1}s)", checkpoint.name);
+            if checkpoint_index == checkpoint_browser.selected_index {
+                text.push_line(Line::styled(format!("> {checkpoint_str}"), Style::default().reversed()));
+            } else {
+                text.push_line(Line::raw(format!("  {checkpoint_str}")));
+            }
+        }
+
+        text.push_line(Line::default());
+        text.push_line(Line::styled(
+            Message::CompareWithCheckpoint { process_name: processes.focused_process().name() }.text(locale),
+            Style::default().fg(theme.muted),
+        ));
+    }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+}
+
+fn render_event_log(processes: &Processes, locale: Locale, theme: &Theme, area: Rect, frame: &mut Frame) {
+    // Leave room for the borders and heading.
+    let max_event_count = (area.height as usize).saturating_sub(4);
+    let events = processes.event_log().events();
+    let skipped_event_count = events.len().saturating_sub(max_event_count);
+
+    let mut text = Text::default();
+    text.push_line(Line::styled(Message::EventLog.text(locale), Style::default().bold()));
+    text.push_line(Line::default());
+
+    for event in events.skip(skipped_event_count) {
+        let seconds = event.at.saturating_duration_since(processes.started_at()).as_secs_f64();
+        text.push_line(Line::from(vec![
+            Span::styled(format!("+{seconds:.1}s  "), Style::default().fg(theme.muted)),
+            Span::raw(event.message.as_str()),
+        ]));
+    }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+}
+
+fn render_config_source(processes: &Processes, locale: Locale, theme: &Theme, area: Rect, frame: &mut Frame) {
+    let process = processes.focused_process();
+
+    let mut text = Text::default();
+    text.push_line(Line::styled(Message::ConfigOf { process_name: process.name() }.text(locale), Style::default().bold()));
+
+    // The actual working directory is shown since it may differ from the
+    // config, such as when a wrapper script changes directory.
+    if let Some(working_directory) = process.working_directory() {
+        text.push_line(Line::styled(
+            Message::WorkingDirectory { path: &working_directory.display().to_string() }.text(locale),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    if let Some(git_branch) = processes.git_branch(process) {
+        text.push_line(Line::styled(
+            Message::GitBranch { branch: git_branch }.text(locale),
+            Style::default().fg(theme.muted),
+        ));
+    }
+
+    match process.config_source() {
+        None => {
+            text.push_line(Line::default());
+            text.push_line(Line::raw(Message::NotLoadedFromConfigFile.text(locale)));
+        },
+        Some(config_source) => {
+            text.push_line(Line::styled(
+                format!("{}:{}", config_source.path.display(), config_source.first_line_number),
+                Style::default().fg(theme.muted),
+            ));
+            text.push_line(Line::default());
+
+            for (line_index, line) in config_source.text.lines().enumerate() {
+                text.push_line(Line::from(vec![
+                    Span::styled(
+                        format!("{:>4}  ", config_source.first_line_number + line_index),
+                        Style::default().fg(theme.muted),
+                    ),
+                    Span::raw(line),
+                ]));
+            }
+        },
+    }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+}
+
+fn render_combined_log(processes: &Processes, redactor: &Redactor, locale: Locale, theme: &Theme, area: Rect, frame: &mut Frame) {
+    // Leave room for the borders and heading.
+    let max_line_count = (area.height as usize).saturating_sub(4);
+
+    let mut text = Text::default();
+    text.push_line(Line::styled(Message::AllProcesses.text(locale), Style::default().bold()));
+    text.push_line(Line::default());
+
+    for line in processes.combined_log().last_lines(max_line_count) {
+        let seconds = line.received_at.saturating_duration_since(processes.started_at()).as_secs_f64();
+        let line_text = if processes.privacy_mode() {
+            redactor.redact(&line.text)
+        } else {
+            line.text
+        };
+        text.push_line(Line::from(vec![
+            Span::styled(format!("+{seconds:.1}s  "), Style::default().fg(theme.muted)),
+            Span::styled(format!("{} | ", line.process_name), Style::default().bold()),
+            Span::raw(line_text),
+        ]));
+    }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+}
+
+fn render_pane_accent(processes: &Processes, area: Rect, frame: &mut Frame) {
+    let accent_color = if processes.combined_log_focused() {
+        None
+    } else {
+        processes.focused_process().accent_color()
+    };
+    if let Some(accent_color) = accent_color {
+        let block = Block::new()
+            .borders(Borders::LEFT)
+            .border_style(Style::default().fg(ratatui_color(accent_color)));
+        frame.render_widget(block, area);
+    }
+}
+
+fn ratatui_color(color: RgbColor) -> Color {
+    let (red, green, blue) = color.to_tuple_rgb8();
+    Color::Rgb(red, green, blue)
+}
+
+/// Render the border and name of the second process when the process pane is
+/// split, leaving the rest of the area for its output.
+fn render_split_pane(processes: &Processes, direction: SplitDirection, split_pane: &mut ProcessPane, area: Rect, frame: &mut Frame) {
+    let Some(split_process) = processes.split_process() else {
+        return;
+    };
+    let borders = match direction {
+        SplitDirection::SideBySide => Borders::LEFT,
+        SplitDirection::Stacked => Borders::TOP,
+    };
+    let block = Block::new()
+        .borders(borders)
+        .title(format!(" {} ", split_process.name()).bold());
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+    render_process_pane(split_pane, inner_area, frame);
 }
 
 fn render_process_pane(process_pane: &mut ProcessPane, area: Rect, frame: &mut Frame) {
@@ -0,0 +1,40 @@
+/// A choice of upstream process for a process, made while Mintaka is running
+/// rather than in the config.
+pub(crate) struct UpstreamPicker {
+    /// The index of the process whose upstream is being chosen.
+    pub(crate) process_index: usize,
+
+    /// The names of the processes that can be chosen as the upstream, where
+    /// `None` means that the process has no upstream.
+    pub(crate) choices: Vec<Option<String>>,
+
+    pub(crate) selected_index: usize,
+}
+
+impl UpstreamPicker {
+    pub(crate) fn new(process_index: usize, choices: Vec<Option<String>>, current_upstream: Option<&str>) -> Self {
+        let selected_index = choices.iter()
+            .position(|choice| choice.as_deref() == current_upstream)
+            .unwrap_or(0);
+
+        Self {
+            process_index,
+            choices,
+            selected_index,
+        }
+    }
+
+    pub(crate) fn move_selection_up(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+
+    pub(crate) fn move_selection_down(&mut self) {
+        if self.selected_index + 1 < self.choices.len() {
+            self.selected_index += 1;
+        }
+    }
+
+    pub(crate) fn selected_upstream(&self) -> Option<String> {
+        self.choices[self.selected_index].clone()
+    }
+}
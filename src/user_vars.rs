@@ -0,0 +1,68 @@
+use termwiz::{escape::{osc::ITermProprietary, OperatingSystemCommand}, surface::Change, terminal::Terminal};
+
+const USER_VAR_NAME: &str = "mintaka_status";
+
+/// Publishes the overall status of the processes so that it can be shown in
+/// the status line of a terminal multiplexer.
+///
+/// When running inside tmux, the status is set as the pane option
+/// `@mintaka_status`. Otherwise, the status is set as the wezterm (or iTerm2)
+/// user var `mintaka_status`.
+pub(crate) struct StatusUserVar {
+    last_value: Option<String>,
+}
+
+impl StatusUserVar {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_value: None,
+        }
+    }
+
+    pub(crate) fn update(&mut self, failure_count: usize, terminal: &mut impl Terminal) {
+        let value = if failure_count == 0 {
+            "ok".to_owned()
+        } else {
+            format!("{failure_count} failing")
+        };
+
+        if self.last_value.as_ref() == Some(&value) {
+            return;
+        }
+
+        if std::env::var_os("TMUX").is_some() {
+            set_tmux_pane_option(&value);
+        } else {
+            set_terminal_user_var(&value, terminal);
+        }
+
+        self.last_value = Some(value);
+    }
+}
+
+fn set_tmux_pane_option(value: &str) {
+    let mut command = std::process::Command::new("tmux");
+    command.arg("set-option").arg("-p").arg("-q");
+    if let Some(pane) = std::env::var_os("TMUX_PANE") {
+        command.arg("-t").arg(pane);
+    }
+    command
+        .arg(format!("@{USER_VAR_NAME}"))
+        .arg(value)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+
+    // The status is purely informational, so failing to set it (for instance,
+    // because tmux is too old to support pane options) isn't worth reporting.
+    let _ = command.status();
+}
+
+fn set_terminal_user_var(value: &str, terminal: &mut impl Terminal) {
+    let escape = OperatingSystemCommand::ITermProprietary(ITermProprietary::SetUserVar {
+        name: USER_VAR_NAME.to_owned(),
+        value: value.to_owned(),
+    });
+    let _ = terminal.render(&[Change::Text(escape.to_string())]);
+    let _ = terminal.flush();
+}
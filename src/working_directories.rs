@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+/// The current working directory of a running process, which may differ from
+/// the configured working directory if the process changed directory, such as
+/// a wrapper script that runs `cd` before `exec`.
+#[cfg(target_os = "linux")]
+pub(crate) fn working_directory_of(pid: u32) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/{pid}/cwd")).ok()
+}
+
+/// Other Unix platforms don't have `/proc`, so `lsof` is used instead, if it's
+/// installed.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) fn working_directory_of(pid: u32) -> Option<PathBuf> {
+    let output = std::process::Command::new("lsof")
+        .args(["-a", "-d", "cwd", "-F", "n", "-p"])
+        .arg(pid.to_string())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // Each field is on its own line, prefixed with the field's identifier.
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .map(PathBuf::from)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn working_directory_of(_pid: u32) -> Option<PathBuf> {
+    None
+}